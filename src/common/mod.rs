@@ -4,11 +4,16 @@
  * SPDX-License-Identifier: Apache-2.0 OR MIT
  */
 
+#[cfg(feature = "datetime")]
 use chrono::{FixedOffset, NaiveDate, NaiveDateTime};
 use mail_parser::DateTime;
+use std::ops::Range;
 
+pub mod color;
 pub mod iana;
 pub mod parser;
+pub mod reader;
+#[cfg(feature = "datetime")]
 pub mod timezone;
 pub mod tokenizer;
 pub mod types;
@@ -50,6 +55,15 @@ pub struct PartialDateTime {
     pub tz_minus: bool,
 }
 
+// Typed view of a UTC-OFFSET value, offset-only dates keep the seconds in `second`
+#[derive(Debug, Clone, Copy, Default)]
+pub struct UtcOffset {
+    pub minus: bool,
+    pub hours: u8,
+    pub minutes: u8,
+    pub seconds: u8,
+}
+
 #[derive(Debug, Clone, Default, PartialEq, Eq, PartialOrd, Ord, Hash)]
 #[cfg_attr(
     any(test, feature = "serde"),
@@ -91,6 +105,43 @@ pub struct Data {
     pub data: Vec<u8>,
 }
 
+#[derive(Debug, Clone, PartialEq)]
+pub struct GeoUri {
+    pub latitude: f64,
+    pub longitude: f64,
+    pub altitude: Option<f64>,
+    pub params: Option<String>,
+}
+
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct ParseDiagnostic {
+    pub line_num: usize,
+    pub line: String,
+    pub kind: ParseDiagnosticKind,
+}
+
+// Source location of a parsed entry, recorded by `Parser::with_spans`.
+// `component_id` is always 0 for vCards.
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct EntrySpan {
+    pub component_id: u32,
+    pub entry_id: u32,
+    pub span: Range<usize>,
+    pub line_num: usize,
+}
+
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub enum ParseDiagnosticKind {
+    UnknownProperty(String),
+    UnknownValueType(String),
+    InvalidValue,
+    MalformedDate,
+    MalformedDuration,
+    MalformedLine,
+    UnterminatedQuote,
+}
+
+#[cfg(feature = "datetime")]
 #[derive(Debug, Clone, PartialEq, Eq)]
 pub struct DateTimeResult {
     pub date_time: NaiveDateTime,
@@ -4,7 +4,7 @@
  * SPDX-License-Identifier: Apache-2.0 OR MIT
  */
 
-use super::{Data, PartialDateTime, tokenizer::Token};
+use super::{Data, GeoUri, PartialDateTime, UtcOffset, tokenizer::Token};
 use crate::{
     Parser,
     common::{IanaParse, IanaString, IanaType},
@@ -14,7 +14,7 @@ use mail_parser::{
     DateTime,
     decoders::{base64::base64_decode, hex::decode_hex},
 };
-use std::{iter::Peekable, slice::Iter, str::FromStr};
+use std::{borrow::Cow, iter::Peekable, slice::Iter, str::FromStr};
 
 impl<'x> Parser<'x> {
     pub(crate) fn raw_token(&mut self) -> Option<&'x str> {
@@ -59,6 +59,12 @@ impl Token<'_> {
     }
 
     pub(crate) fn into_offset(self) -> std::result::Result<PartialDateTime, String> {
+        if let Ok(text) = std::str::from_utf8(self.text.as_ref())
+            && let Some(offset) = UtcOffset::parse(text)
+        {
+            return Ok(offset.into());
+        }
+
         let mut dt = PartialDateTime::default();
         dt.parse_zone(&mut self.text.iter().peekable());
         if !dt.is_null() {
@@ -94,6 +100,10 @@ impl Token<'_> {
 }
 
 impl Data {
+    pub fn from_data_uri(uri: &str) -> Option<Self> {
+        Self::try_parse(uri.trim().as_bytes())
+    }
+
     pub fn try_parse(text: &[u8]) -> Option<Self> {
         if text
             .as_ref()
@@ -157,6 +167,47 @@ impl Data {
     }
 }
 
+impl GeoUri {
+    pub fn parse(uri: &str) -> Option<Self> {
+        let uri = uri.trim();
+        let coords = uri
+            .get(..4)
+            .filter(|prefix| prefix.eq_ignore_ascii_case("geo:"))
+            .and_then(|_| uri.get(4..))?;
+        let (coords, params) = match coords.split_once(';') {
+            Some((coords, params)) => (coords, Some(params.to_string())),
+            None => (coords, None),
+        };
+        let mut coords = coords.split(',').map(|v| v.trim().parse::<f64>());
+        let latitude = coords.next()?.ok()?;
+        let longitude = coords.next()?.ok()?;
+        let altitude = coords.next().transpose().ok()?;
+
+        (coords.next().is_none()
+            && (-90.0..=90.0).contains(&latitude)
+            && (-180.0..=180.0).contains(&longitude))
+        .then_some(GeoUri {
+            latitude,
+            longitude,
+            altitude,
+            params,
+        })
+    }
+
+    pub fn from_coordinates(latitude: f64, longitude: f64) -> Self {
+        GeoUri {
+            latitude,
+            longitude,
+            altitude: None,
+            params: None,
+        }
+    }
+
+    pub fn coordinates(&self) -> (f64, f64) {
+        (self.latitude, self.longitude)
+    }
+}
+
 impl PartialDateTime {
     pub fn parse_timestamp(
         &mut self,
@@ -245,6 +296,7 @@ impl PartialDateTime {
                         *value = Some(ch - b'0');
                     }
                 }
+                b':' if idx == 2 => {}
                 _ => {
                     if !ch.is_ascii_whitespace() {
                         return false;
@@ -267,6 +319,16 @@ impl PartialDateTime {
             && self.tz_minute.is_none()
     }
 
+    pub fn is_valid(&self) -> bool {
+        self.month.is_none_or(|month| (1..=12).contains(&month))
+            && self.day.is_none_or(|day| (1..=31).contains(&day))
+            && self.hour.is_none_or(|hour| hour <= 23)
+            && self.minute.is_none_or(|minute| minute <= 59)
+            && self.second.is_none_or(|second| second <= 60)
+            && self.tz_hour.is_none_or(|hour| hour <= 23)
+            && self.tz_minute.is_none_or(|minute| minute <= 59)
+    }
+
     #[inline(always)]
     pub fn has_date(&self) -> bool {
         self.year.is_some() && self.month.is_some() && self.day.is_some()
@@ -287,6 +349,16 @@ impl PartialDateTime {
         self.has_date() && self.has_time()
     }
 
+    #[inline(always)]
+    pub fn is_date_only(&self) -> bool {
+        self.has_date() && self.hour.is_none()
+    }
+
+    #[inline(always)]
+    pub fn is_utc(&self) -> bool {
+        self.tz_hour == Some(0) && self.tz_minute.unwrap_or(0) == 0
+    }
+
     fn to_datetime(&self) -> Option<DateTime> {
         if self.has_date() && self.has_time() {
             DateTime {
@@ -403,6 +475,29 @@ pub(crate) fn parse_small_digits(
     false
 }
 
+pub(crate) fn decode_text(bytes: &[u8]) -> Option<Cow<'_, str>> {
+    let decode_utf16 = |bytes: &[u8], from_bytes: fn([u8; 2]) -> u16| {
+        if !bytes.len().is_multiple_of(2) {
+            return None;
+        }
+        char::decode_utf16(
+            bytes
+                .chunks_exact(2)
+                .map(|chunk| from_bytes([chunk[0], chunk[1]])),
+        )
+        .collect::<Result<String, _>>()
+        .ok()
+        .map(Cow::Owned)
+    };
+
+    match bytes {
+        [0xEF, 0xBB, 0xBF, rest @ ..] => std::str::from_utf8(rest).ok().map(Cow::Borrowed),
+        [0xFF, 0xFE, rest @ ..] => decode_utf16(rest, u16::from_le_bytes),
+        [0xFE, 0xFF, rest @ ..] => decode_utf16(rest, u16::from_be_bytes),
+        _ => std::str::from_utf8(bytes).ok().map(Cow::Borrowed),
+    }
+}
+
 #[derive(Default)]
 pub(crate) struct Timestamp(pub i64);
 
@@ -573,4 +668,67 @@ mod tests {
             );
         }
     }
+
+    #[test]
+    fn test_parse_geo_uri() {
+        for (uri, expected) in [
+            (
+                "geo:37.386013,-122.082932",
+                Some(GeoUri::from_coordinates(37.386013, -122.082932)),
+            ),
+            (
+                "GEO:48.2010,16.3695,183;u=10",
+                Some(GeoUri {
+                    latitude: 48.201,
+                    longitude: 16.3695,
+                    altitude: Some(183.0),
+                    params: Some("u=10".to_string()),
+                }),
+            ),
+            ("geo:91,0", None),
+            ("geo:37.386013", None),
+            ("37.386013;-122.082932", None),
+        ] {
+            let geo = GeoUri::parse(uri);
+            assert_eq!(geo, expected, "{uri}");
+            if let Some(geo) = geo {
+                assert_eq!(GeoUri::parse(&geo.to_string()), Some(geo));
+            }
+        }
+        assert_eq!(
+            GeoUri::parse("geo:48.2010,16.3695,183;u=10")
+                .unwrap()
+                .to_string(),
+            "geo:48.201,16.3695,183;u=10"
+        );
+
+        let ical = crate::icalendar::ICalendar::parse(
+            "BEGIN:VCALENDAR\r\nBEGIN:VEVENT\r\nGEO:37.386013;-122.082932\r\nEND:VEVENT\r\nEND:VCALENDAR\r\n",
+        )
+        .unwrap();
+        assert_eq!(ical.components[1].geo(), Some((37.386013, -122.082932)));
+
+        for card in [
+            "BEGIN:VCARD\r\nVERSION:4.0\r\nGEO:geo:37.386013,-122.082932;u=10\r\nEND:VCARD\r\n",
+            "BEGIN:VCARD\r\nVERSION:3.0\r\nGEO:37.386013;-122.082932\r\nEND:VCARD\r\n",
+        ] {
+            assert_eq!(
+                crate::vcard::VCard::parse(card).unwrap().geo(),
+                Some((37.386013, -122.082932))
+            );
+        }
+    }
+
+    #[test]
+    #[cfg(feature = "datetime")]
+    fn test_now_utc() {
+        let now = PartialDateTime::now_utc();
+        let expected = std::time::SystemTime::now()
+            .duration_since(std::time::UNIX_EPOCH)
+            .unwrap()
+            .as_secs() as i64;
+        assert!(now.has_date_and_time());
+        assert!(now.is_utc());
+        assert!(now.to_timestamp().unwrap().abs_diff(expected) <= 5);
+    }
 }
@@ -0,0 +1,389 @@
+/*
+ * SPDX-FileCopyrightText: 2020 Stalwart Labs LLC <hello@stalw.art>
+ *
+ * SPDX-License-Identifier: Apache-2.0 OR MIT
+ */
+
+use crate::Parser;
+use std::{
+    collections::VecDeque,
+    io::{self, BufRead, BufReader, Read},
+    ops::Range,
+};
+
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct ContentLine {
+    pub line: String,
+    span: Range<usize>,
+    line_num: usize,
+}
+
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct ParamValue<'x> {
+    pub values: Vec<&'x str>,
+}
+
+pub struct ContentLineReader<R: Read> {
+    reader: BufReader<R>,
+    buf: Vec<u8>,
+    offset: usize,
+    line_num: usize,
+    preserve_trailing_empty_lines: bool,
+    latin1_fallback: bool,
+    empty_lines: VecDeque<ContentLine>,
+}
+
+impl Parser<'_> {
+    pub fn from_reader<R: Read>(reader: R) -> ContentLineReader<R> {
+        ContentLineReader {
+            reader: BufReader::with_capacity(4096, reader),
+            buf: Vec::with_capacity(128),
+            offset: 0,
+            line_num: 0,
+            preserve_trailing_empty_lines: false,
+            latin1_fallback: false,
+            empty_lines: VecDeque::new(),
+        }
+    }
+}
+
+impl<R: Read> ContentLineReader<R> {
+    pub fn preserve_trailing_empty_lines(mut self, preserve: bool) -> Self {
+        self.preserve_trailing_empty_lines = preserve;
+        self
+    }
+
+    // Decode lines that are not valid UTF-8 as ISO-8859-1 instead of failing
+    pub fn latin1_fallback(mut self, fallback: bool) -> Self {
+        self.latin1_fallback = fallback;
+        self
+    }
+
+    fn read_line(&mut self) -> io::Result<Option<ContentLine>> {
+        self.buf.clear();
+        let mut start = self.offset;
+        let mut end = self.offset;
+        let mut line_num = self.line_num + 1;
+
+        loop {
+            let bytes_read = self.reader.read_until(b'\n', &mut self.buf)?;
+            if bytes_read == 0 {
+                break;
+            }
+            self.offset += bytes_read;
+            self.line_num += 1;
+            end = self.offset;
+            if self.buf.last() == Some(&b'\n') {
+                self.buf.pop();
+                end -= 1;
+                if self.buf.last() == Some(&b'\r') {
+                    self.buf.pop();
+                    end -= 1;
+                }
+            }
+
+            // Unfold continuation lines, which may start at a buffer boundary
+            match self.reader.fill_buf()?.first() {
+                Some(b' ' | b'\t') => {
+                    self.reader.consume(1);
+                    self.offset += 1;
+                }
+                _ => {
+                    if self.buf.is_empty() {
+                        if self.preserve_trailing_empty_lines {
+                            self.empty_lines.push_back(ContentLine {
+                                line: String::new(),
+                                span: start..end,
+                                line_num,
+                            });
+                        }
+                        start = self.offset;
+                        line_num = self.line_num + 1;
+                        continue;
+                    }
+                    break;
+                }
+            }
+        }
+
+        if self.buf.is_empty() {
+            // Only empty lines at the end of the input are emitted
+            return Ok(self.empty_lines.pop_front());
+        }
+        self.empty_lines.clear();
+
+        let line = match String::from_utf8(std::mem::take(&mut self.buf)) {
+            Ok(line) => line,
+            Err(err) if self.latin1_fallback => {
+                // ISO-8859-1
+                err.as_bytes().iter().map(|&b| b as char).collect()
+            }
+            Err(err) => return Err(io::Error::new(io::ErrorKind::InvalidData, err)),
+        };
+
+        Ok(Some(ContentLine {
+            line,
+            span: start..end,
+            line_num,
+        }))
+    }
+}
+
+impl<R: Read> Iterator for ContentLineReader<R> {
+    type Item = io::Result<ContentLine>;
+
+    fn next(&mut self) -> Option<Self::Item> {
+        self.read_line().transpose()
+    }
+}
+
+impl ContentLine {
+    pub fn span(&self) -> Range<usize> {
+        self.span.clone()
+    }
+
+    pub fn line_num(&self) -> usize {
+        self.line_num
+    }
+
+    pub fn name(&self) -> &str {
+        let name = &self.line[..self.name_end()];
+        name.rsplit_once('.').map_or(name, |(_, name)| name)
+    }
+
+    pub fn group(&self) -> Option<&str> {
+        self.line[..self.name_end()]
+            .rsplit_once('.')
+            .map(|(group, _)| group)
+    }
+
+    pub fn value(&self) -> &str {
+        self.value_start()
+            .map_or("", |value_start| &self.line[value_start..])
+    }
+
+    pub fn params(&self) -> &str {
+        let params_end = self
+            .value_start()
+            .map_or(self.line.len(), |value_start| value_start - 1);
+        self.line
+            .get(self.name_end() + 1..params_end)
+            .unwrap_or_default()
+    }
+
+    pub fn parsed_params(&self) -> impl Iterator<Item = (&str, ParamValue<'_>)> {
+        split_unquoted(self.params(), ';')
+            .filter(|param| !param.is_empty())
+            .map(|param| match param.split_once('=') {
+                Some((name, value)) => (
+                    name,
+                    ParamValue {
+                        values: split_unquoted(value, ',').map(unquote).collect(),
+                    },
+                ),
+                // vCard 2.1 allows bare parameter values such as `;HOME`
+                None => (param, ParamValue { values: vec![] }),
+            })
+    }
+
+    fn name_end(&self) -> usize {
+        self.line.find([';', ':']).unwrap_or(self.line.len())
+    }
+
+    fn value_start(&self) -> Option<usize> {
+        let mut in_quote = false;
+        for (idx, ch) in self.line.char_indices() {
+            match ch {
+                '"' => in_quote = !in_quote,
+                ':' if !in_quote => return Some(idx + 1),
+                _ => {}
+            }
+        }
+        None
+    }
+}
+
+fn split_unquoted(text: &str, separator: char) -> impl Iterator<Item = &str> {
+    let mut rest = Some(text);
+    std::iter::from_fn(move || {
+        let text = rest?;
+        let mut in_quote = false;
+        for (idx, ch) in text.char_indices() {
+            match ch {
+                '"' => in_quote = !in_quote,
+                ch if ch == separator && !in_quote => {
+                    rest = Some(&text[idx + 1..]);
+                    return Some(&text[..idx]);
+                }
+                _ => {}
+            }
+        }
+        rest = None;
+        Some(text)
+    })
+}
+
+fn unquote(value: &str) -> &str {
+    value
+        .strip_prefix('"')
+        .and_then(|value| value.strip_suffix('"'))
+        .unwrap_or(value)
+}
+
+#[cfg(test)]
+mod tests {
+    use super::ParamValue;
+    use crate::Parser;
+    use std::io::Read;
+
+    struct ChunkedReader<'x> {
+        data: &'x [u8],
+        chunk: usize,
+    }
+
+    impl Read for ChunkedReader<'_> {
+        fn read(&mut self, buf: &mut [u8]) -> std::io::Result<usize> {
+            let len = self.data.len().min(self.chunk).min(buf.len());
+            buf[..len].copy_from_slice(&self.data[..len]);
+            self.data = &self.data[len..];
+            Ok(len)
+        }
+    }
+
+    #[test]
+    fn read_content_lines() {
+        let mut input = String::from("BEGIN:VCALENDAR\r\nBEGIN:VEVENT\r\n");
+        let prefix = "DESCRIPTION;LANGUAGE=en:";
+        let padding = 4096 - input.len() - prefix.len() - 2;
+        let description = "x".repeat(padding);
+        input.push_str(prefix);
+        input.push_str(&description);
+        input.push_str("\r\n continued\r\n\tand tabbed\r\n");
+        assert_eq!(input.find("\r\n continued").unwrap() + 2, 4096);
+        input.push_str("item1.X-NOTE;X-PARAM=\"a:b\":value\r\n\r\nEND:VEVENT\r\nEND:VCALENDAR\r\n");
+
+        let lines = Parser::from_reader(ChunkedReader {
+            data: input.as_bytes(),
+            chunk: 4096,
+        })
+        .collect::<std::io::Result<Vec<_>>>()
+        .unwrap();
+
+        assert_eq!(
+            lines.iter().map(|line| line.name()).collect::<Vec<_>>(),
+            ["BEGIN", "BEGIN", "DESCRIPTION", "X-NOTE", "END", "END"]
+        );
+        assert_eq!(
+            lines[2].value(),
+            format!("{description}continuedand tabbed")
+        );
+        assert_eq!(lines[3].group(), Some("item1"));
+        assert_eq!(lines[3].value(), "value");
+        assert_eq!(lines[5].value(), "VCALENDAR");
+
+        assert_eq!(
+            lines.iter().map(|line| line.line_num()).collect::<Vec<_>>(),
+            [1, 2, 3, 6, 8, 9]
+        );
+        assert_eq!(&input[lines[0].span()], "BEGIN:VCALENDAR");
+        assert_eq!(
+            &input[lines[2].span()],
+            format!("{prefix}{description}\r\n continued\r\n\tand tabbed")
+        );
+        assert_eq!(
+            &input[lines[3].span()],
+            "item1.X-NOTE;X-PARAM=\"a:b\":value"
+        );
+        assert_eq!(&input[lines[4].span()], "END:VEVENT");
+        assert_eq!(lines[5].span().end, input.len() - 2);
+    }
+
+    #[test]
+    fn parsed_params() {
+        let lines = Parser::from_reader(
+            concat!(
+                "ATTENDEE;CN=\"Doe; John: Jr\";MEMBER=\"mailto:a@example.com\",\"mailto:b@example.com\";\r\n",
+                " RSVP=TRUE;X-EMPTY=;HOME:mailto:c@example.com\r\n",
+                "SUMMARY:No params; here\r\n",
+            )
+            .as_bytes(),
+        )
+        .collect::<std::io::Result<Vec<_>>>()
+        .unwrap();
+        let values = |values: &[&'static str]| ParamValue {
+            values: values.to_vec(),
+        };
+
+        assert_eq!(lines[0].value(), "mailto:c@example.com");
+        assert_eq!(
+            lines[0].parsed_params().collect::<Vec<_>>(),
+            [
+                ("CN", values(&["Doe; John: Jr"])),
+                (
+                    "MEMBER",
+                    values(&["mailto:a@example.com", "mailto:b@example.com"])
+                ),
+                ("RSVP", values(&["TRUE"])),
+                ("X-EMPTY", values(&[""])),
+                ("HOME", values(&[])),
+            ]
+        );
+        assert_eq!(lines[1].params(), "");
+        assert_eq!(lines[1].parsed_params().count(), 0);
+        assert_eq!(lines[1].value(), "No params; here");
+    }
+
+    #[test]
+    fn preserve_trailing_empty_lines() {
+        let input = "BEGIN:VCARD\r\n\r\nEND:VCARD\r\n\r\n\n";
+        let lines = |preserve| {
+            Parser::from_reader(input.as_bytes())
+                .preserve_trailing_empty_lines(preserve)
+                .collect::<std::io::Result<Vec<_>>>()
+                .unwrap()
+        };
+
+        assert_eq!(
+            lines(false)
+                .iter()
+                .map(|line| line.line.as_str())
+                .collect::<Vec<_>>(),
+            ["BEGIN:VCARD", "END:VCARD"]
+        );
+
+        let lines = lines(true);
+        assert_eq!(
+            lines
+                .iter()
+                .map(|line| (line.line.as_str(), line.line_num(), line.span()))
+                .collect::<Vec<_>>(),
+            [
+                ("BEGIN:VCARD", 1, 0..11),
+                ("END:VCARD", 3, 15..24),
+                ("", 4, 26..26),
+                ("", 5, 28..28),
+            ]
+        );
+    }
+
+    #[test]
+    fn invalid_utf8() {
+        let input = b"BEGIN:VCARD\r\nFN:Jos\xe9\r\nEND:VCARD\r\n";
+
+        let mut lines = Parser::from_reader(&input[..]);
+        assert_eq!(lines.next().unwrap().unwrap().line, "BEGIN:VCARD");
+        assert_eq!(
+            lines.next().unwrap().unwrap_err().kind(),
+            std::io::ErrorKind::InvalidData
+        );
+        assert_eq!(lines.next().unwrap().unwrap().line, "END:VCARD");
+
+        assert_eq!(
+            Parser::from_reader(&input[..])
+                .latin1_fallback(true)
+                .map(|line| line.unwrap().line)
+                .collect::<Vec<_>>(),
+            ["BEGIN:VCARD", "FN:Jos\u{e9}", "END:VCARD"]
+        );
+    }
+}
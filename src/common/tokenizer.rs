@@ -4,7 +4,10 @@
  * SPDX-License-Identifier: Apache-2.0 OR MIT
  */
 
-use crate::{Parser, common::IanaParse};
+use crate::{
+    Parser,
+    common::{IanaParse, ParseDiagnosticKind},
+};
 use std::borrow::Cow;
 
 #[derive(Debug, PartialEq, Eq)]
@@ -35,6 +38,7 @@ impl<'x> Parser<'x> {
         self.unquote = true;
         self.stop_dot = false;
         self.skip_ws = true;
+        self.caret_decode = false;
     }
 
     pub(crate) fn expect_single_value(&mut self) {
@@ -46,6 +50,7 @@ impl<'x> Parser<'x> {
         self.unfold_qp = false;
         self.stop_dot = false;
         self.skip_ws = false;
+        self.caret_decode = false;
     }
 
     pub(crate) fn expect_multi_value_comma(&mut self) {
@@ -57,6 +62,7 @@ impl<'x> Parser<'x> {
         self.unfold_qp = false;
         self.stop_dot = false;
         self.skip_ws = true;
+        self.caret_decode = false;
     }
 
     pub(crate) fn expect_multi_value_semicolon(&mut self) {
@@ -68,6 +74,7 @@ impl<'x> Parser<'x> {
         self.unfold_qp = false;
         self.stop_dot = false;
         self.skip_ws = false;
+        self.caret_decode = false;
     }
 
     pub(crate) fn expect_multi_value_semicolon_and_comma(&mut self) {
@@ -79,6 +86,7 @@ impl<'x> Parser<'x> {
         self.unfold_qp = false;
         self.stop_dot = false;
         self.skip_ws = false;
+        self.caret_decode = false;
     }
 
     pub(crate) fn expect_param_value(&mut self) {
@@ -90,6 +98,7 @@ impl<'x> Parser<'x> {
         self.unquote = true;
         self.stop_dot = false;
         self.skip_ws = true;
+        self.caret_decode = true;
     }
 
     pub(crate) fn expect_rrule_value(&mut self) {
@@ -101,6 +110,36 @@ impl<'x> Parser<'x> {
         self.unfold_qp = false;
         self.stop_dot = false;
         self.skip_ws = true;
+        self.caret_decode = false;
+    }
+
+    #[inline]
+    fn is_plain_value(&self) -> bool {
+        !self.stop_colon
+            && !self.stop_semicolon
+            && !self.stop_comma
+            && !self.stop_equal
+            && !self.stop_dot
+            && !self.unquote
+            && !self.skip_ws
+            && !self.caret_decode
+    }
+
+    // Bytes that are not handled as plain text by the current token settings
+    #[inline]
+    fn is_delimiter(&self, ch: u8, in_quote: bool) -> bool {
+        match ch {
+            b'\r' | b'\n' | b'\\' => true,
+            b' ' | b'\t' => self.skip_ws && !in_quote,
+            b'^' => self.caret_decode,
+            b'"' => self.unquote,
+            b':' => self.stop_colon && !in_quote,
+            b';' => self.stop_semicolon && !in_quote,
+            b',' => self.stop_comma && !in_quote,
+            b'=' => self.stop_equal && !in_quote,
+            b'.' => self.stop_dot && !in_quote,
+            _ => false,
+        }
     }
 
     fn try_unfold(&mut self) -> bool {
@@ -205,6 +244,28 @@ impl<'x> Parser<'x> {
                     });
                     offset_end = next_offset_end;
                 }
+                b'^' if self.caret_decode
+                    && self
+                        .iter
+                        .peek()
+                        .is_some_and(|(_, ch)| matches!(ch, b'n' | b'N' | b'^' | b'\'')) =>
+                {
+                    // RFC 6868 parameter value encoding
+                    let (next_idx, next_ch) = self.iter.next().unwrap();
+                    if offset_start != usize::MAX {
+                        if buf.is_empty() {
+                            buf.extend_from_slice(&self.input[offset_start..=offset_end]);
+                        }
+                    } else {
+                        offset_start = idx;
+                    }
+                    buf.push(match *next_ch {
+                        b'n' | b'N' => b'\n',
+                        b'\'' => b'"',
+                        _ => b'^',
+                    });
+                    offset_end = next_idx;
+                }
                 b'"' if self.unquote => {
                     in_quote = !in_quote;
                 }
@@ -237,10 +298,33 @@ impl<'x> Parser<'x> {
                     if !buf.is_empty() {
                         buf.push(*ch);
                     }
+
+                    // Consume the run of bytes that would end up in this arm at once,
+                    // single values only react to line breaks and escapes
+                    let run = &self.input[idx + 1..];
+                    let len = if self.is_plain_value() {
+                        plain_run_len(run)
+                    } else {
+                        run.iter()
+                            .position(|ch| self.is_delimiter(*ch, in_quote))
+                            .unwrap_or(run.len())
+                    };
+                    if len > 0 {
+                        self.iter.nth(len - 1);
+                        offset_end = idx + len;
+                        last_idx = offset_end;
+                        if !buf.is_empty() {
+                            buf.extend_from_slice(&run[..len]);
+                        }
+                    }
                 }
             }
         }
 
+        if in_quote && self.diagnostics.is_some() {
+            self.add_diagnostic(last_idx, ParseDiagnosticKind::UnterminatedQuote);
+        }
+
         if buf.is_empty() {
             if offset_start != usize::MAX {
                 Some(Token {
@@ -358,6 +442,21 @@ impl<'x> Token<'x> {
     }
 }
 
+#[inline]
+fn plain_run_len(bytes: &[u8]) -> usize {
+    #[cfg(feature = "memchr")]
+    {
+        memchr::memchr3(b'\r', b'\n', b'\\', bytes).unwrap_or(bytes.len())
+    }
+    #[cfg(not(feature = "memchr"))]
+    {
+        bytes
+            .iter()
+            .position(|ch| matches!(ch, b'\r' | b'\n' | b'\\'))
+            .unwrap_or(bytes.len())
+    }
+}
+
 #[cfg(test)]
 mod tests {
     use super::*;
@@ -368,6 +467,62 @@ mod tests {
         Owned(String),
     }
 
+    #[test]
+    fn test_single_value_runs() {
+        for (input, expected) in [
+            (
+                "Plain text: with; stops, \"quotes\" and ^n",
+                "Plain text: with; stops, \"quotes\" and ^n",
+            ),
+            ("  padded\t \r\nNEXT", "  padded\t "),
+            (
+                "Escaped\\, comma and \\n newline",
+                "Escaped, comma and \n newline",
+            ),
+            ("Folded\r\n  line\r\n\tagain\nNEXT", "Folded lineagain"),
+            ("Mixed\\;\r\n tail ünïcödé", "Mixed;tail ünïcödé"),
+            ("\\\\", "\\"),
+        ] {
+            let mut parser = Parser::new(input);
+            parser.expect_single_value();
+            let token = parser.token().unwrap();
+            assert_eq!(
+                std::str::from_utf8(&token.text).unwrap(),
+                expected,
+                "failed for {input:?}"
+            );
+            assert_eq!(token.stop_char, StopChar::Lf);
+            assert_eq!(token.start, input.find(|ch| ch != '\\').unwrap_or(1));
+        }
+    }
+
+    #[test]
+    fn test_delimited_runs() {
+        let mut parser = Parser::new("X-NAME.b;Param=\"http://a.b/c:d;e\",x^'y^',  padded  :rest");
+        parser.expect_iana_token();
+        parser.stop_dot = true;
+        let mut tokens = vec![];
+        while let Some(token) = parser.token() {
+            let stop_char = token.stop_char;
+            tokens.push((Token::new(token.text).into_string(), stop_char));
+            if stop_char == StopChar::Equal {
+                parser.expect_param_value();
+            }
+        }
+        assert_eq!(
+            tokens,
+            [
+                ("X-NAME".to_string(), StopChar::Dot),
+                ("b".to_string(), StopChar::Semicolon),
+                ("Param".to_string(), StopChar::Equal),
+                ("http://a.b/c:d;e".to_string(), StopChar::Comma),
+                ("x\"y\"".to_string(), StopChar::Comma),
+                ("padded".to_string(), StopChar::Colon),
+                ("rest".to_string(), StopChar::Lf),
+            ]
+        );
+    }
+
     #[test]
     fn test_tokenizer() {
         for (input, expected, disable_stop) in [
@@ -68,10 +68,6 @@ impl Encoding {
 }
 
 impl PartialDateTime {
-    pub fn now() -> Self {
-        Self::from_utc_timestamp(chrono::Utc::now().timestamp())
-    }
-
     pub fn from_utc_timestamp(value: i64) -> Self {
         let dt = DateTime::from_timestamp(value);
 
@@ -101,6 +97,129 @@ impl PartialDateTime {
             ..Default::default()
         }
     }
+}
+
+impl PartialDateTime {
+    pub fn utc_offset(&self) -> Option<UtcOffset> {
+        Some(UtcOffset {
+            minus: self.tz_minus,
+            hours: self.tz_hour?,
+            minutes: self.tz_minute.unwrap_or_default(),
+            seconds: if self.hour.is_none() && self.minute.is_none() {
+                self.second.unwrap_or_default()
+            } else {
+                0
+            },
+        })
+    }
+}
+
+impl UtcOffset {
+    pub fn parse(value: &str) -> Option<Self> {
+        let value = value.trim();
+        if value.eq_ignore_ascii_case("z") {
+            return Some(UtcOffset::default());
+        }
+        let (minus, digits) = match value.as_bytes().first()? {
+            b'-' => (true, &value[1..]),
+            b'+' => (false, &value[1..]),
+            _ => return None,
+        };
+        let digits = digits.replace(':', "");
+        if !matches!(digits.len(), 2 | 4 | 6) || !digits.bytes().all(|ch| ch.is_ascii_digit()) {
+            return None;
+        }
+        let field = |idx: usize| digits.get(idx..idx + 2).map_or(Some(0), |v| v.parse().ok());
+        let offset = UtcOffset {
+            minus,
+            hours: field(0)?,
+            minutes: field(2)?,
+            seconds: field(4)?,
+        };
+        (offset.hours <= 23 && offset.minutes <= 59 && offset.seconds <= 59).then_some(offset)
+    }
+
+    pub fn to_seconds(&self) -> i32 {
+        let seconds = self.hours as i32 * 3600 + self.minutes as i32 * 60 + self.seconds as i32;
+        if self.minus { -seconds } else { seconds }
+    }
+}
+
+// +0000 and -0000 describe the same offset
+impl PartialEq for UtcOffset {
+    fn eq(&self, other: &Self) -> bool {
+        self.to_seconds() == other.to_seconds()
+    }
+}
+
+impl Eq for UtcOffset {}
+
+impl std::hash::Hash for UtcOffset {
+    fn hash<H: std::hash::Hasher>(&self, state: &mut H) {
+        self.to_seconds().hash(state);
+    }
+}
+
+impl std::fmt::Display for UtcOffset {
+    fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+        write!(
+            f,
+            "{}{:02}{:02}",
+            if self.minus { "-" } else { "+" },
+            self.hours,
+            self.minutes
+        )?;
+        if self.seconds != 0 {
+            write!(f, "{:02}", self.seconds)?;
+        }
+        Ok(())
+    }
+}
+
+impl From<UtcOffset> for PartialDateTime {
+    fn from(offset: UtcOffset) -> Self {
+        PartialDateTime {
+            second: (offset.seconds != 0).then_some(offset.seconds),
+            tz_hour: Some(offset.hours),
+            tz_minute: Some(offset.minutes),
+            tz_minus: offset.minus,
+            ..Default::default()
+        }
+    }
+}
+
+#[cfg(feature = "datetime")]
+impl UtcOffset {
+    pub fn as_fixed_offset(&self) -> Option<FixedOffset> {
+        FixedOffset::east_opt(self.to_seconds())
+    }
+}
+
+#[cfg(feature = "datetime")]
+impl PartialDateTime {
+    pub fn now_utc() -> Self {
+        Self::from_utc_timestamp(chrono::Utc::now().timestamp())
+    }
+
+    pub fn from_chrono<T: chrono::TimeZone>(dt: &chrono::DateTime<T>) -> Self {
+        use chrono::{Datelike, Offset, Timelike};
+
+        let local = dt.naive_local();
+        let offset = dt.offset().fix().local_minus_utc();
+        let offset_abs = offset.unsigned_abs();
+
+        PartialDateTime {
+            year: u16::try_from(local.year()).ok(),
+            month: Some(local.month() as u8),
+            day: Some(local.day() as u8),
+            hour: Some(local.hour() as u8),
+            minute: Some(local.minute() as u8),
+            second: Some(local.second() as u8),
+            tz_hour: Some((offset_abs / 3600) as u8),
+            tz_minute: Some(((offset_abs % 3600) / 60) as u8),
+            tz_minus: offset < 0,
+        }
+    }
 
     pub fn to_date_time(&self) -> Option<DateTimeResult> {
         let mut dt = DateTimeResult {
@@ -116,20 +235,18 @@ impl PartialDateTime {
             )?,
             offset: None,
         };
-        if let Some(tz_hour) = self.tz_hour {
-            let secs = (tz_hour as i32 * 3600) + (self.tz_minute.unwrap_or(0) as i32 * 60);
-            dt.offset = if self.tz_minus {
-                FixedOffset::west_opt(secs)?
-            } else {
-                FixedOffset::east_opt(secs)?
-            }
-            .into();
+        if self.tz_hour.is_some() {
+            dt.offset = self.to_fixed_offset()?.into();
         }
         Some(dt)
     }
+
+    pub fn to_fixed_offset(&self) -> Option<FixedOffset> {
+        self.utc_offset()?.as_fixed_offset()
+    }
 }
 
-#[cfg(feature = "rkyv")]
+#[cfg(all(feature = "rkyv", feature = "datetime"))]
 impl ArchivedPartialDateTime {
     pub fn to_date_time(&self) -> Option<DateTimeResult> {
         let mut dt = DateTimeResult {
@@ -6,54 +6,122 @@
 
 use crate::vcard::Jscomp;
 
-use super::parser::Timestamp;
+use super::{GeoUri, parser::Timestamp};
 use mail_builder::encoders::base64::*;
 use mail_parser::DateTime;
-use std::fmt::{Display, Write};
+use std::{
+    borrow::Cow,
+    fmt::{Display, Write},
+    io,
+};
+
+pub(crate) const DEFAULT_FOLD_WIDTH: usize = 75;
+
+#[derive(Debug, Clone)]
+pub struct WriterOptions {
+    pub canonical_param_case: bool,
+    pub fold_width: Option<usize>,
+    // Replaces any PRODID in the iCalendar or vCard being written
+    pub prodid: Option<Cow<'static, str>>,
+    // iCalendar only, vCards always start with VERSION
+    pub force_version: bool,
+}
+
+impl Default for WriterOptions {
+    fn default() -> Self {
+        Self {
+            canonical_param_case: false,
+            fold_width: Some(DEFAULT_FOLD_WIDTH),
+            prodid: None,
+            force_version: false,
+        }
+    }
+}
+
+impl WriterOptions {
+    pub fn canonical_param_case(mut self, canonical: bool) -> Self {
+        self.canonical_param_case = canonical;
+        self
+    }
+
+    pub fn fold_width(mut self, fold_width: Option<usize>) -> Self {
+        self.fold_width = fold_width;
+        self
+    }
+
+    pub fn prodid(mut self, prodid: impl Into<Cow<'static, str>>) -> Self {
+        self.prodid = Some(prodid.into());
+        self
+    }
+
+    pub fn force_version(mut self, force: bool) -> Self {
+        self.force_version = force;
+        self
+    }
+
+    pub(crate) fn max_line_len(&self) -> usize {
+        self.fold_width.unwrap_or(usize::MAX)
+    }
+}
+
+pub(crate) struct IoWriter<'x, W: io::Write> {
+    inner: &'x mut W,
+    error: Option<io::Error>,
+}
+
+impl<W: io::Write> Write for IoWriter<'_, W> {
+    fn write_str(&mut self, s: &str) -> std::fmt::Result {
+        self.inner.write_all(s.as_bytes()).map_err(|err| {
+            self.error = Some(err);
+            std::fmt::Error
+        })
+    }
+}
+
+pub(crate) fn write_io<W: io::Write>(
+    inner: &mut W,
+    f: impl FnOnce(&mut IoWriter<'_, W>) -> std::fmt::Result,
+) -> io::Result<()> {
+    let mut writer = IoWriter { inner, error: None };
+    f(&mut writer).map_err(|_| {
+        writer
+            .error
+            .take()
+            .unwrap_or_else(|| io::Error::other("formatting error"))
+    })
+}
 
 pub(crate) fn write_text(
     out: &mut impl Write,
     line_len: &mut usize,
+    fold_width: usize,
     value: &str,
     escape_semicolon: bool,
     escape_comma: bool,
 ) -> std::fmt::Result {
     for ch in value.chars() {
-        let ch_len = ch.len_utf8();
-        if *line_len + ch_len > 75 {
+        let escaped = match ch {
+            '\r' => Some('r'),
+            '\n' => Some('n'),
+            '\\' => Some('\\'),
+            ';' if escape_semicolon => Some(';'),
+            ',' if escape_comma => Some(','),
+            _ => None,
+        };
+
+        // Escape sequences count towards the line length and are never split
+        let ch_len = if escaped.is_some() { 2 } else { ch.len_utf8() };
+        if *line_len + ch_len > fold_width {
             write!(out, "\r\n ")?;
             *line_len = 1;
         }
 
-        match ch {
-            '\r' => {
-                write!(out, "\\r")?;
-                *line_len += 2;
-                continue;
-            }
-            '\n' => {
-                write!(out, "\\n")?;
-                *line_len += 2;
-                continue;
-            }
-            '\\' => {
-                write!(out, "\\")?;
-                *line_len += 2;
-            }
-            ';' if escape_semicolon => {
-                write!(out, "\\")?;
-                *line_len += 2;
-            }
-            ',' if escape_comma => {
-                write!(out, "\\")?;
-                *line_len += 2;
-            }
-            _ => {
-                *line_len += ch.len_utf8();
-            }
+        if let Some(escaped) = escaped {
+            write!(out, "\\{escaped}")?;
+        } else {
+            write!(out, "{ch}")?;
         }
-
-        write!(out, "{ch}")?;
+        *line_len += ch_len;
     }
 
     Ok(())
@@ -62,6 +130,7 @@ pub(crate) fn write_text(
 pub(crate) fn write_bytes(
     out: &mut impl Write,
     mut line_len: Option<&mut usize>,
+    fold_width: usize,
     value: &[u8],
 ) -> std::fmt::Result {
     const CHARPAD: u8 = b'=';
@@ -84,7 +153,7 @@ pub(crate) fn write_bytes(
                 E2[t3 as usize],
             ] {
                 if let Some(line_len) = line_len.as_deref_mut() {
-                    if *line_len + 1 > 75 {
+                    if *line_len + 1 > fold_width {
                         write!(out, "\r\n ")?;
                         *line_len = 1;
                     }
@@ -119,7 +188,7 @@ pub(crate) fn write_bytes(
 
         for ch in chs.iter() {
             if let Some(line_len) = line_len.as_deref_mut() {
-                if *line_len + 1 > 75 {
+                if *line_len + 1 > fold_width {
                     write!(out, "\r\n ")?;
                     *line_len = 1;
                 }
@@ -148,6 +217,7 @@ impl<T: AsRef<[u8]>> NeedsQuotes for T {
 pub(crate) fn write_param_value(
     out: &mut impl Write,
     line_len: &mut usize,
+    fold_width: usize,
     value: &str,
 ) -> std::fmt::Result {
     let needs_quotes = value.needs_quotes();
@@ -160,7 +230,7 @@ pub(crate) fn write_param_value(
     for ch in value.chars() {
         match ch as u32 {
             0x0A => {
-                write!(out, "\\n")?;
+                write!(out, "^n")?;
                 *line_len += 2;
             }
             0x0D => {
@@ -172,12 +242,16 @@ pub(crate) fn write_param_value(
                 *line_len += 2;
             }
             0x22 => {
-                write!(out, "\\\"")?;
+                write!(out, "^'")?;
+                *line_len += 2;
+            }
+            0x5E => {
+                write!(out, "^^")?;
                 *line_len += 2;
             }
-            0x20 | 0x09 | 0x21 | 0x23..=0x7E | 0x80.. => {
+            0x20 | 0x09 | 0x21 | 0x23..=0x5D | 0x5F..=0x7E | 0x80.. => {
                 let ch_len = ch.len_utf8();
-                if *line_len + ch_len > 75 {
+                if *line_len + ch_len > fold_width {
                     write!(out, "\r\n ")?;
                     *line_len = 1;
                 }
@@ -288,3 +362,98 @@ impl Display for Timestamp {
         )
     }
 }
+
+impl Display for GeoUri {
+    fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+        write!(f, "geo:{},{}", self.latitude, self.longitude)?;
+        if let Some(altitude) = self.altitude {
+            write!(f, ",{altitude}")?;
+        }
+        if let Some(params) = &self.params {
+            write!(f, ";{params}")?;
+        }
+        Ok(())
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use crate::{icalendar::ICalendar, vcard::VCard};
+    use std::io;
+
+    struct FailingWriter;
+
+    impl io::Write for FailingWriter {
+        fn write(&mut self, _: &[u8]) -> io::Result<usize> {
+            Err(io::Error::new(io::ErrorKind::BrokenPipe, "closed"))
+        }
+
+        fn flush(&mut self) -> io::Result<()> {
+            Ok(())
+        }
+    }
+
+    #[test]
+    fn write_to_io() {
+        let ical =
+            ICalendar::parse(std::fs::read_to_string("resources/ical/007.ics").unwrap()).unwrap();
+        let mut out = Vec::new();
+        ical.write_to_io(&mut out).unwrap();
+        assert_eq!(String::from_utf8(out).unwrap(), ical.to_string());
+
+        let vcard = VCard::parse(concat!(
+            "BEGIN:VCARD\r\n",
+            "VERSION:4.0\r\n",
+            "FN:Jane Doe\r\n",
+            "NOTE:a long note that needs to be folded because it is longer than seventy five octets\r\n",
+            "END:VCARD\r\n"
+        ))
+        .unwrap();
+        let mut out = Vec::new();
+        vcard.write_to_io(&mut out).unwrap();
+        let out = String::from_utf8(out).unwrap();
+        assert_eq!(out, vcard.to_string());
+        assert!(out.contains("\r\n "));
+
+        assert_eq!(
+            ical.write_to_io(&mut FailingWriter).unwrap_err().kind(),
+            io::ErrorKind::BrokenPipe
+        );
+    }
+
+    #[test]
+    fn caret_encoding() {
+        let ical = ICalendar::parse(concat!(
+            "BEGIN:VCALENDAR\r\n",
+            "BEGIN:VEVENT\r\n",
+            "ATTENDEE;CN=\"Foo ^' Bar\";X-NOTE=a^nb^^c^x:mailto:foo@example.com\r\n",
+            "END:VEVENT\r\n",
+            "END:VCALENDAR\r\n"
+        ))
+        .unwrap();
+        let entry = &ical.components[1].entries[0];
+        assert_eq!(entry.params[0].value.as_text(), Some("Foo \" Bar"));
+        assert_eq!(entry.params[1].value.as_text(), Some("a\nb^c^x"));
+        assert!(
+            ical.to_string()
+                .contains("ATTENDEE;CN=\"Foo ^' Bar\";X-NOTE=a^nb^^c^^x:mailto:foo@example.com")
+        );
+
+        let vcard = VCard::parse(concat!(
+            "BEGIN:VCARD\r\n",
+            "VERSION:4.0\r\n",
+            "ADR;LABEL=\"^'1 Main St^'^nSpringfield\":;;1 Main St;Springfield;;;\r\n",
+            "END:VCARD\r\n"
+        ))
+        .unwrap();
+        assert_eq!(
+            vcard.entries[1].params[0].value.as_text(),
+            Some("\"1 Main St\"\nSpringfield")
+        );
+        assert!(
+            vcard
+                .to_string()
+                .contains("ADR;LABEL=\"^'1 Main St^'^nSpringfield\":")
+        );
+    }
+}
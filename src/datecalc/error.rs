@@ -46,6 +46,10 @@ pub enum ValidationError {
         until: String,
         dt_start: String,
     },
+    CountWithUntil {
+        count: u32,
+        until: String,
+    },
     TooBigInterval(u16),
     StartYearOutOfRange(i32),
     UnableToGenerateTimeset,
@@ -148,6 +152,10 @@ impl Display for ValidationError {
                 f,
                 "Until date `{until}` is before the start date `{dt_start}`"
             ),
+            ValidationError::CountWithUntil { count, until } => write!(
+                f,
+                "COUNT `{count}` and UNTIL `{until}` cannot be used together"
+            ),
             ValidationError::TooBigInterval(interval) => write!(
                 f,
                 "Interval of {interval} is too big. The maximum interval is 32767."
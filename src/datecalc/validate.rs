@@ -5,10 +5,13 @@
  */
 
 use super::{
-    error::ValidationError,
+    error::{RRuleError, ValidationError},
     rrule::{NWeekday, RRule},
 };
-use crate::{common::timezone::Tz, icalendar::ICalendarFrequency};
+use crate::{
+    common::timezone::Tz,
+    icalendar::{ICalendarFrequency, ICalendarRecurrenceRule, ICalendarValueType},
+};
 use std::{borrow::Cow, ops::RangeInclusive};
 
 pub(crate) static MONTH_RANGE: RangeInclusive<u8> = 1..=12;
@@ -314,6 +317,103 @@ fn validate_by_easter(
     Ok(())
 }
 
+impl ICalendarRecurrenceRule {
+    // Rejects rules that RFC 5545 forbids, regardless of the start date
+    pub fn validate(&self) -> Result<(), RRuleError> {
+        if let (Some(count), Some(until)) = (self.count, &self.until) {
+            let mut value = String::new();
+            let _ = until.format_as_ical(
+                &mut value,
+                if until.has_date_and_time() {
+                    &ICalendarValueType::DateTime
+                } else {
+                    &ICalendarValueType::Date
+                },
+            );
+            return Err(ValidationError::CountWithUntil {
+                count,
+                until: value,
+            }
+            .into());
+        }
+
+        validate_range_for_vec(&(0..=60), &self.bysecond, "BYSECOND")?;
+        validate_range_for_vec(&(0..=59), &self.byminute, "BYMINUTE")?;
+        validate_range_for_vec(&(0..=23), &self.byhour, "BYHOUR")?;
+        for month in &self.bymonth {
+            if !MONTH_RANGE.contains(&month.month()) {
+                return Err(ValidationError::InvalidFieldValueRange {
+                    field: "BYMONTH".into(),
+                    value: month.to_string(),
+                    start_idx: 1.to_string(),
+                    end_idx: 12.to_string(),
+                }
+                .into());
+            }
+        }
+        for (field, values) in [
+            ("BYMONTHDAY", &self.bymonthday),
+            ("BYWEEKNO", &self.byweekno),
+        ] {
+            validate_not_equal_for_vec(&0, values, field)?;
+        }
+        validate_range_for_vec(&(-31..=31), &self.bymonthday, "BYMONTHDAY")?;
+        validate_range_for_vec(&(-53..=53), &self.byweekno, "BYWEEKNO")?;
+        validate_not_equal_for_vec(&0, &self.byyearday, "BYYEARDAY")?;
+        validate_range_for_vec(&(-366..=366), &self.byyearday, "BYYEARDAY")?;
+        validate_not_equal_for_vec(&0, &self.bysetpos, "BYSETPOS")?;
+        validate_range_for_vec(&(-366..=366), &self.bysetpos, "BYSETPOS")?;
+
+        let invalid_freq = |by_rule: &str, values: usize, valid: bool| {
+            if values > 0 && !valid {
+                Err(ValidationError::InvalidByRuleAndFrequency {
+                    by_rule: by_rule.into(),
+                    freq: self.freq,
+                })
+            } else {
+                Ok(())
+            }
+        };
+        invalid_freq(
+            "BYMONTHDAY",
+            self.bymonthday.len(),
+            self.freq != ICalendarFrequency::Weekly,
+        )?;
+        invalid_freq(
+            "BYYEARDAY",
+            self.byyearday.len(),
+            !matches!(
+                self.freq,
+                ICalendarFrequency::Monthly
+                    | ICalendarFrequency::Weekly
+                    | ICalendarFrequency::Daily
+            ),
+        )?;
+        invalid_freq(
+            "BYWEEKNO",
+            self.byweekno.len(),
+            self.freq == ICalendarFrequency::Yearly,
+        )?;
+
+        // Ordinal weekdays only make sense within a month or a year
+        if let Some(day) = self.byday.iter().find(|day| {
+            day.ordwk.is_some()
+                && (!matches!(
+                    self.freq,
+                    ICalendarFrequency::Monthly | ICalendarFrequency::Yearly
+                ) || !self.byweekno.is_empty())
+        }) {
+            return Err(ValidationError::InvalidFieldValue {
+                field: "BYDAY".into(),
+                value: day.to_string(),
+            }
+            .into());
+        }
+
+        Ok(())
+    }
+}
+
 fn validate_range_for_vec_error<'a, T: PartialOrd>(
     range: &RangeInclusive<T>,
     list: &'a [T],
@@ -363,7 +463,7 @@ fn validate_not_equal_for_vec<T: PartialEq<T> + ToString>(
 #[cfg(test)]
 mod tests {
     use super::*;
-    use crate::icalendar::ICalendarFrequency;
+    use crate::{Parser, icalendar::ICalendarFrequency};
     use chrono::TimeZone;
 
     const UTC: Tz = Tz::UTC;
@@ -644,4 +744,64 @@ mod tests {
             ));
         }
     }
+
+    #[test]
+    fn validates_ical_recurrence_rules() {
+        let rule = |value: &str| Parser::new(value).strict().rrule().unwrap().validate();
+
+        assert_eq!(rule("FREQ=MONTHLY;BYDAY=2MO;BYMONTHDAY=-1"), Ok(()));
+        assert_eq!(rule("FREQ=HOURLY;BYYEARDAY=100;BYSECOND=60"), Ok(()));
+        assert_eq!(
+            rule("FREQ=DAILY;COUNT=5;UNTIL=20250101T000000Z"),
+            Err(ValidationError::CountWithUntil {
+                count: 5,
+                until: "20250101T000000Z".into()
+            }
+            .into())
+        );
+        assert_eq!(
+            rule("FREQ=WEEKLY;BYMONTHDAY=1"),
+            Err(ValidationError::InvalidByRuleAndFrequency {
+                by_rule: "BYMONTHDAY".into(),
+                freq: ICalendarFrequency::Weekly
+            }
+            .into())
+        );
+        assert_eq!(
+            rule("FREQ=MONTHLY;BYYEARDAY=1"),
+            Err(ValidationError::InvalidByRuleAndFrequency {
+                by_rule: "BYYEARDAY".into(),
+                freq: ICalendarFrequency::Monthly
+            }
+            .into())
+        );
+        assert_eq!(
+            rule("FREQ=WEEKLY;BYDAY=2MO"),
+            Err(ValidationError::InvalidFieldValue {
+                field: "BYDAY".into(),
+                value: "2MO".into()
+            }
+            .into())
+        );
+        assert_eq!(
+            rule("FREQ=YEARLY;BYMONTH=13"),
+            Err(ValidationError::InvalidFieldValueRange {
+                field: "BYMONTH".into(),
+                value: "13".into(),
+                start_idx: "1".into(),
+                end_idx: "12".into()
+            }
+            .into())
+        );
+        assert_eq!(
+            rule("FREQ=MONTHLY;BYMONTHDAY=32"),
+            Err(ValidationError::InvalidFieldValueRange {
+                field: "BYMONTHDAY".into(),
+                value: "32".into(),
+                start_idx: "-31".into(),
+                end_idx: "31".into()
+            }
+            .into())
+        );
+    }
 }
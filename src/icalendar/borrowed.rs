@@ -0,0 +1,265 @@
+/*
+ * SPDX-FileCopyrightText: 2020 Stalwart Labs LLC <hello@stalw.art>
+ *
+ * SPDX-License-Identifier: Apache-2.0 OR MIT
+ */
+
+use super::{ICalendar, ICalendarComponentType, ICalendarParameterName, ICalendarProperty};
+use crate::{Entry, Parser, StopChar, common::IanaParse};
+use std::borrow::Cow;
+
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct ICalendarRef<'x> {
+    pub components: Vec<ICalendarComponentRef<'x>>,
+}
+
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct ICalendarComponentRef<'x> {
+    pub component_type: ICalendarComponentType,
+    pub entries: Vec<ICalendarEntryRef<'x>>,
+    pub component_ids: Vec<u32>,
+}
+
+// Values are unescaped and unfolded by the tokenizer, they only own their
+// text when the line was folded or contained escapes
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct ICalendarEntryRef<'x> {
+    pub name: ICalendarProperty,
+    pub params: Vec<(ICalendarParameterName, Cow<'x, str>)>,
+    pub value: Cow<'x, str>,
+}
+
+impl ICalendar {
+    pub fn parse_borrowed(input: &str) -> Result<ICalendarRef<'_>, Entry> {
+        Parser::new(input).icalendar_borrowed()
+    }
+}
+
+impl<'x> Parser<'x> {
+    fn icalendar_borrowed(&mut self) -> Result<ICalendarRef<'x>, Entry> {
+        let mut components: Vec<ICalendarComponentRef<'x>> = Vec::new();
+        let mut stack: Vec<usize> = Vec::new();
+
+        loop {
+            // Fetch property name
+            self.expect_iana_token();
+            let Some(token) = self.token() else {
+                break;
+            };
+            let name = token.text;
+            let mut stop_char = token.stop_char;
+
+            // Multi-valued parameters yield one pair per value
+            let mut params = Vec::new();
+            while stop_char == StopChar::Semicolon {
+                self.expect_iana_token();
+                let Some(token) = self.token() else {
+                    stop_char = StopChar::Lf;
+                    break;
+                };
+                let param_name = ICalendarParameterName::try_parse(token.text.as_ref())
+                    .unwrap_or_else(|| ICalendarParameterName::Other(into_str(token.text).into()));
+                stop_char = token.stop_char;
+                if !matches!(
+                    stop_char,
+                    StopChar::Lf | StopChar::Colon | StopChar::Semicolon
+                ) {
+                    if stop_char != StopChar::Equal {
+                        stop_char = self.seek_param_value_or_eol();
+                    }
+                    if stop_char == StopChar::Equal {
+                        self.expect_param_value();
+                        while !matches!(
+                            stop_char,
+                            StopChar::Lf | StopChar::Colon | StopChar::Semicolon
+                        ) {
+                            let Some(token) = self.token() else {
+                                stop_char = StopChar::Lf;
+                                break;
+                            };
+                            stop_char = token.stop_char;
+                            params.push((param_name.clone(), into_str(token.text)));
+                        }
+                    }
+                }
+            }
+
+            // Invalid stop char, try seeking colon
+            if !matches!(stop_char, StopChar::Colon | StopChar::Lf) {
+                stop_char = self.seek_value_or_eol();
+            }
+            if stop_char != StopChar::Colon {
+                continue;
+            }
+
+            self.expect_single_value();
+            let value = self
+                .token()
+                .map(|token| into_str(token.text))
+                .unwrap_or_default();
+
+            match ICalendarProperty::parse(name.as_ref()) {
+                Some(prop @ (ICalendarProperty::Begin | ICalendarProperty::End)) => {
+                    let component_type = ICalendarComponentType::parse(value.as_bytes())
+                        .unwrap_or_else(|| ICalendarComponentType::Other(value.into_owned()));
+
+                    if prop == ICalendarProperty::Begin {
+                        if stack.is_empty() && !components.is_empty() {
+                            break;
+                        } else if stack.len() + 1 > self.max_depth {
+                            return Err(Entry::TooDeeplyNested);
+                        }
+                        let id = u32::try_from(components.len())
+                            .map_err(|_| Entry::TooManyComponents)?;
+                        if let Some(parent) = stack.last() {
+                            components[*parent].component_ids.push(id);
+                        }
+                        stack.push(components.len());
+                        components.push(ICalendarComponentRef {
+                            component_type,
+                            entries: Vec::new(),
+                            component_ids: Vec::new(),
+                        });
+                    } else if stack.pop().is_some() && stack.is_empty() {
+                        break;
+                    }
+                }
+                prop => {
+                    if let Some(component) = stack.last().map(|idx| &mut components[*idx]) {
+                        component.entries.push(ICalendarEntryRef {
+                            name: prop.unwrap_or_else(|| {
+                                ICalendarProperty::Other(into_str(name).into_owned())
+                            }),
+                            params,
+                            value,
+                        });
+                    }
+                }
+            }
+        }
+
+        if let Some(idx) = stack.last() {
+            Err(Entry::UnterminatedComponent(
+                components[*idx].component_type.as_str().to_string().into(),
+            ))
+        } else if components
+            .first()
+            .is_some_and(|root| root.component_type == ICalendarComponentType::VCalendar)
+        {
+            Ok(ICalendarRef { components })
+        } else {
+            Err(Entry::Eof)
+        }
+    }
+}
+
+impl<'x> ICalendarComponentRef<'x> {
+    pub fn property(&self, prop: &ICalendarProperty) -> Option<&ICalendarEntryRef<'x>> {
+        self.entries.iter().find(|entry| &entry.name == prop)
+    }
+
+    pub fn properties<'y: 'x>(
+        &'x self,
+        prop: &'y ICalendarProperty,
+    ) -> impl Iterator<Item = &'x ICalendarEntryRef<'x>> + 'x {
+        self.entries.iter().filter(move |entry| &entry.name == prop)
+    }
+
+    pub fn uid(&self) -> Option<&str> {
+        self.text(&ICalendarProperty::Uid)
+    }
+
+    pub fn text(&self, prop: &ICalendarProperty) -> Option<&str> {
+        self.property(prop).map(|entry| entry.value.as_ref())
+    }
+}
+
+impl ICalendarEntryRef<'_> {
+    pub fn parameter(&self, name: &ICalendarParameterName) -> Option<&str> {
+        self.params
+            .iter()
+            .find_map(|(param_name, value)| (param_name == name).then_some(value.as_ref()))
+    }
+}
+
+fn into_str(text: Cow<'_, [u8]>) -> Cow<'_, str> {
+    match text {
+        Cow::Borrowed(text) => String::from_utf8_lossy(text),
+        Cow::Owned(text) => Cow::Owned(
+            String::from_utf8(text)
+                .unwrap_or_else(|err| String::from_utf8_lossy(&err.into_bytes()).into_owned()),
+        ),
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use crate::{
+        Entry,
+        icalendar::{ICalendar, ICalendarComponentType, ICalendarParameterName, ICalendarProperty},
+    };
+    use std::borrow::Cow;
+
+    #[test]
+    fn parse_borrowed() {
+        let input = concat!(
+            "BEGIN:VCALENDAR\r\n",
+            "VERSION:2.0\r\n",
+            "BEGIN:VEVENT\r\n",
+            "UID:borrowed\r\n",
+            "SUMMARY:Plain summary\r\n",
+            "DESCRIPTION:Folded\r\n",
+            "  description\r\n",
+            "LOCATION;ALTREP=\"http://example.com/a:b\":Room 1\\, Floor 2\r\n",
+            "BEGIN:VALARM\r\n",
+            "ACTION:DISPLAY\r\n",
+            "END:VALARM\r\n",
+            "END:VEVENT\r\n",
+            "END:VCALENDAR\r\n"
+        );
+        let ical = ICalendar::parse_borrowed(input).unwrap();
+
+        assert_eq!(
+            ical.components
+                .iter()
+                .map(|component| (
+                    component.component_type.clone(),
+                    component.component_ids.clone()
+                ))
+                .collect::<Vec<_>>(),
+            [
+                (ICalendarComponentType::VCalendar, vec![1]),
+                (ICalendarComponentType::VEvent, vec![2]),
+                (ICalendarComponentType::VAlarm, vec![]),
+            ]
+        );
+        let event = &ical.components[1];
+        assert_eq!(event.uid(), Some("borrowed"));
+        assert!(matches!(
+            &event.property(&ICalendarProperty::Summary).unwrap().value,
+            Cow::Borrowed("Plain summary")
+        ));
+        assert!(matches!(
+            &event.property(&ICalendarProperty::Description).unwrap().value,
+            Cow::Owned(value) if value == "Folded description"
+        ));
+        let location = event.property(&ICalendarProperty::Location).unwrap();
+        assert!(matches!(
+            &location.params[0],
+            (
+                ICalendarParameterName::Altrep,
+                Cow::Borrowed("http://example.com/a:b")
+            )
+        ));
+        assert_eq!(
+            location.parameter(&ICalendarParameterName::Altrep),
+            Some("http://example.com/a:b")
+        );
+        assert!(matches!(&location.value, Cow::Owned(text) if text == "Room 1, Floor 2"));
+
+        assert_eq!(
+            ICalendar::parse_borrowed("BEGIN:VCALENDAR\r\nBEGIN:VEVENT\r\nEND:VEVENT\r\n"),
+            Err(Entry::UnterminatedComponent("VCALENDAR".into()))
+        );
+    }
+}
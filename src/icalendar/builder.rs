@@ -14,6 +14,12 @@ use crate::{
 use ahash::{AHashMap, AHashSet};
 
 impl ICalendar {
+    pub fn set_prodid(&mut self, prodid: &str) {
+        if let Some(root) = self.components.first_mut() {
+            root.set_property(ICalendarProperty::Prodid, prodid);
+        }
+    }
+
     pub fn remove_component_ids(&mut self, component_ids: &[u32]) {
         // Validate component IDs
         let max_component_id = self.components.len() as u32;
@@ -56,6 +62,12 @@ impl ICalendar {
         }
     }
 
+    pub fn remove_component(&mut self, index: usize) {
+        if let Ok(id) = u32::try_from(index) {
+            self.remove_component_ids(&[id]);
+        }
+    }
+
     pub fn copy_timezones(&mut self, other: &ICalendar) {
         for component in &other.components {
             if component.component_type == ICalendarComponentType::VTimezone {
@@ -83,6 +95,157 @@ impl ICalendar {
     }
 }
 
+#[derive(Debug, Default, Clone, PartialEq, Eq)]
+pub struct MergeReport {
+    pub deduplicated: Vec<String>,
+    pub renamed: Vec<(String, String)>,
+}
+
+impl ICalendar {
+    pub fn merge(&mut self, mut other: ICalendar) -> MergeReport {
+        let mut report = MergeReport::default();
+        if other.components.is_empty() {
+            return report;
+        } else if self.components.is_empty() {
+            *self = other;
+            return report;
+        }
+
+        // Timezones already defined in this calendar
+        let mut timezones: Vec<(String, u32)> = self.components[0]
+            .component_ids
+            .iter()
+            .filter_map(|id| {
+                vtimezone_id(&self.components[*id as usize]).map(|tz_id| (tz_id.to_string(), *id))
+            })
+            .collect();
+        let mut renames: AHashMap<String, String> = AHashMap::new();
+        let first_merged_id = self.components.len();
+
+        let (timezone_ids, component_ids): (Vec<u32>, Vec<u32>) =
+            std::mem::take(&mut other.components[0].component_ids)
+                .into_iter()
+                .partition(|id| {
+                    other.components[*id as usize].component_type
+                        == ICalendarComponentType::VTimezone
+                });
+        for component_id in timezone_ids {
+            let Some(tz_id) = vtimezone_id(&other.components[component_id as usize])
+                .map(|tz_id| tz_id.to_string())
+            else {
+                let new_component_id = self.append_subtree(&mut other, component_id);
+                self.components[0].component_ids.push(new_component_id);
+                continue;
+            };
+
+            if timezones.iter().any(|(id, existing_id)| {
+                (id == &tz_id || renames.get(&tz_id) == Some(id))
+                    && self.is_same_subtree(*existing_id, &other, component_id)
+            }) {
+                report.deduplicated.push(tz_id);
+                continue;
+            }
+
+            let new_tz_id = if timezones.iter().any(|(id, _)| id == &tz_id) {
+                let new_tz_id = (1..)
+                    .map(|n| format!("{tz_id}-{n}"))
+                    .find(|new_tz_id| {
+                        !timezones.iter().any(|(id, _)| id == new_tz_id)
+                            && !other.components.iter().any(|component| {
+                                vtimezone_id(component) == Some(new_tz_id.as_str())
+                            })
+                    })
+                    .unwrap();
+                for entry in &mut other.components[component_id as usize].entries {
+                    if entry.name == ICalendarProperty::Tzid {
+                        entry.values = vec![ICalendarValue::Text(new_tz_id.clone())];
+                    }
+                }
+                report.renamed.push((tz_id.clone(), new_tz_id.clone()));
+                renames.insert(tz_id, new_tz_id.clone());
+                new_tz_id
+            } else {
+                tz_id
+            };
+
+            let new_component_id = self.append_subtree(&mut other, component_id);
+            self.components[0].component_ids.push(new_component_id);
+            timezones.push((new_tz_id, new_component_id));
+        }
+
+        for component_id in component_ids {
+            let new_component_id = self.append_subtree(&mut other, component_id);
+            self.components[0].component_ids.push(new_component_id);
+        }
+
+        // Update references to renamed timezones in the merged components
+        if !renames.is_empty() {
+            for param in self.components[first_merged_id..]
+                .iter_mut()
+                .flat_map(|component| component.entries.iter_mut())
+                .flat_map(|entry| entry.params.iter_mut())
+            {
+                if param.name == ICalendarParameterName::Tzid
+                    && let ICalendarParameterValue::Text(tz_id) = &mut param.value
+                    && let Some(new_tz_id) = renames.get(tz_id.as_str())
+                {
+                    *tz_id = new_tz_id.clone();
+                }
+            }
+        }
+
+        report
+    }
+
+    fn append_subtree(&mut self, other: &mut ICalendar, component_id: u32) -> u32 {
+        let mut component = std::mem::take(&mut other.components[component_id as usize]);
+        let component_ids = std::mem::take(&mut component.component_ids);
+        let new_component_id = self.components.len() as u32;
+        self.components.push(component);
+
+        for component_id in component_ids {
+            let child_id = self.append_subtree(other, component_id);
+            self.components[new_component_id as usize]
+                .component_ids
+                .push(child_id);
+        }
+
+        new_component_id
+    }
+
+    fn is_same_subtree(&self, component_id: u32, other: &ICalendar, other_id: u32) -> bool {
+        let component = &self.components[component_id as usize];
+        let other_component = &other.components[other_id as usize];
+
+        component.component_type == other_component.component_type
+            && component
+                .entries
+                .iter()
+                .filter(|entry| entry.name != ICalendarProperty::Tzid)
+                .eq(other_component
+                    .entries
+                    .iter()
+                    .filter(|entry| entry.name != ICalendarProperty::Tzid))
+            && component.component_ids.len() == other_component.component_ids.len()
+            && component
+                .component_ids
+                .iter()
+                .zip(&other_component.component_ids)
+                .all(|(id, other_id)| self.is_same_subtree(*id, other, *other_id))
+    }
+}
+
+fn vtimezone_id(component: &ICalendarComponent) -> Option<&str> {
+    if component.component_type == ICalendarComponentType::VTimezone {
+        component
+            .property(&ICalendarProperty::Tzid)
+            .and_then(|entry| entry.values.first())
+            .and_then(|value| value.as_text())
+    } else {
+        None
+    }
+}
+
 impl ICalendarComponent {
     pub fn new(component_type: ICalendarComponentType) -> Self {
         Self {
@@ -124,6 +287,57 @@ impl ICalendarComponent {
         });
     }
 
+    // Replaces the value of the first matching entry, keeping its parameters
+    pub fn set_property(&mut self, name: ICalendarProperty, value: impl Into<ICalendarValue>) {
+        if let Some(entry) = self.property_mut(&name) {
+            entry.values = vec![value.into()];
+        } else {
+            self.add_property(name, value);
+        }
+    }
+
+    pub fn set_property_with_params(
+        &mut self,
+        name: ICalendarProperty,
+        params: impl IntoIterator<Item = ICalendarParameter>,
+        value: impl Into<ICalendarValue>,
+    ) {
+        let params = params.into_iter().collect();
+        if let Some(entry) = self.property_mut(&name) {
+            entry.params = params;
+            entry.values = vec![value.into()];
+        } else {
+            self.add_property_with_params(name, params, value);
+        }
+    }
+
+    pub fn set_dtstart(&mut self, dt: PartialDateTime, tz_id: Option<&str>) {
+        set_entry(self, date_time_entry(ICalendarProperty::Dtstart, dt, tz_id));
+    }
+
+    // DTEND and DUE cannot be combined with DURATION
+    pub fn set_dtend(&mut self, dt: PartialDateTime, tz_id: Option<&str>) {
+        self.remove_entries(&ICalendarProperty::Duration);
+        set_entry(self, date_time_entry(ICalendarProperty::Dtend, dt, tz_id));
+    }
+
+    pub fn set_due(&mut self, dt: PartialDateTime, tz_id: Option<&str>) {
+        self.remove_entries(&ICalendarProperty::Duration);
+        set_entry(self, date_time_entry(ICalendarProperty::Due, dt, tz_id));
+    }
+
+    pub fn remove_entries(&mut self, name: &ICalendarProperty) {
+        self.entries.retain(|entry| &entry.name != name);
+    }
+
+    #[cfg(feature = "datetime")]
+    pub fn bump_sequence(&mut self) {
+        self.set_property(ICalendarProperty::Sequence, self.sequence() as i64 + 1);
+        let now = PartialDateTime::now_utc();
+        self.set_property(ICalendarProperty::LastModified, now.clone());
+        self.set_property(ICalendarProperty::Dtstamp, now);
+    }
+
     pub fn add_property_with_params(
         &mut self,
         name: ICalendarProperty,
@@ -136,6 +350,409 @@ impl ICalendarComponent {
             values: vec![value.into()],
         });
     }
+
+    pub fn add_attendee(&mut self, attendee: AttendeeBuilder) {
+        self.entries.push(attendee.build());
+    }
+}
+
+#[derive(Debug, Clone, PartialEq)]
+pub struct EventBuilder {
+    component: ICalendarComponent,
+}
+
+impl Default for EventBuilder {
+    fn default() -> Self {
+        Self::new()
+    }
+}
+
+impl EventBuilder {
+    pub fn new() -> Self {
+        Self {
+            component: ICalendarComponent::new(ICalendarComponentType::VEvent),
+        }
+    }
+
+    pub fn uid(self, uid: impl Into<String>) -> Self {
+        self.set(ICalendarEntry::new(ICalendarProperty::Uid).with_value(uid.into()))
+    }
+
+    pub fn summary(self, summary: impl Into<String>) -> Self {
+        self.set(ICalendarEntry::new(ICalendarProperty::Summary).with_value(summary.into()))
+    }
+
+    pub fn dtstamp(self, dt: PartialDateTime) -> Self {
+        self.set(ICalendarEntry::new(ICalendarProperty::Dtstamp).with_value(dt))
+    }
+
+    pub fn dtstart(self, dt: PartialDateTime, tz_id: Option<&str>) -> Self {
+        self.set(date_time_entry(ICalendarProperty::Dtstart, dt, tz_id))
+    }
+
+    pub fn dtend(self, dt: PartialDateTime, tz_id: Option<&str>) -> Self {
+        self.set(date_time_entry(ICalendarProperty::Dtend, dt, tz_id))
+    }
+
+    pub fn rrule(self, rrule: ICalendarRecurrenceRule) -> Self {
+        self.set(ICalendarEntry::new(ICalendarProperty::Rrule).with_value(rrule))
+    }
+
+    pub fn organizer(self, address: impl Into<String>, name: Option<&str>) -> Self {
+        self.set(
+            ICalendarEntry::new(ICalendarProperty::Organizer)
+                .with_param_opt(name.map(|name| ICalendarParameter::cn(name.to_string())))
+                .with_value(calendar_address_uri(address.into())),
+        )
+    }
+
+    pub fn add_attendee(mut self, address: impl Into<String>, name: Option<&str>) -> Self {
+        self.component.entries.push(
+            ICalendarEntry::new(ICalendarProperty::Attendee)
+                .with_param_opt(name.map(|name| ICalendarParameter::cn(name.to_string())))
+                .with_value(calendar_address_uri(address.into())),
+        );
+        self
+    }
+
+    // DTSTAMP is only defaulted to the current time with the datetime feature
+    #[cfg_attr(not(feature = "datetime"), allow(unused_mut))]
+    pub fn build(mut self) -> ICalendarComponent {
+        #[cfg(feature = "datetime")]
+        if self
+            .component
+            .property(&ICalendarProperty::Dtstamp)
+            .is_none()
+        {
+            self.component.entries.insert(
+                0,
+                ICalendarEntry::new(ICalendarProperty::Dtstamp)
+                    .with_value(PartialDateTime::now_utc()),
+            );
+        }
+        self.component
+    }
+
+    fn set(mut self, entry: ICalendarEntry) -> Self {
+        set_entry(&mut self.component, entry);
+        self
+    }
+}
+
+// Bare email addresses are turned into mailto: URIs
+pub(crate) fn calendar_address_uri(address: String) -> Uri {
+    if address.contains(':') {
+        Uri::Location(address)
+    } else {
+        Uri::Location(format!("mailto:{address}"))
+    }
+}
+
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct AttendeeBuilder {
+    entry: ICalendarEntry,
+}
+
+impl AttendeeBuilder {
+    pub fn new(address: impl Into<String>) -> Self {
+        Self {
+            entry: ICalendarEntry::new(ICalendarProperty::Attendee)
+                .with_value(calendar_address_uri(address.into())),
+        }
+    }
+
+    pub fn name(self, name: impl Into<String>) -> Self {
+        self.set(ICalendarParameter::cn(name.into()))
+    }
+
+    pub fn participation_status(self, partstat: ICalendarParticipationStatus) -> Self {
+        self.set(ICalendarParameter::partstat(partstat))
+    }
+
+    pub fn role(self, role: ICalendarParticipationRole) -> Self {
+        self.set(ICalendarParameter::role(role))
+    }
+
+    pub fn rsvp(self, rsvp: bool) -> Self {
+        self.set(ICalendarParameter::rsvp(rsvp))
+    }
+
+    pub fn kind(self, kind: ICalendarUserTypes) -> Self {
+        self.set(ICalendarParameter::cutype(kind))
+    }
+
+    pub fn build(self) -> ICalendarEntry {
+        self.entry
+    }
+
+    fn set(mut self, param: ICalendarParameter) -> Self {
+        self.entry.set_param(param.name, param.value);
+        self
+    }
+}
+
+#[derive(Debug, Clone, PartialEq)]
+pub struct AlarmBuilder {
+    component: ICalendarComponent,
+}
+
+impl Default for AlarmBuilder {
+    fn default() -> Self {
+        Self::new()
+    }
+}
+
+impl AlarmBuilder {
+    pub fn new() -> Self {
+        Self {
+            component: ICalendarComponent::new(ICalendarComponentType::VAlarm),
+        }
+    }
+
+    pub fn display(self, description: impl Into<String>) -> Self {
+        self.action(ICalendarAction::Display)
+            .set(ICalendarEntry::new(ICalendarProperty::Description).with_value(description.into()))
+    }
+
+    pub fn email(
+        self,
+        summary: impl Into<String>,
+        description: impl Into<String>,
+        attendees: impl IntoIterator<Item = impl Into<String>>,
+    ) -> Self {
+        let mut builder = self
+            .action(ICalendarAction::Email)
+            .set(ICalendarEntry::new(ICalendarProperty::Summary).with_value(summary.into()))
+            .set(
+                ICalendarEntry::new(ICalendarProperty::Description).with_value(description.into()),
+            );
+        for attendee in attendees {
+            builder.component.entries.push(
+                ICalendarEntry::new(ICalendarProperty::Attendee)
+                    .with_value(calendar_address_uri(attendee.into())),
+            );
+        }
+        builder
+    }
+
+    pub fn audio(self, attach: Option<Uri>) -> Self {
+        let builder = self.action(ICalendarAction::Audio);
+        if let Some(attach) = attach {
+            builder.set(ICalendarEntry::new(ICalendarProperty::Attach).with_value(attach))
+        } else {
+            builder
+        }
+    }
+
+    pub fn trigger_before_start(self, mut duration: ICalendarDuration) -> Self {
+        duration.neg = true;
+        self.set(
+            ICalendarEntry::new(ICalendarProperty::Trigger)
+                .with_param(ICalendarParameter::related(ICalendarRelated::Start))
+                .with_value(duration),
+        )
+    }
+
+    pub fn trigger_after_end(self, mut duration: ICalendarDuration) -> Self {
+        duration.neg = false;
+        self.set(
+            ICalendarEntry::new(ICalendarProperty::Trigger)
+                .with_param(ICalendarParameter::related(ICalendarRelated::End))
+                .with_value(duration),
+        )
+    }
+
+    pub fn trigger_absolute(self, dt: PartialDateTime) -> Self {
+        self.set(
+            ICalendarEntry::new(ICalendarProperty::Trigger)
+                .with_param(ICalendarParameter::value(ICalendarValueType::DateTime))
+                .with_value(dt),
+        )
+    }
+
+    pub fn repeat(self, count: u32, duration: ICalendarDuration) -> Self {
+        self.set(ICalendarEntry::new(ICalendarProperty::Repeat).with_value(count as i64))
+            .set(ICalendarEntry::new(ICalendarProperty::Duration).with_value(duration))
+    }
+
+    pub fn build(self) -> ICalendarComponent {
+        self.component
+    }
+
+    fn action(mut self, action: ICalendarAction) -> Self {
+        // Remove properties specific to the previous action
+        self.component.entries.retain(|entry| {
+            !matches!(
+                entry.name,
+                ICalendarProperty::Action
+                    | ICalendarProperty::Description
+                    | ICalendarProperty::Summary
+                    | ICalendarProperty::Attendee
+                    | ICalendarProperty::Attach
+            )
+        });
+        self.set(ICalendarEntry::new(ICalendarProperty::Action).with_value(action))
+    }
+
+    fn set(mut self, entry: ICalendarEntry) -> Self {
+        set_entry(&mut self.component, entry);
+        self
+    }
+}
+
+#[derive(Debug, Default, Clone, PartialEq, Eq)]
+pub struct RecurrenceRuleBuilder {
+    rule: ICalendarRecurrenceRule,
+}
+
+impl RecurrenceRuleBuilder {
+    pub fn new(freq: ICalendarFrequency) -> Self {
+        Self {
+            rule: ICalendarRecurrenceRule {
+                freq,
+                ..Default::default()
+            },
+        }
+    }
+
+    pub fn frequency(mut self, freq: ICalendarFrequency) -> Self {
+        self.rule.freq = freq;
+        self
+    }
+
+    pub fn yearly(self) -> Self {
+        self.frequency(ICalendarFrequency::Yearly)
+    }
+
+    pub fn monthly(self) -> Self {
+        self.frequency(ICalendarFrequency::Monthly)
+    }
+
+    pub fn weekly(self) -> Self {
+        self.frequency(ICalendarFrequency::Weekly)
+    }
+
+    pub fn daily(self) -> Self {
+        self.frequency(ICalendarFrequency::Daily)
+    }
+
+    pub fn interval(mut self, interval: u16) -> Self {
+        self.rule.interval = Some(interval);
+        self
+    }
+
+    // COUNT and UNTIL are mutually exclusive, the last one set wins
+    pub fn count(mut self, count: u32) -> Self {
+        self.rule.count = Some(count);
+        self.rule.until = None;
+        self
+    }
+
+    pub fn until(mut self, until: PartialDateTime) -> Self {
+        self.rule.until = Some(until);
+        self.rule.count = None;
+        self
+    }
+
+    pub fn on_days(mut self, days: &[ICalendarWeekday]) -> Self {
+        self.rule
+            .byday
+            .extend(days.iter().map(|day| ICalendarDay::new(*day, None)));
+        self
+    }
+
+    pub fn on_nth_day(mut self, ordwk: i16, day: ICalendarWeekday) -> Self {
+        self.rule.byday.push(ICalendarDay::new(day, Some(ordwk)));
+        self
+    }
+
+    pub fn by_month(mut self, months: &[u8]) -> Self {
+        self.rule.bymonth.extend(
+            months
+                .iter()
+                .map(|month| ICalendarMonth::new(*month, false)),
+        );
+        self
+    }
+
+    pub fn by_month_day(mut self, days: &[i8]) -> Self {
+        self.rule.bymonthday.extend_from_slice(days);
+        self
+    }
+
+    pub fn by_year_day(mut self, days: &[i16]) -> Self {
+        self.rule.byyearday.extend_from_slice(days);
+        self
+    }
+
+    pub fn by_week_no(mut self, weeks: &[i8]) -> Self {
+        self.rule.byweekno.extend_from_slice(weeks);
+        self
+    }
+
+    pub fn by_hour(mut self, hours: &[u8]) -> Self {
+        self.rule.byhour.extend_from_slice(hours);
+        self
+    }
+
+    pub fn by_minute(mut self, minutes: &[u8]) -> Self {
+        self.rule.byminute.extend_from_slice(minutes);
+        self
+    }
+
+    pub fn by_second(mut self, seconds: &[u8]) -> Self {
+        self.rule.bysecond.extend_from_slice(seconds);
+        self
+    }
+
+    pub fn by_set_pos(mut self, positions: &[i32]) -> Self {
+        self.rule.bysetpos.extend_from_slice(positions);
+        self
+    }
+
+    pub fn week_start(mut self, wkst: ICalendarWeekday) -> Self {
+        self.rule.wkst = Some(wkst);
+        self
+    }
+
+    pub fn build(self) -> ICalendarRecurrenceRule {
+        self.rule
+    }
+}
+
+fn set_entry(component: &mut ICalendarComponent, entry: ICalendarEntry) {
+    let name = entry.name.clone();
+    let mut entry = Some(entry);
+    component.entries.retain_mut(|existing| {
+        if existing.name != name {
+            true
+        } else if let Some(entry) = entry.take() {
+            *existing = entry;
+            true
+        } else {
+            false
+        }
+    });
+    if let Some(entry) = entry {
+        component.entries.push(entry);
+    }
+}
+
+fn date_time_entry(
+    name: ICalendarProperty,
+    dt: PartialDateTime,
+    tz_id: Option<&str>,
+) -> ICalendarEntry {
+    ICalendarEntry::new(name)
+        .with_param_opt(
+            (!dt.has_time()).then_some(ICalendarParameter::value(ICalendarValueType::Date)),
+        )
+        .with_param_opt(
+            tz_id
+                .filter(|_| dt.has_time() && !dt.has_zone())
+                .map(|tz_id| ICalendarParameter::tzid(tz_id.to_string())),
+        )
+        .with_value(dt)
 }
 
 impl ICalendarEntry {
@@ -178,6 +795,31 @@ impl ICalendarEntry {
         self
     }
 
+    pub fn set_param(
+        &mut self,
+        name: ICalendarParameterName,
+        value: impl Into<ICalendarParameterValue>,
+    ) {
+        let mut value = Some(value.into());
+        self.params.retain_mut(|param| {
+            if param.name != name {
+                true
+            } else if let Some(value) = value.take() {
+                param.value = value;
+                true
+            } else {
+                false
+            }
+        });
+        if let Some(value) = value {
+            self.params.push(ICalendarParameter { name, value });
+        }
+    }
+
+    pub fn remove_param(&mut self, name: &ICalendarParameterName) {
+        self.params.retain(|p| &p.name != name);
+    }
+
     pub fn is_type(&self, typ: &ICalendarValueType) -> bool {
         self.parameters(&ICalendarParameterName::Value)
             .any(|p| matches!(p, ICalendarParameterValue::Value(v) if v == typ))
@@ -708,7 +1350,663 @@ impl From<ICalendarProximityValue> for ICalendarValue {
 
 #[cfg(test)]
 mod tests {
-    use crate::icalendar::ICalendar;
+    use super::{AlarmBuilder, AttendeeBuilder, EventBuilder, MergeReport, RecurrenceRuleBuilder};
+    use crate::{
+        common::{PartialDateTime, writer::WriterOptions},
+        icalendar::{
+            ICalendar, ICalendarComponent, ICalendarComponentType, ICalendarDuration,
+            ICalendarEntry, ICalendarFrequency, ICalendarParameter, ICalendarParameterName,
+            ICalendarParameterValue, ICalendarParticipationRole, ICalendarParticipationStatus,
+            ICalendarProperty, ICalendarRecurrenceRule, ICalendarUserTypes, ICalendarValue,
+            ICalendarWeekday, Uri,
+        },
+    };
+
+    fn dt(value: &str) -> PartialDateTime {
+        let mut dt = PartialDateTime::default();
+        let mut iter = value.as_bytes().iter().peekable();
+        assert!(dt.parse_ical_date(&mut iter));
+        if iter.next().is_some() {
+            assert!(dt.parse_ical_time(&mut iter));
+        }
+        dt
+    }
+
+    fn write_entries(component: &ICalendarComponent) -> String {
+        let mut out = String::new();
+        for entry in &component.entries {
+            entry.write_to(&mut out).unwrap();
+        }
+        out.replace("\r\n", "\n")
+    }
+
+    #[test]
+    fn merge_calendars() {
+        let timezone = |tz_id: &str, offset: &str| {
+            format!(
+                concat!(
+                    "BEGIN:VTIMEZONE\r\n",
+                    "TZID:{}\r\n",
+                    "BEGIN:STANDARD\r\n",
+                    "DTSTART:19700101T000000\r\n",
+                    "TZOFFSETFROM:{}\r\n",
+                    "TZOFFSETTO:{}\r\n",
+                    "END:STANDARD\r\n",
+                    "END:VTIMEZONE\r\n",
+                ),
+                tz_id, offset, offset
+            )
+        };
+        let event = |uid: &str, tz_id: &str| {
+            format!(
+                concat!(
+                    "BEGIN:VEVENT\r\n",
+                    "UID:{}\r\n",
+                    "DTSTART;TZID={}:20240101T090000\r\n",
+                    "END:VEVENT\r\n",
+                ),
+                uid, tz_id
+            )
+        };
+        let calendar = |parts: &[String]| {
+            ICalendar::parse(format!(
+                "BEGIN:VCALENDAR\r\n{}END:VCALENDAR\r\n",
+                parts.concat()
+            ))
+            .unwrap()
+        };
+
+        let mut ical = calendar(&[
+            timezone("Europe/Berlin", "+0100"),
+            timezone("Custom", "+0300"),
+            event("a", "Custom"),
+        ]);
+        let report = ical.merge(calendar(&[
+            event("b", "Custom"),
+            timezone("Europe/Berlin", "+0100"),
+            timezone("Custom", "+0400"),
+            timezone("Custom", "+0400"),
+            event("c", "Europe/Berlin"),
+        ]));
+
+        assert_eq!(
+            report,
+            MergeReport {
+                deduplicated: vec!["Europe/Berlin".to_string(), "Custom".to_string()],
+                renamed: vec![("Custom".to_string(), "Custom-1".to_string())],
+            }
+        );
+        assert_eq!(
+            ical.to_string().replace("\r\n", "\n"),
+            format!(
+                "BEGIN:VCALENDAR\n{}{}{}{}{}{}END:VCALENDAR\n",
+                timezone("Europe/Berlin", "+0100"),
+                timezone("Custom", "+0300"),
+                event("a", "Custom"),
+                timezone("Custom-1", "+0400"),
+                event("b", "Custom-1"),
+                event("c", "Europe/Berlin"),
+            )
+            .replace("\r\n", "\n")
+        );
+    }
+
+    #[test]
+    #[cfg(feature = "datetime")]
+    fn bump_sequence() {
+        let mut event = ICalendarComponent::new(ICalendarComponentType::VEvent);
+        event.add_dtstamp(PartialDateTime::from_utc_timestamp(0));
+        assert_eq!(event.sequence(), 0);
+
+        event.bump_sequence();
+        assert_eq!(event.sequence(), 1);
+        event.add_sequence(7);
+        event.bump_sequence();
+        assert_eq!(event.sequence(), 2);
+        assert_eq!(
+            event
+                .properties(&ICalendarProperty::Sequence)
+                .filter_map(|entry| entry.values.first()?.as_integer())
+                .collect::<Vec<_>>(),
+            [2, 7]
+        );
+
+        let dtstamp = event.property(&ICalendarProperty::Dtstamp).unwrap();
+        assert_ne!(
+            dtstamp
+                .values
+                .first()
+                .and_then(|v| v.as_partial_date_time()),
+            Some(&PartialDateTime::from_utc_timestamp(0))
+        );
+        assert!(event.has_property(&ICalendarProperty::LastModified));
+        assert_eq!(event.properties(&ICalendarProperty::Dtstamp).count(), 1);
+
+        // Parameters are only replaced on request
+        event.add_property_with_params(
+            ICalendarProperty::Summary,
+            [ICalendarParameter::language("en".to_string())],
+            "Planning",
+        );
+        event.set_property(ICalendarProperty::Summary, "Review");
+        let summary = event.property(&ICalendarProperty::Summary).unwrap();
+        assert_eq!(summary.params.len(), 1);
+        assert_eq!(
+            summary.values.first().and_then(|v| v.as_text()),
+            Some("Review")
+        );
+        event.set_property_with_params(ICalendarProperty::Summary, [], "Retro");
+        assert!(
+            event
+                .property(&ICalendarProperty::Summary)
+                .unwrap()
+                .params
+                .is_empty()
+        );
+    }
+
+    #[test]
+    fn set_and_remove_params() {
+        let mut entry = ICalendarEntry::new(ICalendarProperty::Attendee)
+            .with_param(ICalendarParameter::cn("Jane".to_string()))
+            .with_param(ICalendarParameter::new(
+                ICalendarParameterName::Other("X-SOURCE".to_string()),
+                "import".to_string(),
+            ))
+            .with_param(ICalendarParameter::cn("Janie".to_string()))
+            .with_value(Uri::Location("mailto:jane@example.com".to_string()));
+
+        entry.set_param(ICalendarParameterName::Cn, "Jane Doe".to_string());
+        entry.set_param(ICalendarParameterName::Rsvp, true);
+        assert_eq!(
+            entry.parameter(&ICalendarParameterName::Cn),
+            Some(&ICalendarParameterValue::Text("Jane Doe".to_string()))
+        );
+
+        entry.remove_param(&ICalendarParameterName::Other("X-SOURCE".to_string()));
+        assert_eq!(
+            entry.parameter(&ICalendarParameterName::Other("X-SOURCE".to_string())),
+            None
+        );
+
+        let mut out = String::new();
+        entry.write_to(&mut out).unwrap();
+        assert_eq!(
+            out,
+            "ATTENDEE;CN=\"Jane Doe\";RSVP=TRUE:mailto:jane@example.com\r\n"
+        );
+    }
+
+    #[test]
+    fn alarm_builder() {
+        let minutes = |minutes| ICalendarDuration {
+            minutes,
+            ..Default::default()
+        };
+
+        assert_eq!(
+            write_entries(
+                &AlarmBuilder::new()
+                    .display("Meeting soon")
+                    .trigger_before_start(minutes(15))
+                    .repeat(2, minutes(5))
+                    .build()
+            ),
+            concat!(
+                "ACTION:DISPLAY\n",
+                "DESCRIPTION:Meeting soon\n",
+                "TRIGGER;RELATED=START:-PT15M\n",
+                "REPEAT:2\n",
+                "DURATION:PT5M\n",
+            )
+        );
+
+        let alarm = AlarmBuilder::new()
+            .display("Replaced")
+            .email(
+                "Reminder",
+                "Meeting ended",
+                ["mailto:jane@example.com", "john@example.com"],
+            )
+            .trigger_after_end(minutes(10))
+            .build();
+        assert!(
+            alarm
+                .properties(&ICalendarProperty::Attendee)
+                .all(|entry| matches!(entry.values[..], [ICalendarValue::Uri(_)]))
+        );
+        assert_eq!(
+            write_entries(&alarm),
+            concat!(
+                "ACTION:EMAIL\n",
+                "SUMMARY:Reminder\n",
+                "DESCRIPTION:Meeting ended\n",
+                "ATTENDEE:mailto:jane@example.com\n",
+                "ATTENDEE:mailto:john@example.com\n",
+                "TRIGGER;RELATED=END:PT10M\n",
+            )
+        );
+
+        assert_eq!(
+            write_entries(
+                &AlarmBuilder::new()
+                    .audio(Some(Uri::Location(
+                        "ftp://example.com/ping.wav".to_string()
+                    )))
+                    .trigger_absolute(dt("20250110T083000Z"))
+                    .build()
+            ),
+            concat!(
+                "ACTION:AUDIO\n",
+                "ATTACH:ftp://example.com/ping.wav\n",
+                "TRIGGER;VALUE=DATE-TIME:20250110T083000Z\n",
+            )
+        );
+    }
+
+    #[test]
+    fn event_builder() {
+        let event = EventBuilder::new()
+            .uid("old-uid")
+            .uid("event-1")
+            .summary("Planning")
+            .dtstamp(dt("20250101T090000Z"))
+            .dtstart(dt("20250110T100000"), Some("Europe/Madrid"))
+            .dtend(dt("20250110T110000"), Some("Europe/Madrid"))
+            .dtstart(dt("20250110T093000"), Some("Europe/Madrid"))
+            .rrule(ICalendarRecurrenceRule {
+                freq: ICalendarFrequency::Weekly,
+                count: Some(4),
+                ..Default::default()
+            })
+            .organizer("mailto:jane@example.com", Some("Jane"))
+            .add_attendee("mailto:john@example.com", Some("John"))
+            .add_attendee("mailto:bob@example.com", None)
+            .build();
+
+        assert_eq!(
+            write_entries(&event),
+            concat!(
+                "UID:event-1\n",
+                "SUMMARY:Planning\n",
+                "DTSTAMP:20250101T090000Z\n",
+                "DTSTART;TZID=Europe/Madrid:20250110T093000\n",
+                "DTEND;TZID=Europe/Madrid:20250110T110000\n",
+                "RRULE:FREQ=WEEKLY;COUNT=4\n",
+                "ORGANIZER;CN=Jane:mailto:jane@example.com\n",
+                "ATTENDEE;CN=John:mailto:john@example.com\n",
+                "ATTENDEE:mailto:bob@example.com\n",
+            )
+        );
+
+        // Addresses are stored as URIs, as the parser does
+        let parsed = ICalendar::parse(concat!(
+            "BEGIN:VCALENDAR\r\n",
+            "BEGIN:VEVENT\r\n",
+            "ORGANIZER;CN=Jane:mailto:jane@example.com\r\n",
+            "ATTENDEE;CN=John:mailto:john@example.com\r\n",
+            "END:VEVENT\r\n",
+            "END:VCALENDAR\r\n"
+        ))
+        .unwrap();
+        for prop in [ICalendarProperty::Organizer, ICalendarProperty::Attendee] {
+            assert_eq!(
+                event.property(&prop),
+                parsed.components[1].property(&prop),
+                "{prop:?}"
+            );
+        }
+
+        let event = EventBuilder::new()
+            .uid("event-2")
+            .dtstart(dt("20250110"), None)
+            .build();
+        let mut out = String::new();
+        event
+            .property(&ICalendarProperty::Dtstart)
+            .unwrap()
+            .write_to(&mut out)
+            .unwrap();
+        assert_eq!(out, "DTSTART;VALUE=DATE:20250110\r\n");
+    }
+
+    #[test]
+    #[cfg(feature = "datetime")]
+    fn event_builder_default_dtstamp() {
+        let event = EventBuilder::new().uid("event-1").build();
+        assert_eq!(event.entries[0].name, ICalendarProperty::Dtstamp);
+        assert!(
+            event.entries[0]
+                .values
+                .first()
+                .and_then(|value| value.as_partial_date_time())
+                .is_some_and(|dt| dt.is_utc())
+        );
+    }
+
+    #[test]
+    fn attendee_builder() {
+        let mut event = ICalendarComponent::new(ICalendarComponentType::VEvent);
+        event.add_attendee(
+            AttendeeBuilder::new("jane@example.com")
+                .name("Jane Doe")
+                .role(ICalendarParticipationRole::OptParticipant)
+                .participation_status(ICalendarParticipationStatus::NeedsAction)
+                .rsvp(false)
+                .rsvp(true),
+        );
+        event.add_attendee(
+            AttendeeBuilder::new("mailto:room@example.com").kind(ICalendarUserTypes::Room),
+        );
+
+        assert_eq!(
+            write_entries(&event),
+            concat!(
+                "ATTENDEE;CN=\"Jane Doe\";ROLE=OPT-PARTICIPANT;PARTSTAT=NEEDS-ACTION;RSVP=TRUE:\n",
+                " mailto:jane@example.com\n",
+                "ATTENDEE;CUTYPE=ROOM:mailto:room@example.com\n",
+            )
+        );
+
+        let attendees = event.attendees().collect::<Vec<_>>();
+        assert_eq!(attendees[0].address, "jane@example.com");
+        assert_eq!(attendees[0].name, Some("Jane Doe"));
+        assert!(attendees[0].rsvp);
+        assert_eq!(attendees[1].kind, Some(&ICalendarUserTypes::Room));
+    }
+
+    #[test]
+    fn recurrence_rule_builder() {
+        let rrule = |rule: ICalendarRecurrenceRule| {
+            let mut out = String::new();
+            ICalendarEntry::new(ICalendarProperty::Rrule)
+                .with_value(ICalendarValue::RecurrenceRule(Box::new(rule)))
+                .write_to(&mut out)
+                .unwrap();
+            out
+        };
+
+        assert_eq!(
+            rrule(
+                RecurrenceRuleBuilder::default()
+                    .weekly()
+                    .on_days(&[ICalendarWeekday::Monday, ICalendarWeekday::Wednesday])
+                    .interval(2)
+                    .count(10)
+                    .build()
+            ),
+            "RRULE:FREQ=WEEKLY;COUNT=10;INTERVAL=2;BYDAY=MO,WE\r\n"
+        );
+        assert_eq!(
+            rrule(
+                RecurrenceRuleBuilder::new(ICalendarFrequency::Monthly)
+                    .by_month_day(&[-1])
+                    .by_month(&[1, 7])
+                    .count(5)
+                    .until(PartialDateTime {
+                        year: Some(2025),
+                        month: Some(12),
+                        day: Some(31),
+                        ..Default::default()
+                    })
+                    .build()
+            ),
+            "RRULE:FREQ=MONTHLY;UNTIL=20251231;BYMONTHDAY=-1;BYMONTH=1,7\r\n"
+        );
+        assert_eq!(
+            rrule(
+                RecurrenceRuleBuilder::default()
+                    .yearly()
+                    .on_nth_day(-1, ICalendarWeekday::Friday)
+                    .by_month(&[11])
+                    .week_start(ICalendarWeekday::Monday)
+                    .build()
+            ),
+            "RRULE:FREQ=YEARLY;BYDAY=-1FR;BYMONTH=11;WKST=MO\r\n"
+        );
+    }
+
+    #[test]
+    fn prodid_and_version() {
+        let mut ical = ICalendar::parse(concat!(
+            "BEGIN:VCALENDAR\r\n",
+            "PRODID:-//Original//EN\r\n",
+            "VERSION:1.0\r\n",
+            "BEGIN:VEVENT\r\n",
+            "UID:1\r\n",
+            "END:VEVENT\r\n",
+            "END:VCALENDAR\r\n"
+        ))
+        .unwrap();
+
+        let mut out = String::new();
+        ical.write_with_options(
+            &mut out,
+            &WriterOptions::default()
+                .prodid("-//My App//EN")
+                .force_version(true),
+        )
+        .unwrap();
+        assert_eq!(
+            out,
+            concat!(
+                "BEGIN:VCALENDAR\r\n",
+                "VERSION:2.0\r\n",
+                "PRODID:-//My App//EN\r\n",
+                "BEGIN:VEVENT\r\n",
+                "UID:1\r\n",
+                "END:VEVENT\r\n",
+                "END:VCALENDAR\r\n"
+            )
+        );
+
+        ical.set_prodid("-//Other App//EN");
+        assert_eq!(
+            ical.components[0]
+                .properties(&ICalendarProperty::Prodid)
+                .map(|entry| entry.values.clone())
+                .collect::<Vec<_>>(),
+            [vec![ICalendarValue::Text("-//Other App//EN".to_string())]]
+        );
+        assert!(ical.to_string().contains("VERSION:1.0\r\n"));
+
+        // vCards always carry VERSION, PRODID is replaced the same way
+        let vcard = crate::vcard::VCard::parse(concat!(
+            "BEGIN:VCARD\r\n",
+            "VERSION:4.0\r\n",
+            "PRODID:-//Original//EN\r\n",
+            "FN:Jane Doe\r\n",
+            "END:VCARD\r\n"
+        ))
+        .unwrap();
+        let mut out = String::new();
+        vcard
+            .write_with_options(
+                &mut out,
+                crate::vcard::VCardVersion::V4_0,
+                &WriterOptions::default().prodid(format!("-//My App {}//EN", "1.0")),
+            )
+            .unwrap();
+        assert_eq!(
+            out,
+            concat!(
+                "BEGIN:VCARD\r\n",
+                "VERSION:4.0\r\n",
+                "PRODID:-//My App 1.0//EN\r\n",
+                "FN:Jane Doe\r\n",
+                "END:VCARD\r\n"
+            )
+        );
+    }
+
+    #[test]
+    fn fold_width() {
+        let description = "Ünïcödé text that is long enough to need folding 🎉🎉🎉 ".repeat(3);
+        let entry =
+            ICalendarEntry::new(ICalendarProperty::Description).with_value(description.as_str());
+        let expected = format!("DESCRIPTION:{description}");
+
+        let mut out = String::new();
+        entry
+            .write_with_options(&mut out, &WriterOptions::default().fold_width(None))
+            .unwrap();
+        assert_eq!(out, format!("{expected}\r\n"));
+
+        for fold_width in [Some(75), Some(30), Some(16)] {
+            let mut out = String::new();
+            entry
+                .write_with_options(&mut out, &WriterOptions::default().fold_width(fold_width))
+                .unwrap();
+            let lines = out
+                .strip_suffix("\r\n")
+                .unwrap()
+                .split("\r\n")
+                .collect::<Vec<_>>();
+            assert!(lines.len() > 1);
+            assert!(
+                lines[1..]
+                    .iter()
+                    .all(|line| line.len() <= fold_width.unwrap())
+            );
+            assert_eq!(
+                lines
+                    .iter()
+                    .enumerate()
+                    .map(|(pos, line)| if pos > 0 { &line[1..] } else { line })
+                    .collect::<String>(),
+                expected
+            );
+        }
+
+        let mut out = String::new();
+        entry.write_to(&mut out).unwrap();
+        let mut default_out = String::new();
+        entry
+            .write_with_options(
+                &mut default_out,
+                &WriterOptions::default().fold_width(Some(75)),
+            )
+            .unwrap();
+        assert_eq!(out, default_out);
+    }
+
+    #[test]
+    fn set_date_times() {
+        let mut event = ICalendar::parse(concat!(
+            "BEGIN:VCALENDAR\r\n",
+            "BEGIN:VEVENT\r\n",
+            "UID:event\r\n",
+            "DTSTART;TZID=Europe/Madrid:20250110T100000\r\n",
+            "DTSTART:20250111T100000Z\r\n",
+            "DURATION:PT1H\r\n",
+            "END:VEVENT\r\n",
+            "END:VCALENDAR\r\n"
+        ))
+        .unwrap()
+        .components
+        .remove(1);
+
+        event.set_dtstart(dt("20250110"), Some("Europe/Madrid"));
+        event.set_dtend(dt("20250111"), None);
+        assert_eq!(
+            write_entries(&event),
+            concat!(
+                "UID:event\n",
+                "DTSTART;VALUE=DATE:20250110\n",
+                "DTEND;VALUE=DATE:20250111\n",
+            )
+        );
+
+        event.set_dtstart(dt("20250110T100000"), Some("Europe/Madrid"));
+        event.set_dtend(dt("20250110T120000Z"), Some("Europe/Madrid"));
+        assert_eq!(
+            write_entries(&event),
+            concat!(
+                "UID:event\n",
+                "DTSTART;TZID=Europe/Madrid:20250110T100000\n",
+                "DTEND:20250110T120000Z\n",
+            )
+        );
+
+        let mut todo = ICalendarComponent::new(ICalendarComponentType::VTodo);
+        todo.add_property(
+            ICalendarProperty::Duration,
+            ICalendarDuration {
+                hours: 1,
+                ..Default::default()
+            },
+        );
+        todo.set_due(dt("20250112T090000"), None);
+        assert_eq!(write_entries(&todo), "DUE:20250112T090000\n");
+    }
+
+    #[test]
+    fn remove_component() {
+        let mut ical = ICalendar::parse(concat!(
+            "BEGIN:VCALENDAR\r\n",
+            "BEGIN:VEVENT\r\n",
+            "UID:first\r\n",
+            "BEGIN:VALARM\r\n",
+            "ACTION:DISPLAY\r\n",
+            "DESCRIPTION:first alarm\r\n",
+            "END:VALARM\r\n",
+            "END:VEVENT\r\n",
+            "BEGIN:VEVENT\r\n",
+            "UID:middle\r\n",
+            "BEGIN:VALARM\r\n",
+            "ACTION:DISPLAY\r\n",
+            "DESCRIPTION:middle alarm\r\n",
+            "END:VALARM\r\n",
+            "END:VEVENT\r\n",
+            "BEGIN:VEVENT\r\n",
+            "UID:last\r\n",
+            "SUMMARY:Last\r\n",
+            "BEGIN:VALARM\r\n",
+            "ACTION:DISPLAY\r\n",
+            "DESCRIPTION:last alarm\r\n",
+            "END:VALARM\r\n",
+            "END:VEVENT\r\n",
+            "END:VCALENDAR\r\n"
+        ))
+        .unwrap();
+        let middle = ical
+            .components
+            .iter()
+            .position(|component| component.uid() == Some("middle"))
+            .unwrap();
+        ical.remove_component(middle);
+        ical.remove_component(99);
+
+        // The middle event and its alarm are gone, the rest still resolves
+        assert_eq!(ical.components.len(), 5);
+        let events = ical
+            .children(0)
+            .map(|component| component.uid().unwrap())
+            .collect::<Vec<_>>();
+        assert_eq!(events, ["first", "last"]);
+        for (idx, uid) in ical.components[0]
+            .component_ids
+            .clone()
+            .into_iter()
+            .zip(events)
+        {
+            let alarms = ical
+                .children(idx as usize)
+                .map(|alarm| {
+                    alarm
+                        .property(&ICalendarProperty::Description)
+                        .and_then(|entry| entry.values[0].as_text())
+                        .unwrap()
+                })
+                .collect::<Vec<_>>();
+            assert_eq!(alarms, [format!("{uid} alarm")]);
+        }
+
+        let last = ical.components[0].component_ids[1] as usize;
+        ical.components[last].remove_entries(&ICalendarProperty::Summary);
+        assert!(!ical.components[last].has_property(&ICalendarProperty::Summary));
+        assert!(ical.components[last].has_property(&ICalendarProperty::Uid));
+    }
 
     #[test]
     fn remove_component_ids() {
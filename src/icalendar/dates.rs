@@ -5,17 +5,29 @@
  */
 
 use super::{
-    ICalendar, ICalendarComponent, ICalendarComponentType, ICalendarPeriod, ICalendarProperty,
-    ICalendarValue, timezone::TzResolver,
+    ICalendar, ICalendarComponent, ICalendarComponentType, ICalendarFrequency,
+    ICalendarParameterValue, ICalendarPeriod, ICalendarProperty, ICalendarRelated, ICalendarValue,
+    timezone::{TzResolver, add_duration},
 };
 use crate::{
-    common::{DateTimeResult, timezone::Tz},
+    common::{DateTimeResult, PartialDateTime, timezone::Tz},
     datecalc::{RRuleIter, error::RRuleError, rrule::RRule},
     icalendar::ICalendarParameterName,
 };
 use ahash::{AHashMap, AHashSet};
-use chrono::{DateTime, TimeDelta, TimeZone, Timelike};
-use std::fmt::{Display, Formatter};
+use chrono::{
+    DateTime, Datelike, NaiveDate, NaiveDateTime, NaiveTime, TimeDelta, TimeZone, Timelike,
+};
+use std::{
+    borrow::Borrow,
+    fmt::{Display, Formatter},
+    hash::Hash,
+    iter::Peekable,
+};
+
+const MAX_FILTER_OCCURRENCES: usize = 10_000;
+// Upper bound on the instances walked while looking for unexcluded occurrences
+const MAX_SCANNED_OCCURRENCES: usize = 100_000;
 
 #[allow(clippy::type_complexity)]
 #[derive(Debug, Default, Clone, PartialEq, Eq)]
@@ -502,6 +514,689 @@ impl ICalendarComponent {
     }
 }
 
+impl ICalendarComponent {
+    pub fn expand_all_day_dates(
+        &self,
+        mut limit: usize,
+    ) -> Result<Vec<NaiveDate>, CalendarErrorType> {
+        let mut dt_start = None;
+        let mut rrule = None;
+        let mut rdates = Vec::new();
+        let mut exdates = AHashSet::new();
+
+        for entry in &self.entries {
+            match (&entry.name, entry.values.first()) {
+                (ICalendarProperty::Dtstart, Some(ICalendarValue::PartialDateTime(dt))) => {
+                    if dt.has_time() {
+                        return Err(CalendarErrorType::InvalidDtStart);
+                    }
+                    dt_start = Some(
+                        dt.to_date_time()
+                            .ok_or(CalendarErrorType::InvalidDtStart)?
+                            .date_time
+                            .date(),
+                    );
+                }
+                (ICalendarProperty::Rrule, Some(ICalendarValue::RecurrenceRule(rule))) => {
+                    rrule = RRule::from_floating_ical(rule);
+                }
+                (ICalendarProperty::Rdate | ICalendarProperty::Exdate, _) => {
+                    for value in &entry.values {
+                        if let ICalendarValue::PartialDateTime(dt) = value
+                            && let Some(dt) = dt.to_date_time()
+                        {
+                            if entry.name == ICalendarProperty::Rdate {
+                                rdates.push(dt.date_time.date());
+                            } else {
+                                exdates.insert(dt.date_time.date());
+                            }
+                        }
+                    }
+                }
+                _ => (),
+            }
+        }
+
+        let dt_start = dt_start.ok_or(CalendarErrorType::MissingDtStart)?;
+        let mut dates = if let Some(rrule) = rrule {
+            let floating_start = Tz::Floating
+                .from_local_datetime(&dt_start.and_time(NaiveTime::MIN))
+                .single()
+                .ok_or(CalendarErrorType::InvalidDtStart)?;
+            let rrule = rrule
+                .validate(floating_start)
+                .map_err(CalendarErrorType::RRule)?;
+            let mut dates = Vec::new();
+            for date in RRuleIter::new(&rrule, &floating_start, true) {
+                if limit != 0 {
+                    limit -= 1;
+                } else {
+                    break;
+                }
+                dates.push(date.date_naive());
+            }
+            dates
+        } else {
+            vec![dt_start]
+        };
+
+        dates.extend(rdates);
+        dates.retain(|date| !exdates.contains(date));
+        dates.sort_unstable();
+        dates.dedup();
+
+        Ok(dates)
+    }
+}
+
+struct RecurrenceParts<'x> {
+    dt_start: DateTimeResult,
+    tz_id: Option<&'x str>,
+    rrule: Option<RRule>,
+    exrules: Vec<RRule>,
+    rdates: Vec<(Option<&'x str>, DateTimeResult)>,
+    exdates: Vec<(Option<&'x str>, DateTimeResult)>,
+}
+
+impl ICalendarComponent {
+    pub fn expand_occurrences(&self, limit: usize) -> Vec<DateTimeResult> {
+        self.occurrences(None, limit)
+            .into_iter()
+            .map(|(_, dt)| dt)
+            .collect()
+    }
+
+    // Occurrences with the TZID they are expressed in, optionally restricted to those
+    // starting within a window of local times
+    pub(crate) fn occurrences(
+        &self,
+        window: Option<(NaiveDateTime, NaiveDateTime)>,
+        limit: usize,
+    ) -> Vec<(Option<&str>, DateTimeResult)> {
+        let Some(parts) = self.recurrence_parts() else {
+            return vec![];
+        };
+        let from = window.map(|(from, _)| from);
+        let in_window = |dt: &DateTimeResult| {
+            window.is_none_or(|(from, to)| dt.date_time >= from && dt.date_time < to)
+        };
+        let mut exrules = parts.exrule_iters(from);
+        // Exclusions are applied before the limit so that they do not eat into it
+        let mut occurrences = if let Some(rrule) = parts.rrule_iter(from) {
+            rrule
+                .take(MAX_SCANNED_OCCURRENCES.max(limit))
+                .map(|date| DateTimeResult {
+                    date_time: date.naive_local(),
+                    offset: parts.dt_start.offset,
+                })
+                .skip_while(|dt| from.is_some_and(|from| dt.date_time < from))
+                .take_while(|dt| window.is_none_or(|(_, to)| dt.date_time < to))
+                .filter(|dt| {
+                    !parts.is_excluded(parts.tz_id, dt)
+                        && !is_exrule_match(&mut exrules, &dt.date_time)
+                })
+                .take(limit)
+                .map(|dt| (parts.tz_id, dt))
+                .collect::<Vec<_>>()
+        } else {
+            vec![(parts.tz_id, parts.dt_start.clone())]
+        };
+
+        occurrences.extend(parts.rdates.iter().cloned());
+        occurrences.retain(|(tz_id, dt)| in_window(dt) && !parts.is_excluded(*tz_id, dt));
+        occurrences.sort_by_key(|(tz_id, dt)| {
+            (
+                dt.date_time,
+                *tz_id,
+                dt.offset.map(|offset| offset.local_minus_utc()),
+            )
+        });
+        occurrences.dedup();
+        let mut exrules = parts.exrule_iters(from);
+        if !exrules.is_empty() {
+            occurrences.retain(|(tz_id, dt)| {
+                *tz_id != parts.tz_id || !is_exrule_match(&mut exrules, &dt.date_time)
+            });
+        }
+        occurrences.truncate(limit);
+
+        occurrences
+    }
+
+    pub fn next_occurrence_after(
+        &self,
+        after: &PartialDateTime,
+        resolver: &TzResolver<impl Borrow<str> + Hash + Eq>,
+    ) -> Option<DateTimeResult> {
+        let after = after.to_date_time_with(None, resolver)?.timestamp();
+        let parts = self.recurrence_parts()?;
+        let is_next = |tz_id: Option<&str>, dt: &DateTimeResult| {
+            !parts.is_excluded(tz_id, dt)
+                && dt
+                    .to_date_time_with(tz_id, resolver)
+                    .is_some_and(|dt| dt.timestamp() > after)
+        };
+
+        // Local times are within a day of UTC, so instances before this cannot be next
+        let from = DateTime::from_timestamp(after, 0)?.naive_utc() - TimeDelta::days(1);
+        let mut exrules = parts.exrule_iters(Some(from));
+        let next = if let Some(rrule) = parts.rrule_iter(Some(from)) {
+            rrule
+                .take(MAX_SCANNED_OCCURRENCES)
+                .map(|date| DateTimeResult {
+                    date_time: date.naive_local(),
+                    offset: parts.dt_start.offset,
+                })
+                .find(|dt| {
+                    is_next(parts.tz_id, dt) && !is_exrule_match(&mut exrules, &dt.date_time)
+                })
+                .map(|dt| (parts.tz_id, dt))
+        } else {
+            Some((parts.tz_id, parts.dt_start.clone())).filter(|(tz_id, dt)| {
+                is_next(*tz_id, dt) && !is_exrule_match(&mut exrules, &dt.date_time)
+            })
+        };
+
+        let mut rdates = parts.rdates.clone();
+        rdates.sort_by_key(|(_, dt)| dt.date_time);
+        let mut exrules = parts.exrule_iters(None);
+        next.into_iter()
+            .chain(rdates.into_iter().filter(|(tz_id, dt)| {
+                is_next(*tz_id, dt)
+                    && (*tz_id != parts.tz_id || !is_exrule_match(&mut exrules, &dt.date_time))
+            }))
+            .filter_map(|(tz_id, dt)| {
+                let timestamp = dt.to_date_time_with(tz_id, resolver)?.timestamp();
+                Some((timestamp, dt))
+            })
+            .min_by_key(|(timestamp, _)| *timestamp)
+            .map(|(_, dt)| dt)
+    }
+
+    fn recurrence_parts(&self) -> Option<RecurrenceParts<'_>> {
+        let mut dt_start = None;
+        let mut dt_start_tzid = None;
+        let mut rrule = None;
+        let mut exrules = Vec::new();
+        let mut rdates = Vec::new();
+        let mut exdates = Vec::new();
+
+        for entry in &self.entries {
+            match (&entry.name, entry.values.first()) {
+                (ICalendarProperty::Dtstart, Some(ICalendarValue::PartialDateTime(dt))) => {
+                    dt_start = dt.to_date_time();
+                    dt_start_tzid = entry.tz_id();
+                }
+                (ICalendarProperty::Rrule, Some(ICalendarValue::RecurrenceRule(rule))) => {
+                    rrule = RRule::from_floating_ical(rule);
+                }
+                (ICalendarProperty::Exrule, Some(ICalendarValue::RecurrenceRule(rule))) => {
+                    exrules.extend(RRule::from_floating_ical(rule));
+                }
+                (ICalendarProperty::Rdate | ICalendarProperty::Exdate, _) => {
+                    let tz_id = entry.tz_id();
+                    for value in &entry.values {
+                        let dt = match value {
+                            ICalendarValue::PartialDateTime(dt) => dt.to_date_time(),
+                            ICalendarValue::Period(
+                                ICalendarPeriod::Range { start, .. }
+                                | ICalendarPeriod::Duration { start, .. },
+                            ) if entry.name == ICalendarProperty::Rdate => start.to_date_time(),
+                            _ => None,
+                        };
+
+                        if let Some(dt) = dt {
+                            if entry.name == ICalendarProperty::Rdate {
+                                rdates.push((tz_id, dt));
+                            } else {
+                                exdates.push((tz_id, dt));
+                            }
+                        }
+                    }
+                }
+                _ => (),
+            }
+        }
+
+        Some(RecurrenceParts {
+            dt_start: dt_start?,
+            rrule,
+            exrules,
+            rdates: rdates
+                .into_iter()
+                .map(|(tz_id, dt)| (tz_id.or(dt_start_tzid), dt))
+                .collect(),
+            exdates: exdates
+                .into_iter()
+                .map(|(tz_id, dt)| (tz_id.or(dt_start_tzid), dt))
+                .collect(),
+            tz_id: dt_start_tzid,
+        })
+    }
+}
+
+impl ICalendarComponent {
+    pub fn trigger_instants(
+        &self,
+        start: DateTime<Tz>,
+        end: Option<DateTime<Tz>>,
+    ) -> impl Iterator<Item = DateTime<Tz>> + '_ {
+        let entry = self.property(&ICalendarProperty::Trigger);
+        let trigger = match entry.and_then(|entry| entry.values.first()) {
+            Some(ICalendarValue::Duration(duration)) => {
+                let anchor = if matches!(
+                    entry.and_then(|entry| entry.parameter(&ICalendarParameterName::Related)),
+                    Some(ICalendarParameterValue::Related(ICalendarRelated::End))
+                ) {
+                    end
+                } else {
+                    Some(start)
+                };
+                anchor.and_then(|anchor| add_duration(&anchor, duration))
+            }
+            Some(ICalendarValue::PartialDateTime(dt)) => dt
+                .to_date_time()
+                .and_then(|dt| dt.to_date_time_with_tz(Tz::UTC))
+                .map(|dt| dt.with_timezone(&start.timezone())),
+            _ => None,
+        };
+
+        // Repetitions are yielded lazily, REPEAT is not bounded by the RFC
+        let interval = self
+            .repeat_interval()
+            .filter(|duration| !duration.neg && duration.to_seconds() > 0);
+        let repeat = interval
+            .and(self.repeat_count())
+            .map_or(0, |count| usize::try_from(count).unwrap_or(usize::MAX));
+
+        std::iter::successors(trigger, move |instant| add_duration(instant, interval?))
+            .take(repeat.saturating_add(1))
+    }
+}
+
+impl RecurrenceParts<'_> {
+    fn is_excluded(&self, tz_id: Option<&str>, dt: &DateTimeResult) -> bool {
+        self.exdates
+            .iter()
+            .any(|(ex_tz_id, ex_dt)| *ex_tz_id == tz_id && ex_dt == dt)
+    }
+
+    fn rrule_iter(&self, from: Option<NaiveDateTime>) -> Option<RRuleIter> {
+        self.rrule
+            .as_ref()
+            .and_then(|rule| self.series_iter(rule, from))
+    }
+
+    // EXRULE is deprecated since RFC 5545 but still honoured for RFC 2445 archives
+    fn exrule_iters(&self, from: Option<NaiveDateTime>) -> Vec<Peekable<RRuleIter>> {
+        self.exrules
+            .iter()
+            .filter_map(|rule| self.series_iter(rule, from))
+            .map(Iterator::peekable)
+            .collect()
+    }
+
+    // Instances before `from` may be skipped
+    fn series_iter(&self, rule: &RRule, from: Option<NaiveDateTime>) -> Option<RRuleIter> {
+        let floating_start = Tz::Floating
+            .from_local_datetime(&self.dt_start.date_time)
+            .single()?;
+        let rule = rule.clone().validate(floating_start).ok()?;
+        let start = match from {
+            Some(from) => Tz::Floating
+                .from_local_datetime(&seek_start(&rule, self.dt_start.date_time, from))
+                .single()?,
+            None => floating_start,
+        };
+
+        Some(RRuleIter::new(&rule, &start, true))
+    }
+}
+
+// Moves the start of a series forward by whole periods of its rule, keeping at least one
+// period before `target` so that the instances from `target` onwards are unchanged.
+// Rules with a COUNT have to be walked from the beginning.
+fn seek_start(rule: &RRule, dt_start: NaiveDateTime, target: NaiveDateTime) -> NaiveDateTime {
+    if rule.count.is_some() || target <= dt_start {
+        return dt_start;
+    }
+    let interval = i64::from(rule.interval.max(1));
+    let period_start = |months: i64| {
+        NaiveDate::from_ymd_opt(
+            i32::try_from(months.div_euclid(12)).ok()?,
+            months.rem_euclid(12) as u32 + 1,
+            1,
+        )
+        .map(|date| date.and_time(NaiveTime::MIN))
+    };
+
+    let seeked = match rule.freq {
+        ICalendarFrequency::Yearly | ICalendarFrequency::Monthly => {
+            let unit = if rule.freq == ICalendarFrequency::Yearly {
+                12
+            } else {
+                1
+            };
+            let months = |dt: NaiveDateTime| i64::from(dt.year()) * 12 + i64::from(dt.month0());
+            let periods = (months(target) - months(dt_start)) / (unit * interval) - 1;
+            (periods > 0)
+                .then(|| period_start(months(dt_start) + periods * unit * interval))
+                .flatten()
+        }
+        freq => {
+            let unit = match freq {
+                ICalendarFrequency::Weekly => 7 * 86400,
+                ICalendarFrequency::Daily => 86400,
+                ICalendarFrequency::Hourly => 3600,
+                ICalendarFrequency::Minutely => 60,
+                _ => 1,
+            };
+            let periods = (target - dt_start).num_seconds() / (unit * interval) - 1;
+            (periods > 0)
+                .then(|| dt_start.checked_add_signed(TimeDelta::seconds(periods * unit * interval)))
+                .flatten()
+        }
+    };
+
+    seeked.unwrap_or(dt_start)
+}
+
+// Callers must check dates in ascending order, as the iterators are only advanced
+fn is_exrule_match(exrules: &mut [Peekable<RRuleIter>], dt: &NaiveDateTime) -> bool {
+    exrules.iter_mut().any(|iter| {
+        while iter.next_if(|ex| ex.naive_local() < *dt).is_some() {}
+        iter.peek().is_some_and(|ex| ex.naive_local() == *dt)
+    })
+}
+
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct RecurrenceSet<'x> {
+    pub master: Option<&'x ICalendarComponent>,
+    pub overrides: Vec<&'x ICalendarComponent>,
+}
+
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct RecurrenceInstance<'x> {
+    pub recurrence_id: DateTimeResult,
+    pub start: DateTimeResult,
+    // TZID of the start
+    pub tz_id: Option<&'x str>,
+    pub component: &'x ICalendarComponent,
+}
+
+impl ICalendar {
+    pub fn resolve_recurrence_set(&self, uid: &str) -> RecurrenceSet<'_> {
+        let mut set = RecurrenceSet {
+            master: None,
+            overrides: Vec::new(),
+        };
+
+        for component in &self.components {
+            if component.component_type.has_time_ranges() && component.uid() == Some(uid) {
+                if component
+                    .property(&ICalendarProperty::RecurrenceId)
+                    .is_some()
+                {
+                    set.overrides.push(component);
+                } else if set.master.is_none() {
+                    set.master = Some(component);
+                }
+            }
+        }
+
+        set
+    }
+}
+
+impl<'x> RecurrenceSet<'x> {
+    pub fn expand(
+        &self,
+        resolver: &TzResolver<impl Borrow<str> + Hash + Eq>,
+        limit: usize,
+    ) -> Vec<RecurrenceInstance<'x>> {
+        self.expand_within(resolver, None, limit)
+    }
+
+    // With a window, only the instances that may overlap the given UTC timestamps are
+    // guaranteed to be returned
+    pub(crate) fn expand_within(
+        &self,
+        resolver: &TzResolver<impl Borrow<str> + Hash + Eq>,
+        window: Option<(i64, i64)>,
+        limit: usize,
+    ) -> Vec<RecurrenceInstance<'x>> {
+        let mut overrides = self
+            .overrides
+            .iter()
+            .filter_map(|&component| {
+                let entry = component.property(&ICalendarProperty::RecurrenceId)?;
+                let rid = entry
+                    .values
+                    .first()?
+                    .as_partial_date_time()?
+                    .to_date_time()?;
+                let timestamp = rid.to_date_time_with(entry.tz_id(), resolver)?.timestamp();
+                let this_and_future = entry
+                    .parameters(&ICalendarParameterName::Range)
+                    .next()
+                    .is_some();
+                let (start, tz_id) = match component.property(&ICalendarProperty::Dtstart) {
+                    Some(dt_start) => (component.dtstart()?.to_date_time()?, dt_start.tz_id()),
+                    None => (rid.clone(), entry.tz_id()),
+                };
+
+                Some((timestamp, rid, start, tz_id, this_and_future, component))
+            })
+            .collect::<Vec<_>>();
+        let mut instances = Vec::new();
+
+        if let Some(master) = self.master {
+            let window = window.and_then(|(start, end)| {
+                let length = master
+                    .dtstart()
+                    .and_then(|start| start.to_date_time())
+                    .zip(master.effective_end(resolver))
+                    .map_or(TimeDelta::zero(), |(start, end)| {
+                        (end.date_time - start.date_time).max(TimeDelta::zero())
+                    });
+                // Instances moved by a THISANDFUTURE override may come from outside the window
+                let moved = overrides
+                    .iter()
+                    .filter(|(_, _, _, _, this_and_future, _)| *this_and_future)
+                    .map(|(_, rid, start, ..)| (rid.date_time, start.date_time - rid.date_time));
+                let shift = moved
+                    .clone()
+                    .map(|(_, shift)| shift.abs())
+                    .max()
+                    .unwrap_or_default();
+                let first_rid = moved.map(|(rid, _)| rid).min();
+                // Local times are within a day of UTC
+                let margin = TimeDelta::days(1) + shift;
+                let from = DateTime::from_timestamp(start, 0)?.naive_utc() - margin - length;
+                let to = DateTime::from_timestamp(end, 0)?.naive_utc() + margin;
+                Some((first_rid.map_or(from, |rid| rid.min(from)), to))
+            });
+            let mut this_and_future: Option<(TimeDelta, &ICalendarComponent)> = None;
+
+            for (tz_id, occurrence) in master.occurrences(window, limit) {
+                let Some(timestamp) = occurrence
+                    .to_date_time_with(tz_id, resolver)
+                    .map(|dt| dt.timestamp())
+                else {
+                    continue;
+                };
+
+                if let Some(pos) = overrides
+                    .iter()
+                    .position(|(rid_timestamp, ..)| *rid_timestamp == timestamp)
+                {
+                    let (_, rid, start, start_tz_id, is_this_and_future, component) =
+                        overrides.swap_remove(pos);
+                    if is_this_and_future {
+                        this_and_future = Some((start.date_time - rid.date_time, component));
+                    }
+                    instances.push((
+                        timestamp,
+                        RecurrenceInstance {
+                            recurrence_id: occurrence,
+                            start,
+                            tz_id: start_tz_id,
+                            component,
+                        },
+                    ));
+                } else if let Some((shift, component)) = this_and_future {
+                    instances.push((
+                        timestamp,
+                        RecurrenceInstance {
+                            start: DateTimeResult {
+                                date_time: occurrence.date_time + shift,
+                                offset: occurrence.offset,
+                            },
+                            recurrence_id: occurrence,
+                            tz_id,
+                            component,
+                        },
+                    ));
+                } else {
+                    instances.push((
+                        timestamp,
+                        RecurrenceInstance {
+                            start: occurrence.clone(),
+                            recurrence_id: occurrence,
+                            tz_id,
+                            component: master,
+                        },
+                    ));
+                }
+            }
+        }
+
+        // Overrides that do not match any occurrence are still part of the set
+        instances.extend(overrides.into_iter().map(
+            |(timestamp, rid, start, tz_id, _, component)| {
+                (
+                    timestamp,
+                    RecurrenceInstance {
+                        recurrence_id: rid,
+                        start,
+                        tz_id,
+                        component,
+                    },
+                )
+            },
+        ));
+        instances.sort_by_key(|(timestamp, _)| *timestamp);
+
+        instances
+            .into_iter()
+            .map(|(_, instance)| instance)
+            .collect()
+    }
+}
+
+impl ICalendar {
+    // Floating times and dates are interpreted in the given timezone, as required by
+    // RFC 4791, Section 9.9
+    pub fn filter_time_range(
+        &self,
+        start: &PartialDateTime,
+        end: &PartialDateTime,
+        default_tz: impl Into<Tz>,
+    ) -> ICalendar {
+        let resolver = self.build_tz_resolver().with_default(default_tz);
+        let range = start
+            .to_date_time_with(None, &resolver)
+            .zip(end.to_date_time_with(None, &resolver))
+            .map(|(start, end)| (start.timestamp(), end.timestamp()));
+        let overlaps = |(start, end): (i64, i64)| {
+            range.is_some_and(|(range_start, range_end)| {
+                if start == end {
+                    start >= range_start && start < range_end
+                } else {
+                    start < range_end && end > range_start
+                }
+            })
+        };
+        let instance_span = |instance: &RecurrenceInstance<'_>| {
+            let component = instance.component;
+            let tz_id = component
+                .property(&ICalendarProperty::Dtstart)
+                .and_then(|entry| entry.tz_id());
+            let start_dt = component.dtstart()?.to_date_time_with(tz_id, &resolver)?;
+            let length = component
+                .effective_end(&resolver)
+                .and_then(|end| end.to_date_time_with_tz(Tz::Floating))
+                .map_or(0, |end| (end.timestamp() - start_dt.timestamp()).max(0));
+            let start = instance
+                .start
+                .to_date_time_with(instance.tz_id, &resolver)?
+                .timestamp();
+            Some((start, start + length))
+        };
+        // Tasks without a start match on their due date, or always when undated
+        let undated_matches = |component: &ICalendarComponent| {
+            component.component_type == ICalendarComponentType::VTodo
+                && match component
+                    .property(&ICalendarProperty::Due)
+                    .and_then(|entry| {
+                        entry
+                            .values
+                            .first()?
+                            .as_partial_date_time()?
+                            .to_date_time_with(entry.tz_id(), &resolver)
+                    }) {
+                    Some(due) => range.is_some_and(|(range_start, range_end)| {
+                        range_start < due.timestamp() && range_end >= due.timestamp()
+                    }),
+                    None => component.property(&ICalendarProperty::Due).is_none(),
+                }
+        };
+        // A series is kept as a whole when any of its instances matches
+        let matches = |set: RecurrenceSet<'_>| {
+            set.master
+                .is_some_and(|master| master.dtstart().is_none() && undated_matches(master))
+                || range.is_some()
+                    && set
+                        .expand_within(&resolver, range, MAX_FILTER_OCCURRENCES)
+                        .iter()
+                        .any(|instance| instance_span(instance).is_some_and(overlaps))
+        };
+
+        let root_ids = self
+            .components
+            .first()
+            .map(|root| root.component_ids.as_slice())
+            .unwrap_or_default();
+        let mut uid_matches = AHashMap::new();
+        let mut remove_ids = Vec::new();
+        for &component_id in root_ids {
+            let Some(component) = self.components.get(component_id as usize) else {
+                continue;
+            };
+            if !component.component_type.has_time_ranges() {
+                continue;
+            }
+
+            let is_match = match component.uid() {
+                Some(uid) => *uid_matches
+                    .entry(uid)
+                    .or_insert_with(|| matches(self.resolve_recurrence_set(uid))),
+                None => matches(RecurrenceSet {
+                    master: Some(component),
+                    overrides: vec![],
+                }),
+            };
+            if !is_match {
+                remove_ids.push(component_id);
+            }
+        }
+
+        let mut filtered = self.clone();
+        filtered.remove_component_ids(&remove_ids);
+        filtered
+    }
+}
+
 impl TimeOrDelta<DateTimeResult, TimeDelta> {
     pub fn into_date_time_with_tz(self, tz: Tz) -> Option<TimeOrDelta<DateTime<Tz>, TimeDelta>> {
         match self {
@@ -555,10 +1250,13 @@ impl Display for CalendarErrorType {
 mod tests {
     use crate::{
         Entry, Parser,
-        common::timezone::Tz,
-        icalendar::dates::{CalendarError, CalendarEvent},
+        common::{PartialDateTime, timezone::Tz},
+        icalendar::{
+            ICalendar, ICalendarComponent, ICalendarDuration,
+            dates::{CalendarError, CalendarEvent},
+        },
     };
-    use chrono::DateTime;
+    use chrono::{DateTime, NaiveDate, TimeDelta, TimeZone};
     use serde::Serialize;
     use std::{io::Write, time::Instant};
 
@@ -641,4 +1339,530 @@ mod tests {
             }
         }
     }
+
+    #[test]
+    fn expand_occurrences() {
+        let ical = ICalendar::parse(concat!(
+            "BEGIN:VCALENDAR\r\n",
+            "BEGIN:VEVENT\r\n",
+            "UID:weekly\r\n",
+            "DTSTART;TZID=Europe/Berlin:20240101T100000\r\n",
+            "RRULE:FREQ=WEEKLY;COUNT=4\r\n",
+            "EXDATE;TZID=Europe/Berlin:20240108T100000\r\n",
+            "EXDATE;TZID=America/New_York:20240115T100000\r\n",
+            "RDATE;TZID=Europe/Berlin:20240122T100000,20240124T100000\r\n",
+            "RDATE;VALUE=PERIOD;TZID=Europe/Berlin:20240126T090000/PT1H\r\n",
+            "END:VEVENT\r\n",
+            "END:VCALENDAR\r\n"
+        ))
+        .unwrap();
+
+        assert_eq!(
+            ical.components[1]
+                .expand_occurrences(100)
+                .into_iter()
+                .map(|dt| dt.date_time.to_string())
+                .collect::<Vec<_>>(),
+            [
+                "2024-01-01 10:00:00",
+                "2024-01-15 10:00:00",
+                "2024-01-22 10:00:00",
+                "2024-01-24 10:00:00",
+                "2024-01-26 09:00:00",
+            ]
+        );
+        assert_eq!(ical.components[1].expand_occurrences(2).len(), 2);
+    }
+
+    #[test]
+    fn expand_exrule() {
+        let ical = ICalendar::parse(concat!(
+            "BEGIN:VCALENDAR\r\n",
+            "BEGIN:VEVENT\r\n",
+            "UID:weekdays\r\n",
+            "DTSTART;TZID=Europe/Berlin:20240101T090000\r\n",
+            "RRULE:FREQ=DAILY;COUNT=10\r\n",
+            "EXRULE:FREQ=WEEKLY;BYDAY=SA,SU\r\n",
+            "EXRULE:FREQ=DAILY;INTERVAL=4;COUNT=2\r\n",
+            "RDATE;TZID=Europe/Berlin:20240113T090000,20240115T090000\r\n",
+            "EXDATE;TZID=Europe/Berlin:20240103T090000\r\n",
+            "END:VEVENT\r\n",
+            "BEGIN:VEVENT\r\n",
+            "UID:workdays\r\n",
+            "DTSTART:20240104T090000\r\n",
+            "RRULE:FREQ=DAILY\r\n",
+            "EXRULE:FREQ=WEEKLY;BYDAY=SA,SU\r\n",
+            "END:VEVENT\r\n",
+            "END:VCALENDAR\r\n"
+        ))
+        .unwrap();
+        let event = &ical.components[1];
+
+        // Weekends and the 1st and 5th are excluded by rule, the 3rd by date
+        assert_eq!(
+            event
+                .expand_occurrences(100)
+                .into_iter()
+                .map(|dt| dt.date_time.to_string())
+                .collect::<Vec<_>>(),
+            [
+                "2024-01-02 09:00:00",
+                "2024-01-04 09:00:00",
+                "2024-01-08 09:00:00",
+                "2024-01-09 09:00:00",
+                "2024-01-10 09:00:00",
+                "2024-01-15 09:00:00",
+            ]
+        );
+
+        let resolver = ical.build_tz_resolver();
+        let after = PartialDateTime {
+            year: Some(2024),
+            month: Some(1),
+            day: Some(4),
+            hour: Some(12),
+            minute: Some(0),
+            second: Some(0),
+            tz_hour: Some(0),
+            tz_minute: Some(0),
+            tz_minus: false,
+        };
+        assert_eq!(
+            event
+                .next_occurrence_after(&after, &resolver)
+                .map(|dt| dt.date_time.to_string())
+                .as_deref(),
+            Some("2024-01-08 09:00:00")
+        );
+        let after = PartialDateTime {
+            day: Some(10),
+            ..after
+        };
+        assert_eq!(
+            event
+                .next_occurrence_after(&after, &resolver)
+                .map(|dt| dt.date_time.to_string())
+                .as_deref(),
+            Some("2024-01-15 09:00:00")
+        );
+
+        // Excluded instances do not count towards the limit
+        assert_eq!(
+            ical.components[2]
+                .expand_occurrences(5)
+                .into_iter()
+                .map(|dt| dt.date_time.to_string())
+                .collect::<Vec<_>>(),
+            [
+                "2024-01-04 09:00:00",
+                "2024-01-05 09:00:00",
+                "2024-01-08 09:00:00",
+                "2024-01-09 09:00:00",
+                "2024-01-10 09:00:00",
+            ]
+        );
+    }
+
+    #[test]
+    fn expand_all_day_dates() {
+        let ical = ICalendar::parse(
+            "BEGIN:VCALENDAR\r\nBEGIN:VEVENT\r\nUID:holiday\r\nDTSTART;VALUE=DATE:20200704\r\nRRULE:FREQ=YEARLY;COUNT=5\r\nEXDATE;VALUE=DATE:20220704\r\nEND:VEVENT\r\nEND:VCALENDAR\r\n",
+        )
+        .unwrap();
+
+        assert_eq!(
+            ical.components[1].expand_all_day_dates(100).unwrap(),
+            [2020, 2021, 2023, 2024]
+                .into_iter()
+                .map(|year| NaiveDate::from_ymd_opt(year, 7, 4).unwrap())
+                .collect::<Vec<_>>()
+        );
+    }
+
+    #[test]
+    fn next_occurrence_after() {
+        let ical = ICalendar::parse(concat!(
+            "BEGIN:VCALENDAR\r\n",
+            "BEGIN:VEVENT\r\n",
+            "UID:weekly\r\n",
+            "DTSTART;TZID=Europe/Berlin:20240101T100000\r\n",
+            "RRULE:FREQ=WEEKLY;COUNT=4\r\n",
+            "EXDATE;TZID=Europe/Berlin:20240108T100000\r\n",
+            "RDATE;TZID=Europe/Berlin:20240201T100000\r\n",
+            "END:VEVENT\r\n",
+            "BEGIN:VEVENT\r\n",
+            "UID:daily\r\n",
+            "DTSTART:20240101T090000Z\r\n",
+            "RRULE:FREQ=DAILY;UNTIL=20240103T090000Z\r\n",
+            "END:VEVENT\r\n",
+            "BEGIN:VEVENT\r\n",
+            "UID:infinite\r\n",
+            "DTSTART;TZID=America/New_York:20200101T090000\r\n",
+            "RRULE:FREQ=YEARLY\r\n",
+            "END:VEVENT\r\n",
+            "BEGIN:VEVENT\r\n",
+            "UID:minutely\r\n",
+            "DTSTART:20000101T000000Z\r\n",
+            "RRULE:FREQ=MINUTELY;INTERVAL=7\r\n",
+            "END:VEVENT\r\n",
+            "BEGIN:VEVENT\r\n",
+            "UID:cancelled\r\n",
+            "DTSTART:20240101T090000Z\r\n",
+            "RRULE:FREQ=DAILY\r\n",
+            "EXRULE:FREQ=DAILY\r\n",
+            "END:VEVENT\r\n",
+            "END:VCALENDAR\r\n"
+        ))
+        .unwrap();
+        let resolver = ical.build_tz_resolver();
+        let utc = |month, day, hour, minute| PartialDateTime {
+            year: Some(2024),
+            month: Some(month),
+            day: Some(day),
+            hour: Some(hour),
+            minute: Some(minute),
+            second: Some(0),
+            tz_hour: Some(0),
+            tz_minute: Some(0),
+            tz_minus: false,
+        };
+
+        for (comp_id, after, expected) in [
+            // Skips the excluded occurrence on the 8th
+            (1, utc(1, 1, 9, 0), Some("2024-01-15 10:00:00")),
+            // 10:00 in Berlin is 09:00 UTC, so the occurrence is not strictly after
+            (1, utc(1, 22, 9, 0), Some("2024-02-01 10:00:00")),
+            (1, utc(2, 1, 9, 0), None),
+            (2, utc(1, 2, 9, 0), Some("2024-01-03 09:00:00")),
+            (2, utc(1, 3, 9, 0), None),
+            (3, utc(6, 1, 0, 0), Some("2025-01-01 09:00:00")),
+            // Skips ahead instead of walking the series from its start
+            (4, utc(6, 1, 0, 0), Some("2024-06-01 00:07:00")),
+            // Gives up when every instance is excluded
+            (5, utc(1, 1, 0, 0), None),
+        ] {
+            assert_eq!(
+                ical.components[comp_id]
+                    .next_occurrence_after(&after, &resolver)
+                    .map(|dt| dt.date_time.to_string())
+                    .as_deref(),
+                expected,
+                "failed for component {comp_id} after {after:?}"
+            );
+        }
+    }
+
+    #[test]
+    fn seek_series_start() {
+        let target = NaiveDate::from_ymd_opt(2031, 3, 17)
+            .unwrap()
+            .and_hms_opt(5, 0, 0)
+            .unwrap();
+
+        for rule in [
+            "FREQ=YEARLY;INTERVAL=3;BYMONTH=2;BYMONTHDAY=29",
+            "FREQ=YEARLY;BYWEEKNO=1;BYDAY=MO",
+            "FREQ=MONTHLY;INTERVAL=5;BYMONTHDAY=31",
+            "FREQ=MONTHLY;BYDAY=-1FR;BYSETPOS=-1",
+            "FREQ=WEEKLY;INTERVAL=2;WKST=SU;BYDAY=SU,TH",
+            "FREQ=DAILY;INTERVAL=9;BYHOUR=4,23",
+            "FREQ=HOURLY;INTERVAL=5;BYMINUTE=10,50",
+            "FREQ=MINUTELY;INTERVAL=13",
+            "FREQ=DAILY;UNTIL=20310320T000000",
+        ] {
+            let ical = ICalendar::parse(format!(
+                "BEGIN:VCALENDAR\r\nBEGIN:VEVENT\r\nDTSTART:20200131T233000\r\nRRULE:{rule}\r\nEND:VEVENT\r\nEND:VCALENDAR\r\n"
+            ))
+            .unwrap();
+            let parts = ical.components[1].recurrence_parts().unwrap();
+            let instances = |from| {
+                parts
+                    .rrule_iter(from)
+                    .unwrap()
+                    .map(|dt| dt.naive_local())
+                    .skip_while(|dt| *dt < target)
+                    .take(20)
+                    .collect::<Vec<_>>()
+            };
+
+            assert_eq!(
+                instances(Some(target)),
+                instances(None),
+                "failed for {rule}"
+            );
+        }
+    }
+
+    #[test]
+    fn resolve_recurrence_set() {
+        let ical = ICalendar::parse(concat!(
+            "BEGIN:VCALENDAR\r\n",
+            "BEGIN:VEVENT\r\n",
+            "UID:series\r\n",
+            "SUMMARY:Master\r\n",
+            "DTSTART;TZID=Europe/Berlin:20240101T100000\r\n",
+            "RRULE:FREQ=DAILY;COUNT=5\r\n",
+            "END:VEVENT\r\n",
+            "BEGIN:VEVENT\r\n",
+            "UID:series\r\n",
+            "SUMMARY:Moved\r\n",
+            "RECURRENCE-ID:20240102T090000Z\r\n",
+            "DTSTART;TZID=Europe/Berlin:20240102T140000\r\n",
+            "END:VEVENT\r\n",
+            "BEGIN:VEVENT\r\n",
+            "UID:series\r\n",
+            "SUMMARY:Later\r\n",
+            "RECURRENCE-ID;RANGE=THISANDFUTURE;TZID=Europe/Berlin:20240104T100000\r\n",
+            "DTSTART;TZID=Europe/Berlin:20240104T110000\r\n",
+            "END:VEVENT\r\n",
+            "BEGIN:VEVENT\r\n",
+            "UID:other\r\n",
+            "DTSTART:20240101T100000Z\r\n",
+            "END:VEVENT\r\n",
+            "END:VCALENDAR\r\n"
+        ))
+        .unwrap();
+        let set = ical.resolve_recurrence_set("series");
+
+        assert_eq!(set.master, Some(&ical.components[1]));
+        assert_eq!(set.overrides, [&ical.components[2], &ical.components[3]]);
+        assert_eq!(
+            set.expand(&ical.build_tz_resolver(), 100)
+                .into_iter()
+                .map(|instance| format!(
+                    "{} {} {}",
+                    instance.recurrence_id.date_time,
+                    instance.start.date_time,
+                    instance.component.summary().unwrap()
+                ))
+                .collect::<Vec<_>>(),
+            [
+                "2024-01-01 10:00:00 2024-01-01 10:00:00 Master",
+                "2024-01-02 10:00:00 2024-01-02 14:00:00 Moved",
+                "2024-01-03 10:00:00 2024-01-03 10:00:00 Master",
+                "2024-01-04 10:00:00 2024-01-04 11:00:00 Later",
+                "2024-01-05 10:00:00 2024-01-05 11:00:00 Later",
+            ]
+        );
+    }
+
+    #[test]
+    fn alarm_trigger_instants() {
+        let ical = ICalendar::parse(concat!(
+            "BEGIN:VCALENDAR\r\n",
+            "BEGIN:VEVENT\r\n",
+            "UID:1\r\n",
+            "DTSTART:20240101T100000Z\r\n",
+            "DURATION:PT1H\r\n",
+            "BEGIN:VALARM\r\n",
+            "ACTION:DISPLAY\r\n",
+            "TRIGGER:-PT15M\r\n",
+            "REPEAT:2\r\n",
+            "DURATION:PT5M\r\n",
+            "END:VALARM\r\n",
+            "BEGIN:VALARM\r\n",
+            "ACTION:DISPLAY\r\n",
+            "TRIGGER;RELATED=END:PT10M\r\n",
+            "END:VALARM\r\n",
+            "BEGIN:VALARM\r\n",
+            "ACTION:DISPLAY\r\n",
+            "TRIGGER;VALUE=DATE-TIME:20231231T120000Z\r\n",
+            "END:VALARM\r\n",
+            "END:VEVENT\r\n",
+            "END:VCALENDAR\r\n"
+        ))
+        .unwrap();
+        let start = Tz::UTC.with_ymd_and_hms(2024, 1, 1, 10, 0, 0).unwrap();
+        let end = start + TimeDelta::hours(1);
+        let instants = |alarm: &ICalendarComponent, end| {
+            alarm
+                .trigger_instants(start, end)
+                .map(|dt| dt.to_rfc3339())
+                .collect::<Vec<_>>()
+        };
+
+        let alarm = &ical.components[2];
+        assert_eq!(alarm.repeat_count(), Some(2));
+        assert_eq!(
+            alarm.repeat_interval(),
+            Some(&ICalendarDuration {
+                minutes: 5,
+                ..Default::default()
+            })
+        );
+        assert_eq!(ical.components[1].repeat_interval(), None);
+        assert_eq!(
+            instants(alarm, Some(end)),
+            [
+                "2024-01-01T09:45:00+00:00",
+                "2024-01-01T09:50:00+00:00",
+                "2024-01-01T09:55:00+00:00"
+            ]
+        );
+        assert_eq!(
+            instants(&ical.components[3], Some(end)),
+            ["2024-01-01T11:10:00+00:00"]
+        );
+        assert!(instants(&ical.components[3], None).is_empty());
+        assert_eq!(
+            instants(&ical.components[4], None),
+            ["2023-12-31T12:00:00+00:00"]
+        );
+
+        // Days follow the wall clock across DST changes and repetitions are lazy
+        let ical = ICalendar::parse(concat!(
+            "BEGIN:VCALENDAR\r\n",
+            "BEGIN:VEVENT\r\n",
+            "UID:2\r\n",
+            "DTSTART;TZID=Europe/Berlin:20240331T100000\r\n",
+            "BEGIN:VALARM\r\n",
+            "ACTION:DISPLAY\r\n",
+            "TRIGGER:-P1D\r\n",
+            "REPEAT:4294967295\r\n",
+            "DURATION:P1D\r\n",
+            "END:VALARM\r\n",
+            "END:VEVENT\r\n",
+            "END:VCALENDAR\r\n"
+        ))
+        .unwrap();
+        let start = Tz::Tz(chrono_tz::Europe::Berlin)
+            .with_ymd_and_hms(2024, 3, 31, 10, 0, 0)
+            .unwrap();
+        assert_eq!(
+            ical.components[2]
+                .trigger_instants(start, None)
+                .take(3)
+                .map(|dt| dt.to_rfc3339())
+                .collect::<Vec<_>>(),
+            [
+                "2024-03-30T10:00:00+01:00",
+                "2024-03-31T10:00:00+02:00",
+                "2024-04-01T10:00:00+02:00"
+            ]
+        );
+    }
+
+    #[test]
+    fn filter_time_range() {
+        let ical = ICalendar::parse(concat!(
+            "BEGIN:VCALENDAR\r\n",
+            "BEGIN:VEVENT\r\n",
+            "UID:in\r\n",
+            "DTSTART:20240110T100000Z\r\n",
+            "DTEND:20240110T110000Z\r\n",
+            "BEGIN:VALARM\r\n",
+            "ACTION:DISPLAY\r\n",
+            "TRIGGER:-PT15M\r\n",
+            "END:VALARM\r\n",
+            "END:VEVENT\r\n",
+            "BEGIN:VEVENT\r\n",
+            "UID:out\r\n",
+            "DTSTART:20240112T100000Z\r\n",
+            "DURATION:PT1H\r\n",
+            "END:VEVENT\r\n",
+            "BEGIN:VEVENT\r\n",
+            "UID:weekly\r\n",
+            "DTSTART;TZID=Europe/Berlin:20231227T090000\r\n",
+            "DURATION:PT1H\r\n",
+            "RRULE:FREQ=WEEKLY\r\n",
+            "END:VEVENT\r\n",
+            "BEGIN:VEVENT\r\n",
+            "UID:weekly\r\n",
+            "RECURRENCE-ID;TZID=Europe/Berlin:20240103T090000\r\n",
+            "DTSTART;TZID=Europe/Berlin:20240103T150000\r\n",
+            "DURATION:PT1H\r\n",
+            "END:VEVENT\r\n",
+            "BEGIN:VEVENT\r\n",
+            "UID:all-day-in\r\n",
+            "DTSTART;VALUE=DATE:20240110\r\n",
+            "END:VEVENT\r\n",
+            "BEGIN:VEVENT\r\n",
+            "UID:all-day-out\r\n",
+            "DTSTART;VALUE=DATE:20240111\r\n",
+            "END:VEVENT\r\n",
+            "BEGIN:VEVENT\r\n",
+            "UID:instant-at-end\r\n",
+            "DTSTART:20240111T000000Z\r\n",
+            "END:VEVENT\r\n",
+            "BEGIN:VTODO\r\n",
+            "UID:undated-task\r\n",
+            "END:VTODO\r\n",
+            "BEGIN:VTODO\r\n",
+            "UID:due-task\r\n",
+            "DUE:20240201T000000Z\r\n",
+            "END:VTODO\r\n",
+            "BEGIN:VTODO\r\n",
+            "UID:due-at-start\r\n",
+            "DUE:20240110T000000Z\r\n",
+            "END:VTODO\r\n",
+            "BEGIN:VTODO\r\n",
+            "UID:due-at-end\r\n",
+            "DUE:20240111T000000Z\r\n",
+            "END:VTODO\r\n",
+            "BEGIN:VEVENT\r\n",
+            "UID:hourly-since-2010\r\n",
+            "DTSTART:20100101T003000Z\r\n",
+            "DURATION:PT30M\r\n",
+            "RRULE:FREQ=HOURLY\r\n",
+            "END:VEVENT\r\n",
+            "BEGIN:VEVENT\r\n",
+            "UID:floating\r\n",
+            "DTSTART:20240110T003000\r\n",
+            "DURATION:PT15M\r\n",
+            "END:VEVENT\r\n",
+            "END:VCALENDAR\r\n"
+        ))
+        .unwrap();
+        let filter = |tz: Tz| {
+            ical.filter_time_range(
+                &PartialDateTime::from_utc_timestamp(1704844800),
+                &PartialDateTime::from_utc_timestamp(1704931200),
+                tz,
+            )
+        };
+        let uids = |filtered: &ICalendar| {
+            filtered.components[0]
+                .component_ids
+                .iter()
+                .filter_map(|id| filtered.components[*id as usize].uid())
+                .map(String::from)
+                .collect::<Vec<_>>()
+        };
+
+        let filtered = filter(Tz::Floating);
+        assert_eq!(
+            uids(&filtered),
+            [
+                "in",
+                "weekly",
+                "weekly",
+                "all-day-in",
+                "undated-task",
+                "due-at-end",
+                "hourly-since-2010",
+                "floating"
+            ]
+        );
+        assert_eq!(filtered.components.len(), 10);
+        assert_eq!(
+            filtered.components[1].component_ids,
+            [2],
+            "alarms are kept with their event"
+        );
+
+        // Floating times follow the calendar timezone, 00:30 in Berlin is still on the 9th
+        assert_eq!(
+            uids(&filter(Tz::Tz(chrono_tz::Europe::Berlin))),
+            [
+                "in",
+                "weekly",
+                "weekly",
+                "all-day-in",
+                "all-day-out",
+                "undated-task",
+                "due-at-end",
+                "hourly-since-2010"
+            ]
+        );
+    }
 }
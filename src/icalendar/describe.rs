@@ -0,0 +1,281 @@
+/*
+ * SPDX-FileCopyrightText: 2020 Stalwart Labs LLC <hello@stalw.art>
+ *
+ * SPDX-License-Identifier: Apache-2.0 OR MIT
+ */
+
+use super::{ICalendarDay, ICalendarFrequency, ICalendarRecurrenceRule, ICalendarWeekday};
+use crate::common::{CalendarScale, PartialDateTime};
+
+// All wording goes through this trait so other languages can be plugged in later
+trait RuleLocale {
+    fn every(&self, freq: &ICalendarFrequency, interval: u16) -> String;
+    fn weekday(&self, day: &ICalendarWeekday) -> &'static str;
+    fn month(&self, month: u8) -> &'static str;
+    fn nth_weekday(&self, day: &ICalendarDay) -> String;
+    fn list(&self, items: Vec<String>) -> String;
+    fn on(&self, what: String) -> String;
+    fn on_month_days(&self, days: &[i8]) -> String;
+    fn in_months(&self, months: String) -> String;
+    fn times(&self, count: u32) -> String;
+    fn until(&self, dt: &PartialDateTime) -> String;
+    fn custom(&self) -> &'static str;
+}
+
+struct English;
+
+impl ICalendarRecurrenceRule {
+    pub fn describe(&self) -> String {
+        self.describe_with(&English)
+    }
+
+    fn describe_with(&self, locale: &impl RuleLocale) -> String {
+        let mut text = locale.every(&self.freq, self.interval.unwrap_or(1).max(1));
+        let is_custom = !self.bysecond.is_empty()
+            || !self.byminute.is_empty()
+            || !self.byhour.is_empty()
+            || !self.byyearday.is_empty()
+            || !self.byweekno.is_empty()
+            || !self.bysetpos.is_empty()
+            || self.bymonth.iter().any(|month| month.is_leap())
+            || self
+                .rscale
+                .as_ref()
+                .is_some_and(|rscale| rscale != &CalendarScale::Gregorian)
+            || matches!(
+                self.freq,
+                ICalendarFrequency::Hourly
+                    | ICalendarFrequency::Minutely
+                    | ICalendarFrequency::Secondly
+            ) && (!self.byday.is_empty()
+                || !self.bymonthday.is_empty()
+                || !self.bymonth.is_empty());
+
+        if is_custom {
+            text.push_str(locale.custom());
+        } else {
+            if !self.byday.is_empty() {
+                let days = self
+                    .byday
+                    .iter()
+                    .map(|day| {
+                        if day.ordwk.is_some() {
+                            locale.nth_weekday(day)
+                        } else {
+                            locale.weekday(&day.weekday).to_string()
+                        }
+                    })
+                    .collect();
+                text.push_str(&locale.on(locale.list(days)));
+            }
+            if !self.bymonthday.is_empty() {
+                text.push_str(&locale.on_month_days(&self.bymonthday));
+            }
+            if !self.bymonth.is_empty() {
+                let months = self
+                    .bymonth
+                    .iter()
+                    .map(|month| locale.month(month.month()).to_string())
+                    .collect();
+                text.push_str(&locale.in_months(locale.list(months)));
+            }
+        }
+
+        if let Some(count) = self.count.filter(|count| *count > 0) {
+            text.push_str(&locale.times(count));
+        } else if let Some(until) = &self.until {
+            text.push_str(&locale.until(until));
+        }
+
+        text
+    }
+}
+
+impl RuleLocale for English {
+    fn every(&self, freq: &ICalendarFrequency, interval: u16) -> String {
+        let unit = match freq {
+            ICalendarFrequency::Yearly => "year",
+            ICalendarFrequency::Monthly => "month",
+            ICalendarFrequency::Weekly => "week",
+            ICalendarFrequency::Daily => "day",
+            ICalendarFrequency::Hourly => "hour",
+            ICalendarFrequency::Minutely => "minute",
+            ICalendarFrequency::Secondly => "second",
+        };
+        if interval == 1 {
+            format!("Every {unit}")
+        } else {
+            format!("Every {interval} {unit}s")
+        }
+    }
+
+    fn weekday(&self, day: &ICalendarWeekday) -> &'static str {
+        match day {
+            ICalendarWeekday::Sunday => "Sunday",
+            ICalendarWeekday::Monday => "Monday",
+            ICalendarWeekday::Tuesday => "Tuesday",
+            ICalendarWeekday::Wednesday => "Wednesday",
+            ICalendarWeekday::Thursday => "Thursday",
+            ICalendarWeekday::Friday => "Friday",
+            ICalendarWeekday::Saturday => "Saturday",
+        }
+    }
+
+    fn month(&self, month: u8) -> &'static str {
+        match month {
+            1 => "January",
+            2 => "February",
+            3 => "March",
+            4 => "April",
+            5 => "May",
+            6 => "June",
+            7 => "July",
+            8 => "August",
+            9 => "September",
+            10 => "October",
+            11 => "November",
+            12 => "December",
+            _ => "?",
+        }
+    }
+
+    fn nth_weekday(&self, day: &ICalendarDay) -> String {
+        let weekday = self.weekday(&day.weekday);
+        match day.ordwk.unwrap_or_default() {
+            -1 => format!("the last {weekday}"),
+            n if n < 0 => format!("the {} to last {weekday}", ordinal(n.unsigned_abs())),
+            n => format!("the {} {weekday}", ordinal(n.unsigned_abs())),
+        }
+    }
+
+    fn list(&self, mut items: Vec<String>) -> String {
+        match items.len() {
+            0 | 1 => items.pop().unwrap_or_default(),
+            _ => {
+                let last = items.pop().unwrap();
+                format!("{} and {last}", items.join(", "))
+            }
+        }
+    }
+
+    fn on(&self, what: String) -> String {
+        format!(" on {what}")
+    }
+
+    fn on_month_days(&self, days: &[i8]) -> String {
+        let mut items = Vec::new();
+        let from_start = days
+            .iter()
+            .filter(|day| **day > 0)
+            .map(|day| day.to_string())
+            .collect::<Vec<_>>();
+        if !from_start.is_empty() {
+            let label = if from_start.len() == 1 { "day" } else { "days" };
+            items.push(format!("{label} {}", self.list(from_start)));
+        }
+        for day in days.iter().filter(|day| **day < 0) {
+            items.push(match day {
+                -1 => "the last day".to_string(),
+                n => format!("the {} to last day", ordinal(n.unsigned_abs().into())),
+            });
+        }
+        format!(" on {}", self.list(items))
+    }
+
+    fn in_months(&self, months: String) -> String {
+        format!(" in {months}")
+    }
+
+    fn times(&self, count: u32) -> String {
+        if count == 1 {
+            ", once".to_string()
+        } else {
+            format!(", {count} times")
+        }
+    }
+
+    fn until(&self, dt: &PartialDateTime) -> String {
+        format!(
+            ", until {} {}, {}",
+            self.month(dt.month.unwrap_or_default()),
+            dt.day.unwrap_or_default(),
+            dt.year.unwrap_or_default()
+        )
+    }
+
+    fn custom(&self) -> &'static str {
+        " (custom rule)"
+    }
+}
+
+fn ordinal(n: u16) -> String {
+    match n {
+        1 => "first".to_string(),
+        2 => "second".to_string(),
+        3 => "third".to_string(),
+        4 => "fourth".to_string(),
+        5 => "fifth".to_string(),
+        n => match (n % 10, n % 100) {
+            (_, 11..=13) => format!("{n}th"),
+            (1, _) => format!("{n}st"),
+            (2, _) => format!("{n}nd"),
+            (3, _) => format!("{n}rd"),
+            _ => format!("{n}th"),
+        },
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use crate::Parser;
+
+    #[test]
+    fn describe_rules() {
+        for (rule, expected) in [
+            ("FREQ=DAILY", "Every day"),
+            (
+                "FREQ=WEEKLY;INTERVAL=2;BYDAY=MO,WE;COUNT=10",
+                "Every 2 weeks on Monday and Wednesday, 10 times",
+            ),
+            (
+                "FREQ=WEEKLY;BYDAY=MO,WE,FR;UNTIL=20251231T000000Z",
+                "Every week on Monday, Wednesday and Friday, until December 31, 2025",
+            ),
+            (
+                "FREQ=MONTHLY;BYDAY=2TU;COUNT=1",
+                "Every month on the second Tuesday, once",
+            ),
+            (
+                "FREQ=MONTHLY;BYDAY=-2FR",
+                "Every month on the second to last Friday",
+            ),
+            (
+                "FREQ=MONTHLY;BYMONTHDAY=1,15",
+                "Every month on days 1 and 15",
+            ),
+            (
+                "FREQ=MONTHLY;INTERVAL=3;BYMONTHDAY=5,-1",
+                "Every 3 months on day 5 and the last day",
+            ),
+            (
+                "FREQ=YEARLY;BYMONTH=11;BYDAY=4TH",
+                "Every year on the fourth Thursday in November",
+            ),
+            (
+                "FREQ=YEARLY;BYMONTH=3,6;BYMONTHDAY=21",
+                "Every year on day 21 in March and June",
+            ),
+            (
+                "FREQ=MONTHLY;BYDAY=MO,TU,WE,TH,FR;BYSETPOS=-1;COUNT=3",
+                "Every month (custom rule), 3 times",
+            ),
+            ("FREQ=HOURLY;INTERVAL=4", "Every 4 hours"),
+        ] {
+            assert_eq!(
+                Parser::new(rule).strict().rrule().unwrap().describe(),
+                expected,
+                "failed for {rule}"
+            );
+        }
+    }
+}
@@ -0,0 +1,307 @@
+/*
+ * SPDX-FileCopyrightText: 2020 Stalwart Labs LLC <hello@stalw.art>
+ *
+ * SPDX-License-Identifier: Apache-2.0 OR MIT
+ */
+
+use super::{ICalendarComponent, ICalendarEntry, ICalendarParameter, ICalendarProperty};
+
+#[derive(Debug, Default, Clone, PartialEq, Eq)]
+pub struct ComponentDiff {
+    pub added: Vec<ICalendarEntry>,
+    pub removed: Vec<ICalendarEntry>,
+    pub modified: Vec<EntryDiff>,
+}
+
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct EntryDiff {
+    pub old: ICalendarEntry,
+    pub new: ICalendarEntry,
+}
+
+pub fn diff(old: &ICalendarComponent, new: &ICalendarComponent) -> ComponentDiff {
+    let mut removed = old
+        .entries
+        .iter()
+        .filter(|entry| !is_managed(&entry.name))
+        .collect::<Vec<_>>();
+    let mut added = Vec::new();
+    for entry in new.entries.iter().filter(|entry| !is_managed(&entry.name)) {
+        if let Some(pos) = removed.iter().position(|old_entry| *old_entry == entry) {
+            removed.remove(pos);
+        } else {
+            added.push(entry);
+        }
+    }
+
+    // Pair entries whose values are unchanged, then single instances of a property
+    let mut modified = Vec::new();
+    added.retain(|entry| {
+        if let Some(pos) = removed
+            .iter()
+            .position(|old_entry| old_entry.name == entry.name && old_entry.values == entry.values)
+        {
+            modified.push(EntryDiff {
+                old: removed.remove(pos).clone(),
+                new: (*entry).clone(),
+            });
+            false
+        } else {
+            true
+        }
+    });
+    let mut idx = 0;
+    while idx < added.len() {
+        let name = &added[idx].name;
+        let old_pos = removed
+            .iter()
+            .enumerate()
+            .filter(|(_, entry)| &entry.name == name)
+            .map(|(pos, _)| pos)
+            .collect::<Vec<_>>();
+        if let [old_pos] = old_pos[..]
+            && added.iter().filter(|entry| &entry.name == name).count() == 1
+        {
+            modified.push(EntryDiff {
+                old: removed.remove(old_pos).clone(),
+                new: added.remove(idx).clone(),
+            });
+        } else {
+            idx += 1;
+        }
+    }
+
+    ComponentDiff {
+        added: added.into_iter().cloned().collect(),
+        removed: removed.into_iter().cloned().collect(),
+        modified,
+    }
+}
+
+impl ComponentDiff {
+    pub fn is_empty(&self) -> bool {
+        self.added.is_empty() && self.removed.is_empty() && self.modified.is_empty()
+    }
+
+    pub fn is_scheduling_significant(&self) -> bool {
+        self.added
+            .iter()
+            .chain(self.removed.iter())
+            .map(|entry| &entry.name)
+            .chain(self.modified.iter().map(|entry| &entry.new.name))
+            .any(is_scheduling_significant)
+    }
+}
+
+impl EntryDiff {
+    pub fn added_params(&self) -> impl Iterator<Item = &ICalendarParameter> {
+        self.new
+            .params
+            .iter()
+            .filter(|param| !self.old.params.contains(param))
+    }
+
+    pub fn removed_params(&self) -> impl Iterator<Item = &ICalendarParameter> {
+        self.old
+            .params
+            .iter()
+            .filter(|param| !self.new.params.contains(param))
+    }
+
+    pub fn has_value_changes(&self) -> bool {
+        self.old.values != self.new.values
+    }
+}
+
+impl ICalendarComponent {
+    pub fn diff(&self, new: &ICalendarComponent) -> ComponentDiff {
+        diff(self, new)
+    }
+
+    pub fn apply_diff(&mut self, diff: &ComponentDiff) -> bool {
+        // Refuse to apply a diff computed against a different version
+        if !diff
+            .removed
+            .iter()
+            .chain(diff.modified.iter().map(|entry| &entry.old))
+            .all(|entry| self.entries.contains(entry))
+        {
+            return false;
+        }
+        if diff.is_empty() {
+            return true;
+        }
+
+        for entry in &diff.removed {
+            if let Some(pos) = self.entries.iter().position(|e| e == entry) {
+                self.entries.remove(pos);
+            }
+        }
+        for entry in &diff.modified {
+            if let Some(old_entry) = self.entries.iter_mut().find(|e| *e == &entry.old) {
+                *old_entry = entry.new.clone();
+            }
+        }
+        self.entries.extend(diff.added.iter().cloned());
+
+        let is_significant = diff.is_scheduling_significant();
+        if is_significant {
+            self.set_property(ICalendarProperty::Sequence, self.sequence() as i64 + 1);
+        }
+
+        // Stamping the modification time needs a clock
+        #[cfg(feature = "datetime")]
+        {
+            let now = crate::common::PartialDateTime::now_utc();
+            self.set_property(ICalendarProperty::LastModified, now.clone());
+            if is_significant {
+                self.set_property(ICalendarProperty::Dtstamp, now);
+            }
+        }
+
+        true
+    }
+}
+
+fn is_managed(name: &ICalendarProperty) -> bool {
+    matches!(
+        name,
+        ICalendarProperty::Sequence | ICalendarProperty::LastModified | ICalendarProperty::Dtstamp
+    )
+}
+
+fn is_scheduling_significant(name: &ICalendarProperty) -> bool {
+    matches!(
+        name,
+        ICalendarProperty::Dtstart
+            | ICalendarProperty::Dtend
+            | ICalendarProperty::Duration
+            | ICalendarProperty::Due
+            | ICalendarProperty::Rrule
+            | ICalendarProperty::Rdate
+            | ICalendarProperty::Exdate
+            | ICalendarProperty::Exrule
+            | ICalendarProperty::Status
+    )
+}
+
+#[cfg(test)]
+mod tests {
+    use crate::icalendar::{ICalendar, ICalendarParameterName, ICalendarProperty};
+
+    #[test]
+    fn diff_and_apply() {
+        let ical = ICalendar::parse(concat!(
+            "BEGIN:VCALENDAR\r\n",
+            "BEGIN:VEVENT\r\n",
+            "UID:event\r\n",
+            "DTSTAMP:20240101T000000Z\r\n",
+            "SEQUENCE:2\r\n",
+            "SUMMARY:Planning\r\n",
+            "DTSTART:20240115T100000Z\r\n",
+            "LOCATION:Room 1\r\n",
+            "ATTENDEE;PARTSTAT=NEEDS-ACTION:mailto:a@example.com\r\n",
+            "ATTENDEE:mailto:b@example.com\r\n",
+            "END:VEVENT\r\n",
+            "BEGIN:VEVENT\r\n",
+            "UID:event\r\n",
+            "DTSTAMP:20240102T000000Z\r\n",
+            "SEQUENCE:2\r\n",
+            "SUMMARY;LANGUAGE=en:Planning\r\n",
+            "DTSTART:20240116T100000Z\r\n",
+            "ATTENDEE;PARTSTAT=ACCEPTED:mailto:a@example.com\r\n",
+            "ATTENDEE:mailto:b@example.com\r\n",
+            "ATTENDEE:mailto:c@example.com\r\n",
+            "END:VEVENT\r\n",
+            "END:VCALENDAR\r\n"
+        ))
+        .unwrap();
+        let (old, new) = (&ical.components[1], &ical.components[2]);
+
+        let diff = old.diff(new);
+        assert_eq!(
+            diff.added
+                .iter()
+                .map(|entry| entry.values[0].as_text().unwrap())
+                .collect::<Vec<_>>(),
+            ["mailto:c@example.com"]
+        );
+        assert_eq!(
+            diff.removed
+                .iter()
+                .map(|entry| &entry.name)
+                .collect::<Vec<_>>(),
+            [&ICalendarProperty::Location]
+        );
+        assert_eq!(
+            diff.modified
+                .iter()
+                .map(|entry| (
+                    &entry.new.name,
+                    entry.has_value_changes(),
+                    entry
+                        .added_params()
+                        .map(|param| &param.name)
+                        .collect::<Vec<_>>(),
+                    entry.removed_params().count()
+                ))
+                .collect::<Vec<_>>(),
+            [
+                (
+                    &ICalendarProperty::Summary,
+                    false,
+                    vec![&ICalendarParameterName::Language],
+                    0
+                ),
+                (
+                    &ICalendarProperty::Attendee,
+                    false,
+                    vec![&ICalendarParameterName::Partstat],
+                    1
+                ),
+                (&ICalendarProperty::Dtstart, true, vec![], 0),
+            ]
+        );
+        assert!(diff.is_scheduling_significant());
+
+        let mut patched = old.clone();
+        assert!(patched.apply_diff(&diff));
+        assert!(patched.content_eq_ignoring(
+            new,
+            &[
+                ICalendarProperty::Dtstamp,
+                ICalendarProperty::Sequence,
+                ICalendarProperty::LastModified,
+            ]
+        ));
+        assert_eq!(
+            patched
+                .property(&ICalendarProperty::Sequence)
+                .and_then(|entry| entry.values[0].as_integer()),
+            Some(3)
+        );
+        #[cfg(feature = "datetime")]
+        assert!(patched.has_property(&ICalendarProperty::LastModified));
+        assert!(patched.diff(new).is_empty());
+
+        // Diffs only apply to the version they were computed against
+        assert!(!patched.apply_diff(&diff));
+
+        // Non-scheduling changes leave the sequence untouched
+        let mut renamed = new.clone();
+        renamed
+            .property_mut(&ICalendarProperty::Summary)
+            .unwrap()
+            .values = vec!["Review".into()];
+        let diff = new.diff(&renamed);
+        assert!(!diff.is_scheduling_significant());
+        let mut patched = new.clone();
+        assert!(patched.apply_diff(&diff));
+        assert_eq!(
+            patched
+                .property(&ICalendarProperty::Sequence)
+                .and_then(|entry| entry.values[0].as_integer()),
+            Some(2)
+        );
+    }
+}
@@ -0,0 +1,649 @@
+/*
+ * SPDX-FileCopyrightText: 2020 Stalwart Labs LLC <hello@stalw.art>
+ *
+ * SPDX-License-Identifier: Apache-2.0 OR MIT
+ */
+
+use super::{
+    ICalendar, ICalendarComponent, ICalendarComponentType, ICalendarEntry, ICalendarFreeBusyType,
+    ICalendarParameter, ICalendarPeriod, ICalendarProperty, ICalendarStatus, ICalendarTransparency,
+    dates::RecurrenceSet, timezone::TzResolver,
+};
+use crate::common::{DateTimeResult, PartialDateTime, timezone::Tz};
+use ahash::{AHashMap, AHashSet};
+use chrono::{NaiveDate, TimeDelta};
+use std::{borrow::Borrow, hash::Hash};
+
+const MAX_OCCURRENCES: usize = 10_000;
+
+struct Occurrence {
+    start: i64,
+    end: i64,
+    first_day: NaiveDate,
+    last_day: NaiveDate,
+}
+
+pub fn free_busy(
+    events: &[ICalendarComponent],
+    range: (PartialDateTime, PartialDateTime),
+    resolver: &TzResolver<impl Borrow<str> + Hash + Eq>,
+) -> ICalendarComponent {
+    let mut component = ICalendarComponent::new(ICalendarComponentType::VFreebusy);
+    let (Some(range_start), Some(range_end)) = (
+        range.0.to_date_time_with(None, resolver),
+        range.1.to_date_time_with(None, resolver),
+    ) else {
+        return component;
+    };
+    let (range_start, range_end) = (range_start.timestamp(), range_end.timestamp());
+    let mut busy = Vec::new();
+
+    for (_, occurrence) in busy_occurrences(events, Some((range_start, range_end)), resolver) {
+        if occurrence.start < range_end && occurrence.end > range_start {
+            busy.push((
+                occurrence.start.max(range_start),
+                occurrence.end.min(range_end),
+            ));
+        }
+    }
+
+    let merged = merge_intervals(busy);
+
+    component.entries.push(
+        ICalendarEntry::new(ICalendarProperty::Dtstart)
+            .with_value(PartialDateTime::from_utc_timestamp(range_start)),
+    );
+    component.entries.push(
+        ICalendarEntry::new(ICalendarProperty::Dtend)
+            .with_value(PartialDateTime::from_utc_timestamp(range_end)),
+    );
+    component
+        .entries
+        .extend(merged.into_iter().map(|(start, end)| {
+            ICalendarEntry::new(ICalendarProperty::Freebusy)
+                .with_param(ICalendarParameter::fbtype(ICalendarFreeBusyType::Busy))
+                .with_value(ICalendarPeriod::Range {
+                    start: PartialDateTime::from_utc_timestamp(start),
+                    end: PartialDateTime::from_utc_timestamp(end),
+                })
+        }));
+
+    component
+}
+
+impl ICalendar {
+    pub fn find_conflicts(
+        &self,
+        resolver: &TzResolver<impl Borrow<str> + Hash + Eq>,
+    ) -> Vec<(usize, usize)> {
+        let ids = self
+            .components
+            .iter()
+            .enumerate()
+            .map(|(idx, event)| (event as *const ICalendarComponent, idx))
+            .collect::<AHashMap<_, _>>();
+        let is_all_day = |idx: usize| {
+            self.components[idx]
+                .dtstart()
+                .is_some_and(|dt| !dt.has_time())
+        };
+        let mut times = Vec::new();
+        let mut days = Vec::new();
+        for (event, occurrence) in busy_occurrences(&self.components, None, resolver) {
+            if let Some(&idx) = ids.get(&(event as *const ICalendarComponent)) {
+                times.push((occurrence.start, occurrence.end, idx));
+                days.push((occurrence.first_day, occurrence.last_day, idx));
+            }
+        }
+
+        let mut conflicts = AHashSet::new();
+        let mut add_conflict = |a: usize, b: usize| {
+            // Recurrence overrides share the UID of the series they modify
+            let (uid_a, uid_b) = (self.components[a].uid(), self.components[b].uid());
+            if uid_a.is_none() || uid_a != uid_b {
+                conflicts.insert((a.min(b), a.max(b)));
+            }
+        };
+        // All-day events conflict with anything on the same days
+        sweep_overlaps(times, |a, b| {
+            if !is_all_day(a) && !is_all_day(b) {
+                add_conflict(a, b);
+            }
+        });
+        sweep_overlaps(days, |a, b| {
+            if is_all_day(a) || is_all_day(b) {
+                add_conflict(a, b);
+            }
+        });
+
+        let mut conflicts = conflicts.into_iter().collect::<Vec<_>>();
+        conflicts.sort_unstable();
+        conflicts
+    }
+}
+
+impl ICalendar {
+    pub fn available_windows(
+        &self,
+        range: (PartialDateTime, PartialDateTime),
+        resolver: &TzResolver<impl Borrow<str> + Hash + Eq>,
+    ) -> Vec<(DateTimeResult, DateTimeResult)> {
+        available_windows(
+            self,
+            self.components.iter().filter(|component| {
+                component.component_type == ICalendarComponentType::VAvailability
+            }),
+            range,
+            resolver,
+        )
+    }
+}
+
+impl ICalendarComponent {
+    // Free windows of this VAVAILABILITY alone, resolving AVAILABLE components in `calendar`
+    pub fn available_windows(
+        &self,
+        calendar: &ICalendar,
+        range: (PartialDateTime, PartialDateTime),
+        resolver: &TzResolver<impl Borrow<str> + Hash + Eq>,
+    ) -> Vec<(DateTimeResult, DateTimeResult)> {
+        if self.component_type == ICalendarComponentType::VAvailability {
+            available_windows(calendar, [self], range, resolver)
+        } else {
+            vec![]
+        }
+    }
+}
+
+fn available_windows<'x>(
+    calendar: &ICalendar,
+    availabilities: impl IntoIterator<Item = &'x ICalendarComponent>,
+    range: (PartialDateTime, PartialDateTime),
+    resolver: &TzResolver<impl Borrow<str> + Hash + Eq>,
+) -> Vec<(DateTimeResult, DateTimeResult)> {
+    let (Some(range_start), Some(range_end)) = (
+        range.0.to_date_time_with(None, resolver),
+        range.1.to_date_time_with(None, resolver),
+    ) else {
+        return vec![];
+    };
+    let (range_start, range_end) = (range_start.timestamp(), range_end.timestamp());
+
+    // Lower priorities are applied first so that higher ones override them,
+    // with PRIORITY 0 or missing ranking below 9
+    let mut availabilities = availabilities
+        .into_iter()
+        .map(|component| {
+            let priority = component
+                .property(&ICalendarProperty::Priority)
+                .and_then(|entry| entry.values.first())
+                .and_then(|value| value.as_integer())
+                .filter(|priority| (1..=9).contains(priority))
+                .unwrap_or(10);
+            (priority, component)
+        })
+        .collect::<Vec<_>>();
+    availabilities.sort_by_key(|(priority, _)| std::cmp::Reverse(*priority));
+
+    // Time not covered by any VAVAILABILITY is free, while every BUSYTYPE
+    // blocks the time it covers unless an AVAILABLE window frees it again
+    let mut free = Vec::from_iter((range_start < range_end).then_some((range_start, range_end)));
+    for (_, availability) in availabilities {
+        let tz_id = availability
+            .property(&ICalendarProperty::Dtstart)
+            .and_then(|entry| entry.tz_id());
+        let start = match availability.dtstart() {
+            Some(start) => match start.to_date_time_with(tz_id, resolver) {
+                Some(start) => start.timestamp(),
+                None => continue,
+            },
+            None => i64::MIN,
+        };
+        let end = if availability.has_property(&ICalendarProperty::Dtend)
+            || availability.has_property(&ICalendarProperty::Duration)
+        {
+            match availability
+                .effective_end(resolver)
+                .and_then(|end| end.to_date_time_with_tz(Tz::Floating))
+            {
+                Some(end) => end.timestamp(),
+                None => continue,
+            }
+        } else {
+            i64::MAX
+        };
+        let (start, end) = (start.max(range_start), end.min(range_end));
+        if start >= end {
+            continue;
+        }
+
+        let series = recurrence_sets(
+            availability
+                .component_ids
+                .iter()
+                .filter_map(|id| calendar.components.get(*id as usize))
+                .filter(|component| component.component_type == ICalendarComponentType::Available),
+        );
+
+        let mut windows = Vec::new();
+        for set in series {
+            for instance in set.expand_within(resolver, Some((start, end)), MAX_OCCURRENCES) {
+                let component = instance.component;
+                let tz_id = component
+                    .property(&ICalendarProperty::Dtstart)
+                    .and_then(|entry| entry.tz_id());
+                let Some((window_start, dt_start, dt_end)) = instance
+                    .start
+                    .to_date_time_with(instance.tz_id, resolver)
+                    .zip(
+                        component
+                            .dtstart()
+                            .and_then(|dt| dt.to_date_time_with(tz_id, resolver)),
+                    )
+                    .zip(
+                        component
+                            .effective_end(resolver)
+                            .and_then(|end| end.to_date_time_with_tz(Tz::Floating)),
+                    )
+                    .map(|((window_start, dt_start), dt_end)| (window_start, dt_start, dt_end))
+                else {
+                    continue;
+                };
+                let window_start = window_start.timestamp();
+                let window_end = window_start + dt_end.timestamp() - dt_start.timestamp();
+                if window_start < window_end && window_start < end && window_end > start {
+                    windows.push((window_start.max(start), window_end.min(end)));
+                }
+            }
+        }
+
+        free = subtract_intervals(free, start, end);
+        free.extend(windows);
+        free = merge_intervals(free);
+    }
+
+    free.into_iter()
+        .filter_map(|(start, end)| {
+            Some((
+                PartialDateTime::from_utc_timestamp(start).to_date_time()?,
+                PartialDateTime::from_utc_timestamp(end).to_date_time()?,
+            ))
+        })
+        .collect()
+}
+
+fn merge_intervals(mut intervals: Vec<(i64, i64)>) -> Vec<(i64, i64)> {
+    intervals.sort_unstable();
+    let mut merged: Vec<(i64, i64)> = Vec::with_capacity(intervals.len());
+    for (start, end) in intervals {
+        if let Some(last) = merged.last_mut()
+            && start <= last.1
+        {
+            last.1 = last.1.max(end);
+        } else {
+            merged.push((start, end));
+        }
+    }
+    merged
+}
+
+fn subtract_intervals(intervals: Vec<(i64, i64)>, start: i64, end: i64) -> Vec<(i64, i64)> {
+    let mut result = Vec::with_capacity(intervals.len() + 1);
+    for (from, to) in intervals {
+        if to <= start || from >= end {
+            result.push((from, to));
+        } else {
+            if from < start {
+                result.push((from, start));
+            }
+            if to > end {
+                result.push((end, to));
+            }
+        }
+    }
+    result
+}
+
+// Groups components into series by UID, in the order they first appear
+fn recurrence_sets<'x>(
+    components: impl IntoIterator<Item = &'x ICalendarComponent>,
+) -> Vec<RecurrenceSet<'x>> {
+    let mut sets: Vec<RecurrenceSet<'x>> = Vec::new();
+    let mut uids = AHashMap::new();
+
+    for component in components {
+        let idx = match component.uid() {
+            Some(uid) => *uids.entry(uid).or_insert(sets.len()),
+            None => sets.len(),
+        };
+        if idx == sets.len() {
+            sets.push(RecurrenceSet {
+                master: None,
+                overrides: vec![],
+            });
+        }
+
+        let set = &mut sets[idx];
+        if component.has_property(&ICalendarProperty::RecurrenceId) {
+            set.overrides.push(component);
+        } else if set.master.is_none() {
+            set.master = Some(component);
+        }
+    }
+
+    sets
+}
+
+// Overridden instances are replaced by their override, which may itself be free
+fn busy_occurrences<'x>(
+    events: &'x [ICalendarComponent],
+    window: Option<(i64, i64)>,
+    resolver: &TzResolver<impl Borrow<str> + Hash + Eq>,
+) -> Vec<(&'x ICalendarComponent, Occurrence)> {
+    recurrence_sets(
+        events
+            .iter()
+            .filter(|event| event.component_type == ICalendarComponentType::VEvent),
+    )
+    .into_iter()
+    .flat_map(|set| set.expand_within(resolver, window, MAX_OCCURRENCES))
+    .filter_map(|instance| {
+        let event = instance.component;
+        if event.transparency() == Some(&ICalendarTransparency::Transparent)
+            || event.status() == Some(&ICalendarStatus::Cancelled)
+        {
+            return None;
+        }
+
+        let tz_id = event
+            .property(&ICalendarProperty::Dtstart)
+            .and_then(|entry| entry.tz_id());
+        let length = event
+            .effective_end(resolver)?
+            .to_date_time_with_tz(Tz::Floating)?
+            .timestamp()
+            - event
+                .dtstart()?
+                .to_date_time_with(tz_id, resolver)?
+                .timestamp();
+        if length <= 0 {
+            return None;
+        }
+
+        let start = instance
+            .start
+            .to_date_time_with(instance.tz_id, resolver)?
+            .timestamp();
+        let last_local = instance
+            .start
+            .date_time
+            .checked_add_signed(TimeDelta::seconds(length - 1))?;
+
+        Some((
+            event,
+            Occurrence {
+                start,
+                end: start + length,
+                first_day: instance.start.date_time.date(),
+                last_day: last_local.date().succ_opt()?,
+            },
+        ))
+    })
+    .collect()
+}
+
+// Reports every pair of spans from different events that overlap, in a single pass
+fn sweep_overlaps<T: Ord + Copy>(
+    mut spans: Vec<(T, T, usize)>,
+    mut found: impl FnMut(usize, usize),
+) {
+    spans.sort_unstable();
+    let mut active: Vec<(T, usize)> = Vec::new();
+    for (start, end, idx) in spans {
+        active.retain(|(active_end, _)| *active_end > start);
+        for &(_, other) in &active {
+            if other != idx {
+                found(other, idx);
+            }
+        }
+        active.push((end, idx));
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::icalendar::ICalendarValue;
+
+    #[test]
+    fn free_busy_from_events() {
+        let ical = ICalendar::parse(concat!(
+            "BEGIN:VCALENDAR\r\n",
+            "BEGIN:VEVENT\r\n",
+            "UID:standup\r\n",
+            "DTSTART;TZID=Europe/Berlin:20240101T100000\r\n",
+            "DURATION:PT30M\r\n",
+            "RRULE:FREQ=DAILY\r\n",
+            "END:VEVENT\r\n",
+            "BEGIN:VEVENT\r\n",
+            "UID:overlap\r\n",
+            "DTSTART:20240102T091500Z\r\n",
+            "DTEND:20240102T100000Z\r\n",
+            "END:VEVENT\r\n",
+            "BEGIN:VEVENT\r\n",
+            "UID:clipped\r\n",
+            "DTSTART:20240102T220000Z\r\n",
+            "DTEND:20240103T020000Z\r\n",
+            "END:VEVENT\r\n",
+            "BEGIN:VEVENT\r\n",
+            "UID:transparent\r\n",
+            "DTSTART:20240102T120000Z\r\n",
+            "DTEND:20240102T130000Z\r\n",
+            "TRANSP:TRANSPARENT\r\n",
+            "END:VEVENT\r\n",
+            "BEGIN:VEVENT\r\n",
+            "UID:cancelled\r\n",
+            "DTSTART:20240102T140000Z\r\n",
+            "DTEND:20240102T150000Z\r\n",
+            "STATUS:CANCELLED\r\n",
+            "END:VEVENT\r\n",
+            "BEGIN:VEVENT\r\n",
+            "UID:standup\r\n",
+            "RECURRENCE-ID;TZID=Europe/Berlin:20240102T100000\r\n",
+            "DTSTART;TZID=Europe/Berlin:20240102T160000\r\n",
+            "DURATION:PT30M\r\n",
+            "END:VEVENT\r\n",
+            "BEGIN:VEVENT\r\n",
+            "UID:since-1990\r\n",
+            "DTSTART:19900101T120000Z\r\n",
+            "DURATION:PT1H\r\n",
+            "RRULE:FREQ=DAILY\r\n",
+            "END:VEVENT\r\n",
+            "BEGIN:VEVENT\r\n",
+            "UID:rdate\r\n",
+            "DTSTART:20240101T180000Z\r\n",
+            "DURATION:PT1H\r\n",
+            "RDATE;TZID=America/New_York:20240102T130000\r\n",
+            "END:VEVENT\r\n",
+            "END:VCALENDAR\r\n"
+        ))
+        .unwrap();
+        let resolver = ical.build_tz_resolver();
+        let at = |day: u8, hour: u8, minute: u8| PartialDateTime {
+            year: Some(2024),
+            month: Some(1),
+            day: Some(day),
+            hour: Some(hour),
+            minute: Some(minute),
+            second: Some(0),
+            tz_hour: Some(0),
+            tz_minute: Some(0),
+            tz_minus: false,
+        };
+        let range = |day: u8, hour: u8| at(day, hour, 0);
+
+        let vfreebusy = free_busy(&ical.components, (range(2, 0), range(3, 0)), &resolver);
+        assert_eq!(vfreebusy.component_type, ICalendarComponentType::VFreebusy);
+        assert_eq!(vfreebusy.dtstart(), Some(&range(2, 0)));
+        assert_eq!(
+            vfreebusy
+                .entries
+                .iter()
+                .filter(|entry| entry.name == ICalendarProperty::Freebusy)
+                .map(|entry| {
+                    assert_eq!(
+                        entry.params,
+                        [ICalendarParameter::fbtype(ICalendarFreeBusyType::Busy)]
+                    );
+                    match entry.values.first() {
+                        Some(ICalendarValue::Period(ICalendarPeriod::Range { start, end })) => {
+                            (start.clone(), end.clone())
+                        }
+                        other => panic!("unexpected value {other:?}"),
+                    }
+                })
+                .collect::<Vec<_>>(),
+            [
+                // The standup moved to the afternoon, the other event remains
+                (at(2, 9, 15), range(2, 10)),
+                (range(2, 12), range(2, 13)),
+                (range(2, 15), at(2, 15, 30)),
+                // The RDATE is in New York time
+                (range(2, 18), range(2, 19)),
+                (range(2, 22), range(3, 0))
+            ]
+        );
+    }
+
+    #[test]
+    fn find_conflicts() {
+        let ical = ICalendar::parse(concat!(
+            "BEGIN:VCALENDAR\r\n",
+            "BEGIN:VEVENT\r\n",
+            "UID:new-york\r\n",
+            "DTSTART;TZID=America/New_York:20240115T090000\r\n",
+            "DURATION:PT1H\r\n",
+            "END:VEVENT\r\n",
+            "BEGIN:VEVENT\r\n",
+            "UID:london\r\n",
+            "DTSTART;TZID=Europe/London:20240115T090000\r\n",
+            "DURATION:PT1H\r\n",
+            "END:VEVENT\r\n",
+            "BEGIN:VEVENT\r\n",
+            "UID:weekly\r\n",
+            "DTSTART:20240101T143000Z\r\n",
+            "DURATION:PT1H\r\n",
+            "RRULE:FREQ=WEEKLY\r\n",
+            "END:VEVENT\r\n",
+            "BEGIN:VEVENT\r\n",
+            "UID:all-day\r\n",
+            "DTSTART;VALUE=DATE:20240115\r\n",
+            "END:VEVENT\r\n",
+            "BEGIN:VEVENT\r\n",
+            "UID:next-day\r\n",
+            "DTSTART;VALUE=DATE:20240116\r\n",
+            "DTEND;VALUE=DATE:20240118\r\n",
+            "END:VEVENT\r\n",
+            "BEGIN:VEVENT\r\n",
+            "UID:transparent\r\n",
+            "DTSTART:20240115T090000Z\r\n",
+            "DURATION:PT1H\r\n",
+            "TRANSP:TRANSPARENT\r\n",
+            "END:VEVENT\r\n",
+            "END:VCALENDAR\r\n"
+        ))
+        .unwrap();
+
+        // New York at 9am overlaps the weekly 14:30 UTC series, not London at 9am
+        assert_eq!(
+            ical.find_conflicts(&ical.build_tz_resolver()),
+            [(1, 3), (1, 4), (2, 4), (3, 4)]
+        );
+    }
+
+    #[test]
+    fn available_windows() {
+        let ical = ICalendar::parse(concat!(
+            "BEGIN:VCALENDAR\r\n",
+            "BEGIN:VAVAILABILITY\r\n",
+            "UID:office-hours\r\n",
+            "DTSTART:20240101T000000Z\r\n",
+            "BUSYTYPE:BUSY-UNAVAILABLE\r\n",
+            "BEGIN:AVAILABLE\r\n",
+            "UID:weekdays\r\n",
+            "DTSTART:20240101T090000Z\r\n",
+            "DTEND:20240101T170000Z\r\n",
+            "RRULE:FREQ=WEEKLY;BYDAY=MO,TU,WE,TH,FR\r\n",
+            "END:AVAILABLE\r\n",
+            "BEGIN:AVAILABLE\r\n",
+            "UID:weekdays\r\n",
+            "RECURRENCE-ID:20240109T090000Z\r\n",
+            "DTSTART:20240109T120000Z\r\n",
+            "DTEND:20240109T170000Z\r\n",
+            "END:AVAILABLE\r\n",
+            "END:VAVAILABILITY\r\n",
+            "BEGIN:VAVAILABILITY\r\n",
+            "UID:offsite\r\n",
+            "PRIORITY:1\r\n",
+            "DTSTART:20240108T130000Z\r\n",
+            "DTEND:20240108T150000Z\r\n",
+            "END:VAVAILABILITY\r\n",
+            "END:VCALENDAR\r\n"
+        ))
+        .unwrap();
+        let dt = |day: u8, hour: u8| PartialDateTime {
+            year: Some(2024),
+            month: Some(1),
+            day: Some(day),
+            hour: Some(hour),
+            minute: Some(0),
+            second: Some(0),
+            tz_hour: Some(0),
+            tz_minute: Some(0),
+            tz_minus: false,
+        };
+
+        assert_eq!(
+            ical.available_windows((dt(8, 0), dt(10, 0)), &ical.build_tz_resolver()),
+            [
+                (dt(8, 9), dt(8, 13)),
+                (dt(8, 15), dt(8, 17)),
+                (dt(9, 12), dt(9, 17))
+            ]
+            .map(|(start, end)| (start.to_date_time().unwrap(), end.to_date_time().unwrap()))
+        );
+
+        // Time outside every VAVAILABILITY is free
+        let new_year_eve = PartialDateTime::from_utc_timestamp(1703980800);
+        assert_eq!(
+            ical.available_windows((new_year_eve.clone(), dt(1, 10)), &ical.build_tz_resolver()),
+            [(new_year_eve, dt(1, 0)), (dt(1, 9), dt(1, 10))]
+                .map(|(start, end)| (start.to_date_time().unwrap(), end.to_date_time().unwrap()))
+        );
+
+        // Each VAVAILABILITY on its own
+        let availabilities = ical
+            .components
+            .iter()
+            .filter(|component| component.component_type == ICalendarComponentType::VAvailability)
+            .collect::<Vec<_>>();
+        assert_eq!(
+            availabilities[0].available_windows(
+                &ical,
+                (dt(8, 0), dt(10, 0)),
+                &ical.build_tz_resolver()
+            ),
+            [(dt(8, 9), dt(8, 17)), (dt(9, 12), dt(9, 17))]
+                .map(|(start, end)| (start.to_date_time().unwrap(), end.to_date_time().unwrap()))
+        );
+        assert_eq!(
+            availabilities[1].available_windows(
+                &ical,
+                (dt(8, 0), dt(10, 0)),
+                &ical.build_tz_resolver()
+            ),
+            [(dt(8, 0), dt(8, 13)), (dt(8, 15), dt(10, 0))]
+                .map(|(start, end)| (start.to_date_time().unwrap(), end.to_date_time().unwrap()))
+        );
+    }
+}
@@ -0,0 +1,259 @@
+/*
+ * SPDX-FileCopyrightText: 2020 Stalwart Labs LLC <hello@stalw.art>
+ *
+ * SPDX-License-Identifier: Apache-2.0 OR MIT
+ */
+
+use super::{
+    ICalendar, ICalendarComponent, ICalendarComponentType, ICalendarEntry, ICalendarMethod,
+    ICalendarParameter, ICalendarParameterName, ICalendarParticipationStatus, ICalendarProperty,
+    ICalendarStatus, builder::calendar_address_uri, utils::strip_mailto_scheme,
+};
+use crate::common::PartialDateTime;
+
+// The component is detached from its calendar, so sub-components such as
+// VALARMs cannot be carried over. RFC 5546 does not allow them in CANCEL or
+// REPLY, and alarms are left for each attendee to set up. No PRODID is added,
+// set one with `ICalendar::set_prodid` or `WriterOptions::prodid`.
+impl ICalendarComponent {
+    pub fn to_itip_request(
+        mut self,
+        organizer: impl Into<String>,
+        attendees: impl IntoIterator<Item = impl Into<String>>,
+    ) -> ICalendar {
+        // Only the address changes, CN and SENT-BY are kept
+        let organizer = organizer.into();
+        match self
+            .entries
+            .iter_mut()
+            .find(|entry| entry.name == ICalendarProperty::Organizer)
+        {
+            Some(entry) if entry.is_calendar_address(&organizer) => {}
+            Some(entry) => {
+                entry.values = vec![calendar_address_uri(organizer).into()];
+            }
+            None => {
+                self.entries.push(
+                    ICalendarEntry::new(ICalendarProperty::Organizer)
+                        .with_value(calendar_address_uri(organizer)),
+                );
+            }
+        }
+
+        for attendee in attendees {
+            let attendee = attendee.into();
+            if !self
+                .properties(&ICalendarProperty::Attendee)
+                .any(|entry| entry.is_calendar_address(&attendee))
+            {
+                self.entries.push(
+                    ICalendarEntry::new(ICalendarProperty::Attendee)
+                        .with_param(ICalendarParameter::partstat(
+                            ICalendarParticipationStatus::NeedsAction,
+                        ))
+                        .with_param(ICalendarParameter::rsvp(true))
+                        .with_value(calendar_address_uri(attendee)),
+                );
+            }
+        }
+
+        self.entries
+            .retain(|entry| entry.name != ICalendarProperty::RequestStatus);
+        self.into_itip(ICalendarMethod::Request)
+    }
+
+    pub fn to_itip_cancel(mut self) -> ICalendar {
+        self.entries
+            .retain(|entry| entry.name != ICalendarProperty::RequestStatus);
+        self.set_property(ICalendarProperty::Status, ICalendarStatus::Cancelled);
+        self.into_itip(ICalendarMethod::Cancel)
+    }
+
+    pub fn to_itip_reply(
+        mut self,
+        attendee: &str,
+        partstat: ICalendarParticipationStatus,
+    ) -> ICalendar {
+        // A reply only carries the responding attendee
+        let mut has_attendee = false;
+        self.entries.retain_mut(|entry| match &entry.name {
+            ICalendarProperty::Attendee if !has_attendee && entry.is_calendar_address(attendee) => {
+                has_attendee = true;
+                entry.remove_param(&ICalendarParameterName::Rsvp);
+                entry.set_param(ICalendarParameterName::Partstat, partstat.clone());
+                true
+            }
+            ICalendarProperty::Uid
+            | ICalendarProperty::Dtstamp
+            | ICalendarProperty::Sequence
+            | ICalendarProperty::Organizer
+            | ICalendarProperty::RecurrenceId
+            | ICalendarProperty::Dtstart
+            | ICalendarProperty::Dtend
+            | ICalendarProperty::Duration
+            | ICalendarProperty::Due
+            | ICalendarProperty::Summary
+            | ICalendarProperty::Comment => true,
+            _ => false,
+        });
+        if !has_attendee {
+            self.entries.push(
+                ICalendarEntry::new(ICalendarProperty::Attendee)
+                    .with_param(ICalendarParameter::partstat(partstat))
+                    .with_value(calendar_address_uri(attendee.to_string())),
+            );
+        }
+
+        self.into_itip(ICalendarMethod::Reply)
+    }
+
+    fn into_itip(mut self, method: ICalendarMethod) -> ICalendar {
+        self.component_ids.clear();
+        self.set_property(ICalendarProperty::Dtstamp, PartialDateTime::now_utc());
+        if self.property(&ICalendarProperty::Sequence).is_none() {
+            self.add_sequence(0);
+        }
+
+        let mut root = ICalendarComponent::new(ICalendarComponentType::VCalendar);
+        root.add_property(ICalendarProperty::Version, "2.0");
+        root.add_property(ICalendarProperty::Method, method);
+        root.component_ids.push(1);
+
+        ICalendar {
+            components: vec![root, self],
+        }
+    }
+}
+
+impl ICalendarEntry {
+    fn is_calendar_address(&self, address: &str) -> bool {
+        self.calendar_address()
+            .is_some_and(|value| value.eq_ignore_ascii_case(strip_mailto_scheme(address)))
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use crate::icalendar::{
+        ICalendar, ICalendarMethod, ICalendarParameterName, ICalendarParameterValue,
+        ICalendarParticipationStatus, ICalendarProperty, ICalendarStatus, ICalendarValue, Uri,
+    };
+
+    #[test]
+    fn itip_messages() {
+        let ical = ICalendar::parse(concat!(
+            "BEGIN:VCALENDAR\r\n",
+            "BEGIN:VEVENT\r\n",
+            "UID:meeting\r\n",
+            "DTSTAMP:20240101T000000Z\r\n",
+            "SUMMARY:Planning\r\n",
+            "DTSTART:20240115T100000Z\r\n",
+            "LOCATION:Room 1\r\n",
+            "ORGANIZER;CN=Boss;SENT-BY=\"mailto:assistant@example.com\":mailto:old@example.com\r\n",
+            "REQUEST-STATUS:2.0;Success\r\n",
+            "ATTENDEE;PARTSTAT=NEEDS-ACTION;RSVP=TRUE:mailto:a@example.com\r\n",
+            "BEGIN:VALARM\r\n",
+            "ACTION:DISPLAY\r\n",
+            "TRIGGER:-PT15M\r\n",
+            "END:VALARM\r\n",
+            "END:VEVENT\r\n",
+            "END:VCALENDAR\r\n"
+        ))
+        .unwrap();
+        let event = ical.components[1].clone();
+        let method = |itip: &ICalendar| {
+            itip.components[0]
+                .property(&ICalendarProperty::Method)
+                .and_then(|entry| entry.values.first())
+                .cloned()
+        };
+
+        let request = event.clone().to_itip_request(
+            "mailto:organizer@example.com",
+            ["mailto:A@example.com", "b@example.com"],
+        );
+        assert_eq!(
+            method(&request),
+            Some(ICalendarValue::Method(ICalendarMethod::Request))
+        );
+        assert_eq!(request.components.len(), 2);
+        assert_eq!(request.components[0].component_ids, [1]);
+        assert!(!request.components[0].has_property(&ICalendarProperty::Prodid));
+        let request = &request.components[1];
+        assert_eq!(
+            request
+                .properties(&ICalendarProperty::Attendee)
+                .filter_map(|entry| entry.calendar_address())
+                .collect::<Vec<_>>(),
+            ["a@example.com", "b@example.com"]
+        );
+        assert_eq!(
+            request
+                .properties(&ICalendarProperty::Attendee)
+                .filter_map(|entry| entry.values.first())
+                .collect::<Vec<_>>(),
+            [
+                &ICalendarValue::Uri(Uri::Location("mailto:a@example.com".into())),
+                &ICalendarValue::Uri(Uri::Location("mailto:b@example.com".into()))
+            ]
+        );
+        let organizer = request.property(&ICalendarProperty::Organizer).unwrap();
+        assert_eq!(
+            organizer.values,
+            [ICalendarValue::Uri(Uri::Location(
+                "mailto:organizer@example.com".into()
+            ))]
+        );
+        assert!(organizer.parameter(&ICalendarParameterName::Cn).is_some());
+        assert!(
+            organizer
+                .parameter(&ICalendarParameterName::SentBy)
+                .is_some()
+        );
+        assert!(!request.has_property(&ICalendarProperty::RequestStatus));
+        assert_eq!(request.sequence(), 0);
+        assert!(request.component_ids.is_empty());
+
+        let cancel = event.clone().to_itip_cancel();
+        assert_eq!(
+            method(&cancel),
+            Some(ICalendarValue::Method(ICalendarMethod::Cancel))
+        );
+        assert_eq!(
+            cancel.components[1].status(),
+            Some(&ICalendarStatus::Cancelled)
+        );
+        assert_eq!(cancel.components[1].uid(), Some("meeting"));
+
+        let reply = event
+            .clone()
+            .to_itip_request("mailto:organizer@example.com", ["mailto:b@example.com"])
+            .components
+            .remove(1)
+            .to_itip_reply("b@example.com", ICalendarParticipationStatus::Accepted);
+        assert_eq!(
+            method(&reply),
+            Some(ICalendarValue::Method(ICalendarMethod::Reply))
+        );
+        let reply = &reply.components[1];
+        let attendees = reply
+            .properties(&ICalendarProperty::Attendee)
+            .collect::<Vec<_>>();
+        assert_eq!(attendees.len(), 1);
+        assert_eq!(attendees[0].calendar_address(), Some("b@example.com"));
+        assert_eq!(
+            attendees[0].parameter(&ICalendarParameterName::Partstat),
+            Some(&ICalendarParameterValue::Partstat(
+                ICalendarParticipationStatus::Accepted
+            ))
+        );
+        assert!(
+            attendees[0]
+                .parameter(&ICalendarParameterName::Rsvp)
+                .is_none()
+        );
+        assert!(!reply.has_property(&ICalendarProperty::Location));
+        assert_eq!(reply.uid(), Some("meeting"));
+        assert!(reply.has_property(&ICalendarProperty::Organizer));
+    }
+}
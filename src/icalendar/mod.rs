@@ -9,15 +9,26 @@ use crate::common::{
 };
 use std::hash::{Hash, Hasher};
 
+pub mod borrowed;
 pub mod builder;
+#[cfg(feature = "datetime")]
 pub mod dates;
+pub mod describe;
+pub mod diff;
+#[cfg(feature = "datetime")]
+pub mod freebusy;
+#[cfg(feature = "datetime")]
+pub mod itip;
 pub mod parser;
+#[cfg(all(feature = "uuid", feature = "datetime"))]
+pub mod repair;
+#[cfg(feature = "datetime")]
 pub mod timezone;
 pub mod types;
 pub mod utils;
 pub mod writer;
 
-#[cfg(feature = "rkyv")]
+#[cfg(all(feature = "rkyv", feature = "datetime"))]
 pub mod rkyv_timezone;
 #[cfg(feature = "rkyv")]
 pub mod rkyv_types;
@@ -26,6 +37,10 @@ pub mod rkyv_utils;
 #[cfg(feature = "rkyv")]
 pub mod rkyv_writer;
 
+pub use diff::diff;
+#[cfg(feature = "datetime")]
+pub use freebusy::free_busy;
+
 #[derive(Debug, Default, Clone, PartialEq, Eq)]
 #[cfg_attr(
     any(test, feature = "serde"),
@@ -53,6 +68,7 @@ pub struct ICalendar {
 pub struct ICalendarComponent {
     pub component_type: ICalendarComponentType,
     pub entries: Vec<ICalendarEntry>,
+    // Positions in `ICalendar::components` of the direct sub-components
     pub component_ids: Vec<u32>,
 }
 
@@ -8,7 +8,7 @@ use super::*;
 use crate::{
     Entry, Parser, StopChar, Token,
     common::{
-        CalendarScale, Encoding, PartialDateTime,
+        CalendarScale, Encoding, ParseDiagnosticKind, PartialDateTime,
         parser::{Boolean, Integer, parse_digits, parse_small_digits},
     },
     icalendar::{ICalendarDay, ICalendarWeekday},
@@ -45,6 +45,7 @@ impl Parser<'_> {
                 Some(token) => token,
                 None => break,
             };
+            let line_offset = token.start;
 
             let mut params = Params {
                 params: Vec::new(),
@@ -87,6 +88,9 @@ impl Parser<'_> {
                                 .unwrap_or_else(|| {
                                     ICalendarComponentType::Other(token.into_string())
                                 });
+                            if ical_stack.len() + 2 > self.max_depth {
+                                return Entry::TooDeeplyNested;
+                            }
                             ical_stack.push(ical_idx);
                             ical.component_ids.push(next_component_id);
                             ical_components.push(ICalendarComponent {
@@ -307,11 +311,18 @@ impl Parser<'_> {
                                             })
                                     }
                                     ICalendarValueType::Duration => {
-                                        if let Some(duration) =
+                                        let duration = if self.lenient_durations {
                                             ICalendarDuration::parse(token.text.as_ref())
-                                        {
+                                        } else {
+                                            ICalendarDuration::parse_strict(token.text.as_ref())
+                                        };
+                                        if let Some(duration) = duration {
                                             ICalendarValue::Duration(duration)
                                         } else {
+                                            self.add_diagnostic(
+                                                token.start,
+                                                ParseDiagnosticKind::MalformedDuration,
+                                            );
                                             ICalendarValue::Text(token.into_string())
                                         }
                                     }
@@ -414,6 +425,7 @@ impl Parser<'_> {
                     });
                 }
 
+                self.add_span(ical_idx as u32, ical.entries.len(), line_offset);
                 ical.entries.push(entry);
             }
         }
@@ -971,7 +983,8 @@ impl IanaParse for ICalendarMonth {
 
 impl ICalendarMonth {
     pub fn new(month: u8, is_leap: bool) -> Self {
-        ICalendarMonth(if is_leap { -(month as i8) } else { month as i8 })
+        let month = month.min(i8::MAX as u8) as i8;
+        ICalendarMonth(if is_leap { -month } else { month })
     }
 
     pub fn is_leap(&self) -> bool {
@@ -983,6 +996,12 @@ impl ICalendarMonth {
     }
 }
 
+impl ICalendarDay {
+    pub fn new(weekday: ICalendarWeekday, ordwk: Option<i16>) -> Self {
+        ICalendarDay { ordwk, weekday }
+    }
+}
+
 impl From<ICalendarMonth> for u8 {
     fn from(value: ICalendarMonth) -> Self {
         value.month()
@@ -1057,6 +1076,66 @@ impl ICalendarDuration {
 
         if saw_component { Some(dur) } else { None }
     }
+
+    pub fn parse_strict(value: &[u8]) -> Option<Self> {
+        let mut dur = ICalendarDuration::default();
+        let mut iter = value.iter().peekable();
+        match iter.peek() {
+            Some(b'+') => {
+                iter.next();
+            }
+            Some(b'-') => {
+                iter.next();
+                dur.neg = true;
+            }
+            _ => {}
+        }
+        if !matches!(iter.next(), Some(b'P' | b'p')) {
+            return None;
+        }
+
+        let mut in_time = false;
+        let mut has_date = false;
+        let mut has_time = false;
+        let mut last_rank = 0;
+        loop {
+            let mut num = None;
+            while let Some(ch) = iter.next_if(|ch| ch.is_ascii_digit()) {
+                num = Some(
+                    num.unwrap_or(0u32)
+                        .saturating_mul(10)
+                        .saturating_add((ch - b'0') as u32),
+                );
+            }
+            let Some(ch) = iter.next() else {
+                return (num.is_none() && if in_time { has_time } else { has_date }).then_some(dur);
+            };
+            let (rank, value) = match (ch.to_ascii_uppercase(), in_time) {
+                (b'T', false) if num.is_none() => {
+                    in_time = true;
+                    continue;
+                }
+                (b'W', false) => (1, &mut dur.weeks),
+                (b'D', false) => (2, &mut dur.days),
+                (b'H', true) => (3, &mut dur.hours),
+                (b'M', true) => (4, &mut dur.minutes),
+                (b'S', true) => (5, &mut dur.seconds),
+                _ => return None,
+            };
+            if rank <= last_rank {
+                return None;
+            }
+            *value = num?;
+            last_rank = rank;
+            has_date |= !in_time;
+            has_time |= in_time;
+
+            // Weeks cannot be combined with any other component
+            if rank == 1 && iter.peek().is_some() {
+                return None;
+            }
+        }
+    }
 }
 
 impl IanaParse for ICalendarDuration {
@@ -2179,4 +2258,572 @@ mod tests {
         let reparsed = ICalendarDuration::parse(zero.to_string().as_bytes()).unwrap();
         assert_eq!(reparsed, zero);
     }
+
+    #[test]
+    fn test_lenient_durations() {
+        for (value, lenient, strict) in [
+            ("PT1H30M", Some("PT1H30M"), Some("PT1H30M")),
+            ("-P2W", Some("-P2W"), Some("-P2W")),
+            ("-P1W2D", Some("-P1W2D"), None),
+            ("P1WT2H", Some("P1WT2H"), None),
+            ("p1dt2h", Some("P1DT2H"), Some("P1DT2H")),
+            ("P1H", Some("PT1H"), None),
+            ("P1M", Some("PT1M"), None),
+            ("PT30M1H", Some("PT1H30M"), None),
+            ("P1DT", Some("P1D"), None),
+            ("PT1H15", Some("PT1H"), None),
+            ("PT 15M", Some("PT15M"), None),
+            ("PTM", Some("PT0S"), None),
+            ("PT", None, None),
+        ] {
+            let input = format!(
+                "BEGIN:VCALENDAR\r\nBEGIN:VALARM\r\nTRIGGER:{value}\r\nEND:VALARM\r\nEND:VCALENDAR\r\n"
+            );
+            for (lenient_durations, expected) in [(true, lenient), (false, strict)] {
+                let mut parser = Parser::new(&input)
+                    .lenient_durations(lenient_durations)
+                    .with_diagnostics();
+                let Entry::ICalendar(ical) = parser.entry() else {
+                    panic!("Failed to parse {value}");
+                };
+                let result = ical.components[1].entries[0].values.first().cloned();
+                match expected {
+                    Some(expected) => {
+                        assert_eq!(
+                            result.and_then(|value| value.as_duration().map(|d| d.to_string())),
+                            Some(expected.to_string()),
+                            "{value} (lenient: {lenient_durations})"
+                        );
+                        assert!(parser.take_diagnostics().is_empty());
+                    }
+                    None => {
+                        assert_eq!(
+                            result,
+                            Some(ICalendarValue::Text(value.to_string())),
+                            "{value} (lenient: {lenient_durations})"
+                        );
+                        assert_eq!(
+                            parser
+                                .take_diagnostics()
+                                .into_iter()
+                                .map(|diagnostic| (diagnostic.line_num, diagnostic.kind))
+                                .collect::<Vec<_>>(),
+                            [(3, ParseDiagnosticKind::MalformedDuration)]
+                        );
+                    }
+                }
+            }
+        }
+    }
+
+    #[test]
+    fn test_whitespace_roundtrip() {
+        for description in [
+            "  leading spaces",
+            "trailing spaces  ",
+            "internal  double   spaces",
+            "\ttab and  spaces\t",
+            "   ",
+            concat!(
+                "a long description that is folded across several lines ",
+                "     with runs of spaces landing near the fold boundary     ",
+                "and again here      at the end  "
+            ),
+        ] {
+            let mut ical = ICalendar::default();
+            ical.components.push(ICalendarComponent {
+                component_type: ICalendarComponentType::VCalendar,
+                entries: vec![],
+                component_ids: vec![1],
+            });
+            ical.components.push(ICalendarComponent {
+                component_type: ICalendarComponentType::VEvent,
+                entries: vec![ICalendarEntry {
+                    name: ICalendarProperty::Description,
+                    params: vec![],
+                    values: vec![ICalendarValue::Text(description.into())],
+                }],
+                component_ids: vec![],
+            });
+
+            for _ in 0..2 {
+                let ical_text = ical.to_string();
+                ical = ICalendar::parse(&ical_text).unwrap();
+                assert_eq!(
+                    ical.components[1]
+                        .property(&ICalendarProperty::Description)
+                        .and_then(|entry| entry.values.first())
+                        .and_then(|value| value.as_text()),
+                    Some(description),
+                    "Failed to roundtrip {description:?}: {ical_text}"
+                );
+            }
+        }
+    }
+
+    #[test]
+    fn test_escaped_separators() {
+        let mut ical = ICalendar::parse(concat!(
+            "BEGIN:VCALENDAR\r\n",
+            "BEGIN:VEVENT\r\n",
+            "CATEGORIES:A,B\\,C,D\r\n",
+            "CATEGORIES:\\,lead,trail\\,,semi\\;colon,back\\\\\r\n",
+            "RESOURCES:PROJECTOR,ROOM 1\\, 2ND FLOOR\r\n",
+            "END:VEVENT\r\n",
+            "END:VCALENDAR\r\n"
+        ))
+        .unwrap();
+
+        for _ in 0..2 {
+            let values = |prop| {
+                ical.components[1]
+                    .properties(prop)
+                    .flat_map(|entry| entry.values.iter().filter_map(|v| v.as_text()))
+                    .collect::<Vec<_>>()
+            };
+            assert_eq!(
+                values(&ICalendarProperty::Categories),
+                ["A", "B,C", "D", ",lead", "trail,", "semi;colon", "back\\"]
+            );
+            assert_eq!(
+                values(&ICalendarProperty::Resources),
+                ["PROJECTOR", "ROOM 1, 2ND FLOOR"]
+            );
+            ical = ICalendar::parse(ical.to_string()).unwrap();
+        }
+        assert!(ical.to_string().contains("CATEGORIES:A,B\\,C,D\r\n"));
+    }
+
+    #[test]
+    fn test_parse_period_list() {
+        let utc = |hour| PartialDateTime {
+            year: Some(1997),
+            month: Some(3),
+            day: Some(8),
+            hour: Some(hour),
+            minute: Some(0),
+            second: Some(0),
+            tz_hour: Some(0),
+            tz_minute: Some(0),
+            tz_minus: false,
+        };
+        let hours = |neg, hours| ICalendarDuration {
+            neg,
+            hours,
+            ..Default::default()
+        };
+
+        for (line, expected, output) in [
+            (
+                "FREEBUSY:19970308T160000Z/PT3H,19970308T200000Z/PT1H",
+                vec![
+                    ICalendarPeriod::Duration {
+                        start: utc(16),
+                        duration: hours(false, 3),
+                    },
+                    ICalendarPeriod::Duration {
+                        start: utc(20),
+                        duration: hours(false, 1),
+                    },
+                ],
+                "FREEBUSY:19970308T160000Z/PT3H,19970308T200000Z/PT1H",
+            ),
+            (
+                "RDATE;VALUE=PERIOD:19970308T160000Z/19970308T170000Z,19970308T200000Z/+PT1H",
+                vec![
+                    ICalendarPeriod::Range {
+                        start: utc(16),
+                        end: utc(17),
+                    },
+                    ICalendarPeriod::Duration {
+                        start: utc(20),
+                        duration: hours(false, 1),
+                    },
+                ],
+                "RDATE;VALUE=PERIOD:19970308T160000Z/19970308T170000Z,19970308T200000Z/PT1H",
+            ),
+            (
+                "FREEBUSY:19970308T160000Z/-PT3H",
+                vec![ICalendarPeriod::Duration {
+                    start: utc(16),
+                    duration: hours(true, 3),
+                }],
+                "FREEBUSY:19970308T160000Z/-PT3H",
+            ),
+        ] {
+            let ical = ICalendar::parse(format!(
+                "BEGIN:VCALENDAR\r\nBEGIN:VFREEBUSY\r\n{line}\r\nEND:VFREEBUSY\r\nEND:VCALENDAR\r\n"
+            ))
+            .unwrap();
+            let entry = &ical.components[1].entries[0];
+
+            assert_eq!(
+                entry.values,
+                expected
+                    .into_iter()
+                    .map(ICalendarValue::Period)
+                    .collect::<Vec<_>>(),
+                "failed for {line}"
+            );
+            assert!(
+                ical.to_string().contains(&format!("\r\n{output}\r\n")),
+                "failed for {line}"
+            );
+        }
+    }
+
+    #[test]
+    fn test_max_depth() {
+        let mut input = String::from("BEGIN:VCALENDAR\r\n");
+        for _ in 0..100_000 {
+            input.push_str("BEGIN:X-NESTED\r\n");
+        }
+        assert_eq!(ICalendar::parse(&input), Err(Entry::TooDeeplyNested));
+
+        let input = concat!(
+            "BEGIN:VCALENDAR\r\n",
+            "BEGIN:VEVENT\r\n",
+            "BEGIN:VALARM\r\n",
+            "END:VALARM\r\n",
+            "END:VEVENT\r\n",
+            "END:VCALENDAR\r\n"
+        );
+        assert!(matches!(
+            Parser::new(input).max_depth(3).entry(),
+            Entry::ICalendar(_)
+        ));
+        assert_eq!(
+            Parser::new(input).max_depth(2).entry(),
+            Entry::TooDeeplyNested
+        );
+    }
+
+    #[test]
+    fn test_parse_bytes() {
+        let input =
+            "BEGIN:VCALENDAR\r\nBEGIN:VEVENT\r\nSUMMARY:Café\r\nEND:VEVENT\r\nEND:VCALENDAR\r\n";
+        let expected = ICalendar::parse(input).unwrap();
+        let utf16 = input.encode_utf16().collect::<Vec<_>>();
+
+        for bytes in [
+            input.as_bytes().to_vec(),
+            [&[0xEF, 0xBB, 0xBF], input.as_bytes()].concat(),
+            [0xFF, 0xFE]
+                .into_iter()
+                .chain(utf16.iter().flat_map(|ch| ch.to_le_bytes()))
+                .collect(),
+            [0xFE, 0xFF]
+                .into_iter()
+                .chain(utf16.iter().flat_map(|ch| ch.to_be_bytes()))
+                .collect(),
+        ] {
+            assert_eq!(ICalendar::parse_bytes(&bytes), Ok(expected.clone()));
+        }
+
+        assert_eq!(
+            ICalendar::parse_bytes(b"BEGIN:VCALENDAR\r\nSUMMARY:Caf\xE9\r\nEND:VCALENDAR\r\n"),
+            Err(Entry::InvalidEncoding)
+        );
+        assert_eq!(
+            ICalendar::parse_bytes(&[0xFF, 0xFE, b'B']),
+            Err(Entry::InvalidEncoding)
+        );
+    }
+
+    #[test]
+    fn test_preserve_trailing_empty_lines() {
+        let input = concat!(
+            "BEGIN:VCALENDAR\r\n",
+            "BEGIN:VEVENT\r\n",
+            "UID:1\r\n",
+            "END:VEVENT\r\n",
+            "END:VCALENDAR\r\n",
+            "BEGIN:VCALENDAR\r\n",
+            "BEGIN:VEVENT\r\n",
+            "UID:2\r\n",
+            "END:VEVENT\r\n",
+            "END:VCALENDAR\r\n",
+            "\r\n",
+            "\r\n"
+        );
+        let roundtrip = |preserve| {
+            let mut parser = Parser::new(input).preserve_trailing_empty_lines(preserve);
+            let mut output = String::new();
+            loop {
+                match parser.entry() {
+                    Entry::ICalendar(ical) => output.push_str(&ical.to_string()),
+                    Entry::EmptyLine => output.push_str("\r\n"),
+                    Entry::Eof => break,
+                    other => panic!("Unexpected entry {other:?}"),
+                }
+            }
+            output
+        };
+
+        assert_eq!(roundtrip(true), input);
+        assert_eq!(
+            roundtrip(false),
+            input.trim_end_matches("\r\n").to_string() + "\r\n"
+        );
+    }
+
+    #[test]
+    fn test_entry_spans() {
+        let input = concat!(
+            "BEGIN:VCALENDAR\r\n",
+            "VERSION:2.0\r\n",
+            "BEGIN:VEVENT\r\n",
+            "\r\n",
+            "DTSTART;TZID=Europe/Berlin:\r\n 20240101T090000\r\n",
+            "DESCRIPTION:Folded\r\n\tdescription\r\n",
+            "END:VEVENT\r\n",
+            "END:VCALENDAR",
+        );
+        let (ical, spans) = ICalendar::parse_with_spans(input).unwrap();
+
+        assert_eq!(spans.len(), 3);
+        for span in &spans {
+            let entry =
+                &ical.components[span.component_id as usize].entries[span.entry_id as usize];
+            assert!(
+                input[span.span.clone()].starts_with(entry.name.as_str()),
+                "{span:?}"
+            );
+        }
+        assert_eq!(
+            spans.iter().map(|span| span.line_num).collect::<Vec<_>>(),
+            [2, 5, 7]
+        );
+        assert_eq!(&input[spans[0].span.clone()], "VERSION:2.0");
+        assert_eq!(
+            &input[spans[1].span.clone()],
+            "DTSTART;TZID=Europe/Berlin:\r\n 20240101T090000"
+        );
+        assert_eq!(
+            &input[spans[2].span.clone()],
+            "DESCRIPTION:Folded\r\n\tdescription"
+        );
+
+        let input = "BEGIN:VCARD\nVERSION:4.0\nitem1.EMAIL;TYPE=work:a@b.c\nEND:VCARD\n";
+        let (vcard, spans) = crate::vcard::VCard::parse_with_spans(input).unwrap();
+        assert_eq!(spans.len(), vcard.entries.len());
+        assert_eq!(&input[spans[1].span.clone()], "item1.EMAIL;TYPE=work:a@b.c");
+        assert_eq!(spans[1].line_num, 3);
+    }
+
+    #[test]
+    fn test_rscale_leap_month() {
+        let input = concat!(
+            "BEGIN:VCALENDAR\r\n",
+            "BEGIN:VEVENT\r\n",
+            "RRULE:FREQ=YEARLY;BYMONTHDAY=8;BYMONTH=5L,6;RSCALE=HEBREW;SKIP=FORWARD\r\n",
+            "END:VEVENT\r\n",
+            "END:VCALENDAR\r\n"
+        );
+        let ical = ICalendar::parse(input).unwrap();
+        let rrule = ical.components[1].rrule().unwrap();
+
+        assert_eq!(rrule.rscale, Some(CalendarScale::Hebrew));
+        assert_eq!(
+            rrule.bymonth,
+            [ICalendarMonth::new(5, true), ICalendarMonth::new(6, false)]
+        );
+        assert_eq!(rrule.bymonth[0].month(), 5);
+        assert!(rrule.bymonth[0].is_leap());
+        assert!(!rrule.bymonth[1].is_leap());
+        assert_eq!(ICalendarMonth::new(200, false).month(), 127);
+        assert!(!ICalendarMonth::new(200, false).is_leap());
+
+        assert_eq!(ical.to_string(), input);
+        assert_eq!(ICalendar::parse(ical.to_string()).unwrap(), ical);
+    }
+
+    #[test]
+    fn test_byday_ordinals() {
+        let input = concat!(
+            "BEGIN:VCALENDAR\r\n",
+            "BEGIN:VEVENT\r\n",
+            "RRULE:FREQ=MONTHLY;BYDAY=-1SU,2MO,TU\r\n",
+            "END:VEVENT\r\n",
+            "END:VCALENDAR\r\n"
+        );
+        let ical = ICalendar::parse(input).unwrap();
+
+        assert_eq!(
+            ical.components[1].rrule().unwrap().byday,
+            [
+                ICalendarDay::new(ICalendarWeekday::Sunday, Some(-1)),
+                ICalendarDay::new(ICalendarWeekday::Monday, Some(2)),
+                ICalendarDay::new(ICalendarWeekday::Tuesday, None),
+            ]
+        );
+        assert_eq!(ical.to_string(), input);
+        assert_eq!(
+            ICalendarDay::new(ICalendarWeekday::Friday, Some(-53)).to_string(),
+            "-53FR"
+        );
+    }
+
+    #[test]
+    fn test_other_name_case() {
+        let ical = ICalendar::parse(concat!(
+            "BEGIN:VCALENDAR\r\n",
+            "BEGIN:VEVENT\r\n",
+            "x-foo;x-param=a:one\r\n",
+            "X-FOO;X-PARAM=b:two\r\n",
+            "END:VEVENT\r\n",
+            "END:VCALENDAR\r\n"
+        ))
+        .unwrap();
+        let event = &ical.components[1];
+        let (lower, upper) = (&event.entries[0], &event.entries[1]);
+
+        assert_ne!(lower.name, upper.name);
+        assert!(lower.name.eq_ignore_ascii_case(&upper.name));
+        assert!(!lower.name.eq_ignore_ascii_case(&ICalendarProperty::Summary));
+        assert!(
+            lower.params[0]
+                .name
+                .eq_ignore_ascii_case(&upper.params[0].name)
+        );
+        assert_eq!(lower.name.as_str(), "x-foo");
+    }
+
+    #[test]
+    fn test_name_roundtrip() {
+        macro_rules! roundtrip {
+            ($ty:ident, $($variant:ident),+ $(,)?) => {{
+                // Fails to build when a new variant is missing from the list
+                let _ = |name: &$ty| match name {
+                    $($ty::$variant)|+ | $ty::Other(_) => (),
+                };
+                for name in [$($ty::$variant),+] {
+                    assert_eq!($ty::from(name.as_str()), name);
+                    assert_eq!($ty::from(name.as_str().to_ascii_lowercase().as_str()), name);
+                }
+                assert_eq!($ty::from("X-UNKNOWN"), $ty::Other("X-UNKNOWN".to_string()));
+            }};
+        }
+
+        roundtrip!(
+            ICalendarProperty,
+            Calscale,
+            Method,
+            Prodid,
+            Version,
+            Attach,
+            Categories,
+            Class,
+            Comment,
+            Description,
+            Geo,
+            Location,
+            PercentComplete,
+            Priority,
+            Resources,
+            Status,
+            Summary,
+            Completed,
+            Dtend,
+            Due,
+            Dtstart,
+            Duration,
+            Freebusy,
+            Transp,
+            Tzid,
+            Tzname,
+            Tzoffsetfrom,
+            Tzoffsetto,
+            Tzurl,
+            Attendee,
+            Contact,
+            Organizer,
+            RecurrenceId,
+            RelatedTo,
+            Url,
+            Uid,
+            Exdate,
+            Exrule,
+            Rdate,
+            Rrule,
+            Action,
+            Repeat,
+            Trigger,
+            Created,
+            Dtstamp,
+            LastModified,
+            Sequence,
+            RequestStatus,
+            Xml,
+            Tzuntil,
+            TzidAliasOf,
+            Busytype,
+            Name,
+            RefreshInterval,
+            Source,
+            Color,
+            Image,
+            Conference,
+            CalendarAddress,
+            LocationType,
+            ParticipantType,
+            ResourceType,
+            StructuredData,
+            StyledDescription,
+            Acknowledged,
+            Proximity,
+            Concept,
+            Link,
+            Refid,
+            Begin,
+            End,
+            Coordinates,
+            ShowWithoutTime,
+            Jsid,
+            Jsprop,
+            EstimatedDuration,
+            Reason,
+            Substate,
+            TaskMode
+        );
+        roundtrip!(
+            ICalendarParameterName,
+            Altrep,
+            Cn,
+            Cutype,
+            DelegatedFrom,
+            DelegatedTo,
+            Dir,
+            Fmttype,
+            Fbtype,
+            Language,
+            Member,
+            Partstat,
+            Range,
+            Related,
+            Reltype,
+            Role,
+            Rsvp,
+            ScheduleAgent,
+            ScheduleForceSend,
+            ScheduleStatus,
+            SentBy,
+            Tzid,
+            Value,
+            Display,
+            Email,
+            Feature,
+            Label,
+            Size,
+            Filename,
+            ManagedId,
+            Order,
+            Schema,
+            Derived,
+            Gap,
+            Linkrel,
+            Jsptr,
+            Jsid
+        );
+    }
 }
@@ -0,0 +1,173 @@
+/*
+ * SPDX-FileCopyrightText: 2020 Stalwart Labs LLC <hello@stalw.art>
+ *
+ * SPDX-License-Identifier: Apache-2.0 OR MIT
+ */
+
+use super::{ICalendar, ICalendarComponent, ICalendarComponentType, ICalendarProperty};
+use crate::common::PartialDateTime;
+
+// 5a92c01c-2ffa-4066-b13d-a1e1c3a3e15e
+static REPAIR_NAMESPACE: uuid::Uuid = uuid::Uuid::from_bytes([
+    90, 146, 192, 28, 47, 250, 64, 102, 177, 61, 161, 225, 195, 163, 225, 94,
+]);
+
+#[derive(Debug, Clone, Default)]
+pub struct RepairOptions {
+    pub generate_uid: bool,
+    pub generate_dtstamp: bool,
+    pub uid_seed: Option<String>,
+}
+
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub enum ICalendarRepair {
+    Uid { component_id: u32, uid: String },
+    Dtstamp { component_id: u32 },
+}
+
+impl RepairOptions {
+    pub fn generate_uid(mut self, generate: bool) -> Self {
+        self.generate_uid = generate;
+        self
+    }
+
+    pub fn generate_dtstamp(mut self, generate: bool) -> Self {
+        self.generate_dtstamp = generate;
+        self
+    }
+
+    pub fn uid_seed(mut self, seed: impl Into<String>) -> Self {
+        self.uid_seed = Some(seed.into());
+        self
+    }
+}
+
+impl ICalendar {
+    pub fn repair(&mut self, opts: RepairOptions) -> Vec<ICalendarRepair> {
+        let mut repairs = Vec::new();
+        let now = PartialDateTime::now_utc();
+
+        for (component_id, component) in self.components.iter_mut().enumerate() {
+            if !matches!(
+                component.component_type,
+                ICalendarComponentType::VEvent
+                    | ICalendarComponentType::VTodo
+                    | ICalendarComponentType::VJournal
+                    | ICalendarComponentType::VFreebusy
+                    | ICalendarComponentType::VAvailability
+                    | ICalendarComponentType::Available
+            ) {
+                continue;
+            }
+            let component_id = component_id as u32;
+
+            if opts.generate_uid && component.uid().is_none_or(|uid| uid.trim().is_empty()) {
+                let uid = match &opts.uid_seed {
+                    Some(seed) => seeded_uid(seed, component_id, component),
+                    None => uuid::Uuid::new_v4().hyphenated().to_string(),
+                };
+                component.set_property(ICalendarProperty::Uid, uid.clone());
+                repairs.push(ICalendarRepair::Uid { component_id, uid });
+            }
+
+            if opts.generate_dtstamp && !component.has_property(&ICalendarProperty::Dtstamp) {
+                component.set_property(ICalendarProperty::Dtstamp, now.clone());
+                repairs.push(ICalendarRepair::Dtstamp { component_id });
+            }
+        }
+
+        repairs
+    }
+}
+
+// Derived from the component contents so that re-importing the same file yields the same UIDs
+fn seeded_uid(seed: &str, component_id: u32, component: &ICalendarComponent) -> String {
+    let mut name = format!(
+        "{seed}\n{component_id}\n{}\n",
+        component.component_type.as_str()
+    );
+    for entry in &component.entries {
+        if !matches!(
+            entry.name,
+            ICalendarProperty::Uid | ICalendarProperty::Dtstamp
+        ) {
+            let _ = entry.write_to(&mut name);
+        }
+    }
+
+    uuid::Uuid::new_v5(&REPAIR_NAMESPACE, name.as_bytes())
+        .hyphenated()
+        .to_string()
+}
+
+#[cfg(test)]
+mod tests {
+    use super::{ICalendarRepair, RepairOptions};
+    use crate::icalendar::{ICalendar, ICalendarProperty};
+
+    #[test]
+    fn repair_uid_and_dtstamp() {
+        let input = concat!(
+            "BEGIN:VCALENDAR\r\n",
+            "BEGIN:VEVENT\r\n",
+            "SUMMARY:No UID\r\n",
+            "DTSTART:20240110T100000Z\r\n",
+            "BEGIN:VALARM\r\n",
+            "ACTION:DISPLAY\r\n",
+            "TRIGGER:-PT15M\r\n",
+            "END:VALARM\r\n",
+            "END:VEVENT\r\n",
+            "BEGIN:VTODO\r\n",
+            "UID:task\r\n",
+            "DTSTAMP:20240101T000000Z\r\n",
+            "END:VTODO\r\n",
+            "END:VCALENDAR\r\n"
+        );
+
+        // Nothing is repaired unless asked for
+        let mut ical = ICalendar::parse(input).unwrap();
+        assert!(ical.repair(RepairOptions::default()).is_empty());
+        assert_eq!(ical, ICalendar::parse(input).unwrap());
+
+        let seeded = |seed: &str| {
+            let mut ical = ICalendar::parse(input).unwrap();
+            let repairs = ical.repair(
+                RepairOptions::default()
+                    .generate_uid(true)
+                    .generate_dtstamp(true)
+                    .uid_seed(seed),
+            );
+            (ical, repairs)
+        };
+        let (ical, repairs) = seeded("calendar.ics");
+        let uid = ical.components[1].uid().unwrap().to_string();
+        assert_eq!(
+            repairs,
+            [
+                ICalendarRepair::Uid {
+                    component_id: 1,
+                    uid: uid.clone()
+                },
+                ICalendarRepair::Dtstamp { component_id: 1 }
+            ]
+        );
+        assert!(
+            ical.components[1]
+                .property(&ICalendarProperty::Dtstamp)
+                .is_some()
+        );
+        assert!(ical.components[2].uid().is_none());
+        assert_eq!(ical.components[3].uid(), Some("task"));
+
+        // Seeded UIDs are stable, unseeded ones are unique
+        assert_eq!(seeded("calendar.ics").1, repairs);
+        assert_ne!(seeded("other.ics").1, repairs);
+        let unseeded = |ical: &mut ICalendar| {
+            ical.repair(RepairOptions::default().generate_uid(true));
+            ical.components[1].uid().unwrap().to_string()
+        };
+        let mut ical = ICalendar::parse(input).unwrap();
+        let mut other = ICalendar::parse(input).unwrap();
+        assert_ne!(unseeded(&mut ical), unseeded(&mut other));
+    }
+}
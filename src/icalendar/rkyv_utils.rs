@@ -5,9 +5,27 @@
  */
 
 use super::*;
-use crate::common::{ArchivedPartialDateTime, timezone::Tz};
+use crate::common::ArchivedPartialDateTime;
+#[cfg(feature = "datetime")]
+use crate::common::timezone::Tz;
+#[cfg(feature = "datetime")]
 use chrono::DateTime;
 
+// Cached bytes are validated before use, so corrupt input is an error rather than UB
+impl ICalendar {
+    pub fn to_rkyv_bytes(&self) -> Result<rkyv::util::AlignedVec, rkyv::rancor::Error> {
+        rkyv::to_bytes::<rkyv::rancor::Error>(self)
+    }
+
+    pub fn from_rkyv_bytes(bytes: &[u8]) -> Result<Self, rkyv::rancor::Error> {
+        rkyv::from_bytes::<ICalendar, rkyv::rancor::Error>(bytes)
+    }
+
+    pub fn access_rkyv_bytes(bytes: &[u8]) -> Result<&ArchivedICalendar, rkyv::rancor::Error> {
+        rkyv::access::<ArchivedICalendar, rkyv::rancor::Error>(bytes)
+    }
+}
+
 impl ArchivedICalendar {
     pub fn uids(&self) -> impl Iterator<Item = &str> {
         self.components
@@ -284,6 +302,7 @@ impl ArchivedUri {
     }
 }
 
+#[cfg(feature = "datetime")]
 impl ArchivedICalendarPeriod {
     pub fn time_range(&self, tz: Tz) -> Option<(DateTime<Tz>, DateTime<Tz>)> {
         match self {
@@ -312,6 +331,7 @@ impl ArchivedICalendarPeriod {
 }
 
 impl ArchivedPartialDateTime {
+    #[cfg(feature = "datetime")]
     pub fn to_date_time_with_tz(&self, tz: Tz) -> Option<DateTime<Tz>> {
         self.to_date_time()
             .and_then(|dt| dt.to_date_time_with_tz(tz))
@@ -339,11 +359,12 @@ impl ArchivedPartialDateTime {
 }
 
 impl ArchivedICalendarDuration {
+    #[cfg(feature = "datetime")]
     pub fn to_time_delta(&self) -> Option<chrono::TimeDelta> {
-        chrono::TimeDelta::new(self.as_seconds(), 0)
+        chrono::TimeDelta::new(self.to_seconds(), 0)
     }
 
-    pub fn as_seconds(&self) -> i64 {
+    pub fn to_seconds(&self) -> i64 {
         let secs = self.seconds.to_native() as i64
             + self.minutes.to_native() as i64 * 60
             + self.hours.to_native() as i64 * 3600
@@ -353,6 +374,11 @@ impl ArchivedICalendarDuration {
         if self.neg { -secs } else { secs }
     }
 
+    #[deprecated(note = "use `to_seconds` instead")]
+    pub fn as_seconds(&self) -> i64 {
+        self.to_seconds()
+    }
+
     pub fn to_native(&self) -> ICalendarDuration {
         ICalendarDuration {
             neg: self.neg,
@@ -431,3 +457,37 @@ impl ArchivedICalendarMonth {
         self.0.unsigned_abs()
     }
 }
+
+#[cfg(test)]
+mod tests {
+    use crate::icalendar::ICalendar;
+    use rkyv::util::AlignedVec;
+
+    #[test]
+    fn rkyv_bytes_roundtrip() {
+        let ical = ICalendar::parse(concat!(
+            "BEGIN:VCALENDAR\r\n",
+            "BEGIN:VEVENT\r\n",
+            "UID:cached\r\n",
+            "SUMMARY:Cached event\r\n",
+            "DTSTART:20240110T100000Z\r\n",
+            "END:VEVENT\r\n",
+            "END:VCALENDAR\r\n"
+        ))
+        .unwrap();
+
+        let bytes = ical.to_rkyv_bytes().unwrap();
+        assert_eq!(ICalendar::from_rkyv_bytes(&bytes).unwrap(), ical);
+        let archived = ICalendar::access_rkyv_bytes(&bytes).unwrap();
+        assert_eq!(archived.uids().collect::<Vec<_>>(), ["cached"]);
+        assert_eq!(archived.to_string(), ical.to_string());
+
+        let mut corrupt = AlignedVec::<16>::new();
+        corrupt.extend_from_slice(&bytes[..bytes.len() / 2]);
+        assert!(ICalendar::access_rkyv_bytes(&corrupt).is_err());
+        assert!(ICalendar::from_rkyv_bytes(&corrupt).is_err());
+        corrupt.clear();
+        corrupt.extend_from_slice(&[0xff; 64]);
+        assert!(ICalendar::access_rkyv_bytes(&corrupt).is_err());
+    }
+}
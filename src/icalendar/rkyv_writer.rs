@@ -8,7 +8,7 @@ use super::*;
 use crate::{
     common::{
         ArchivedPartialDateTime, CalendarScale,
-        writer::{write_bytes, write_param_value, write_text},
+        writer::{DEFAULT_FOLD_WIDTH, write_bytes, write_param_value, write_text},
     },
     icalendar::ValueSeparator,
 };
@@ -106,7 +106,7 @@ impl ArchivedICalendarEntry {
 
             match &param.value {
                 ArchivedICalendarParameterValue::Text(v) => {
-                    write_param_value(out, &mut line_len, v)?;
+                    write_param_value(out, &mut line_len, DEFAULT_FOLD_WIDTH, v)?;
                 }
                 ArchivedICalendarParameterValue::Integer(i) => {
                     write!(out, "{i}")?;
@@ -123,49 +123,49 @@ impl ArchivedICalendarEntry {
                 }
                 ArchivedICalendarParameterValue::Uri(uri) => {
                     write!(out, "\"")?;
-                    write_uri(out, &mut line_len, uri, false)?;
+                    write_uri(out, &mut line_len, DEFAULT_FOLD_WIDTH, uri, false)?;
                     write!(out, "\"")?;
                 }
                 ArchivedICalendarParameterValue::Cutype(v) => {
-                    write_param_value(out, &mut line_len, v.as_str())?;
+                    write_param_value(out, &mut line_len, DEFAULT_FOLD_WIDTH, v.as_str())?;
                 }
                 ArchivedICalendarParameterValue::Fbtype(v) => {
-                    write_param_value(out, &mut line_len, v.as_str())?;
+                    write_param_value(out, &mut line_len, DEFAULT_FOLD_WIDTH, v.as_str())?;
                 }
                 ArchivedICalendarParameterValue::Partstat(v) => {
-                    write_param_value(out, &mut line_len, v.as_str())?;
+                    write_param_value(out, &mut line_len, DEFAULT_FOLD_WIDTH, v.as_str())?;
                 }
                 ArchivedICalendarParameterValue::Related(v) => {
-                    write_param_value(out, &mut line_len, v.as_str())?;
+                    write_param_value(out, &mut line_len, DEFAULT_FOLD_WIDTH, v.as_str())?;
                 }
                 ArchivedICalendarParameterValue::Reltype(v) => {
-                    write_param_value(out, &mut line_len, v.as_str())?;
+                    write_param_value(out, &mut line_len, DEFAULT_FOLD_WIDTH, v.as_str())?;
                 }
                 ArchivedICalendarParameterValue::Role(v) => {
-                    write_param_value(out, &mut line_len, v.as_str())?;
+                    write_param_value(out, &mut line_len, DEFAULT_FOLD_WIDTH, v.as_str())?;
                 }
                 ArchivedICalendarParameterValue::ScheduleAgent(v) => {
-                    write_param_value(out, &mut line_len, v.as_str())?;
+                    write_param_value(out, &mut line_len, DEFAULT_FOLD_WIDTH, v.as_str())?;
                 }
                 ArchivedICalendarParameterValue::ScheduleForceSend(v) => {
-                    write_param_value(out, &mut line_len, v.as_str())?;
+                    write_param_value(out, &mut line_len, DEFAULT_FOLD_WIDTH, v.as_str())?;
                 }
                 ArchivedICalendarParameterValue::Value(v) => {
                     types = Some(v);
-                    write_param_value(out, &mut line_len, v.as_str())?;
+                    write_param_value(out, &mut line_len, DEFAULT_FOLD_WIDTH, v.as_str())?;
                 }
                 ArchivedICalendarParameterValue::Display(v) => {
-                    write_param_value(out, &mut line_len, v.as_str())?;
+                    write_param_value(out, &mut line_len, DEFAULT_FOLD_WIDTH, v.as_str())?;
                 }
                 ArchivedICalendarParameterValue::Feature(v) => {
-                    write_param_value(out, &mut line_len, v.as_str())?;
+                    write_param_value(out, &mut line_len, DEFAULT_FOLD_WIDTH, v.as_str())?;
                 }
                 ArchivedICalendarParameterValue::Duration(v) => {
                     write!(out, "{v}")?;
                     line_len += 14;
                 }
                 ArchivedICalendarParameterValue::Linkrel(v) => {
-                    write_param_value(out, &mut line_len, v.as_str())?;
+                    write_param_value(out, &mut line_len, DEFAULT_FOLD_WIDTH, v.as_str())?;
                 }
                 ArchivedICalendarParameterValue::Null => {
                     last_param = None;
@@ -197,7 +197,7 @@ impl ArchivedICalendarEntry {
 
                 let text = match value {
                     ArchivedICalendarValue::Binary(v) => {
-                        write_bytes(out, Some(&mut line_len), v)?;
+                        write_bytes(out, Some(&mut line_len), DEFAULT_FOLD_WIDTH, v)?;
                         continue;
                     }
                     ArchivedICalendarValue::Boolean(v) => {
@@ -207,7 +207,7 @@ impl ArchivedICalendarEntry {
                         continue;
                     }
                     ArchivedICalendarValue::Uri(v) => {
-                        write_uri(out, &mut line_len, v, true)?;
+                        write_uri(out, &mut line_len, DEFAULT_FOLD_WIDTH, v, true)?;
                         continue;
                     }
                     ArchivedICalendarValue::PartialDateTime(v) => {
@@ -241,7 +241,7 @@ impl ArchivedICalendarEntry {
                         continue;
                     }
                     ArchivedICalendarValue::Text(v) => {
-                        write_text(out, &mut line_len, v, true, true)?;
+                        write_text(out, &mut line_len, DEFAULT_FOLD_WIDTH, v, true, true)?;
                         continue;
                     }
                     ArchivedICalendarValue::CalendarScale(v) => v.as_str(),
@@ -267,6 +267,7 @@ impl ArchivedICalendarEntry {
 pub(crate) fn write_uri(
     out: &mut impl Write,
     line_len: &mut usize,
+    fold_width: usize,
     value: &ArchivedUri,
     escape: bool,
 ) -> std::fmt::Result {
@@ -284,9 +285,9 @@ pub(crate) fn write_uri(
                 write!(out, "base64,")?;
             }
             *line_len += 8;
-            write_bytes(out, Some(line_len), &v.data)
+            write_bytes(out, Some(line_len), fold_width, &v.data)
         }
-        ArchivedUri::Location(v) => write_text(out, line_len, v, true, true),
+        ArchivedUri::Location(v) => write_text(out, line_len, fold_width, v, true, true),
     }
 }
 
@@ -538,6 +539,12 @@ impl ArchivedPartialDateTime {
                 self.tz_hour.as_ref().copied().unwrap_or_default(),
                 self.tz_minute.as_ref().copied().unwrap_or_default(),
             )?;
+            if self.hour.is_none()
+                && self.minute.is_none()
+                && let Some(second) = self.second.as_ref().filter(|second| **second != 0)
+            {
+                write!(out, "{:02}", second)?;
+            }
         }
 
         Ok(())
@@ -5,20 +5,41 @@
  */
 
 use super::{
-    ICalendar, ICalendarComponent, ICalendarComponentType, ICalendarEntry, ICalendarProperty,
-    ICalendarValue,
+    ICalendar, ICalendarComponent, ICalendarComponentType, ICalendarDuration, ICalendarEntry,
+    ICalendarParameterValue, ICalendarProperty, ICalendarValue,
 };
-use crate::{common::timezone::Tz, icalendar::ICalendarParameterName};
-use std::{collections::HashMap, str::FromStr};
+use crate::{
+    common::{DateTimeResult, PartialDateTime, timezone::Tz},
+    datecalc::{RRuleIter, rrule::RRule},
+    icalendar::ICalendarParameterName,
+};
+use chrono::{
+    DateTime, FixedOffset, NaiveDate, NaiveDateTime, NaiveTime, Offset, TimeDelta, TimeZone, Utc,
+};
+use std::{borrow::Borrow, cmp::Ordering, collections::HashMap, hash::Hash, str::FromStr};
 
 pub struct TzResolver<T> {
     tzs: HashMap<T, Tz>,
+    zones: HashMap<T, Vec<TimezoneObservance>>,
     default: Tz,
 }
 
+struct TimezoneObservance {
+    dt_start: NaiveDateTime,
+    offset_from: FixedOffset,
+    offset_to: FixedOffset,
+    // Sorted UTC onsets, expanded once when the zone is loaded
+    transitions: Vec<NaiveDateTime>,
+    // Recurrence rule still producing onsets past the expansion horizon
+    rrule: Option<(RRule, DateTime<Tz>)>,
+}
+
+// Onsets are precomputed up to this year, later lookups walk the rule
+const TRANSITION_HORIZON_YEAR: i32 = 2100;
+
 impl<T> TzResolver<T>
 where
-    T: std::borrow::Borrow<str> + std::hash::Hash + Eq,
+    T: Borrow<str> + Hash + Eq,
 {
     pub fn resolve(&self, tz_name: &str) -> Option<Tz> {
         self.tzs
@@ -42,6 +63,220 @@ where
         self.default = default.into();
         self
     }
+
+    // Offsets computed from the embedded VTIMEZONE definition, if there is one
+    pub fn offset(&self, tz_id: &str, local: NaiveDateTime) -> Option<FixedOffset> {
+        let observances = self.zones.get(tz_id)?;
+        let mut candidates = observances
+            .iter()
+            .flat_map(|observance| [observance.offset_from, observance.offset_to])
+            .collect::<Vec<_>>();
+        candidates.sort_unstable_by_key(|offset| std::cmp::Reverse(offset.local_minus_utc()));
+        candidates.dedup();
+
+        // When the local time is ambiguous, prefer the offset in effect before the transition
+        candidates
+            .iter()
+            .find(|&&offset| {
+                offset_at_utc(observances, local - offset_delta(offset)) == Some(offset)
+            })
+            .copied()
+            .or_else(|| offset_at_utc(observances, local - offset_delta(*candidates.first()?)))
+    }
+}
+
+fn offset_at_utc(observances: &[TimezoneObservance], utc: NaiveDateTime) -> Option<FixedOffset> {
+    observances
+        .iter()
+        .filter_map(|observance| {
+            observance
+                .last_transition(utc)
+                .map(|transition| (transition, observance.offset_to))
+        })
+        .max_by_key(|(transition, _)| *transition)
+        .map(|(_, offset)| offset)
+        .or_else(|| {
+            observances
+                .iter()
+                .min_by_key(|observance| observance.dt_start)
+                .map(|observance| observance.offset_from)
+        })
+}
+
+impl TimezoneObservance {
+    fn parse(component: &ICalendarComponent) -> Option<Self> {
+        let mut dt_start = None;
+        let mut offset_from = None;
+        let mut offset_to = None;
+        let mut rrule = None;
+        let mut rdates = Vec::new();
+
+        for entry in &component.entries {
+            match (&entry.name, entry.values.first()) {
+                (ICalendarProperty::Dtstart, Some(ICalendarValue::PartialDateTime(dt))) => {
+                    dt_start = dt.to_date_time().map(|dt| dt.date_time);
+                }
+                (ICalendarProperty::Tzoffsetfrom, Some(ICalendarValue::PartialDateTime(dt))) => {
+                    offset_from = dt.to_fixed_offset();
+                }
+                (ICalendarProperty::Tzoffsetto, Some(ICalendarValue::PartialDateTime(dt))) => {
+                    offset_to = dt.to_fixed_offset();
+                }
+                (ICalendarProperty::Rrule, Some(ICalendarValue::RecurrenceRule(rule))) => {
+                    rrule = RRule::from_floating_ical(rule);
+                }
+                (ICalendarProperty::Rdate, _) => {
+                    rdates.extend(entry.values.iter().filter_map(|value| {
+                        if let ICalendarValue::PartialDateTime(dt) = value {
+                            dt.to_date_time().map(|dt| dt.date_time)
+                        } else {
+                            None
+                        }
+                    }));
+                }
+                _ => (),
+            }
+        }
+
+        let dt_start = dt_start?;
+        let offset_from = offset_from?;
+        let delta = offset_delta(offset_from);
+        let mut transitions = rdates
+            .into_iter()
+            .map(|rdate| rdate - delta)
+            .collect::<Vec<_>>();
+        let mut unexpanded = None;
+
+        if let Some((rrule, start)) = rrule.and_then(|rrule| {
+            let start = Tz::Floating.from_local_datetime(&dt_start).single()?;
+            rrule.validate(start).ok().map(|rrule| (rrule, start))
+        }) {
+            let horizon = transition_horizon();
+            for onset in RRuleIter::new(&rrule, &start, true) {
+                let onset = onset.naive_local() - delta;
+                if onset > horizon {
+                    unexpanded = Some((rrule, start));
+                    break;
+                }
+                transitions.push(onset);
+            }
+        } else {
+            transitions.push(dt_start - delta);
+        }
+
+        transitions.sort_unstable();
+
+        Some(TimezoneObservance {
+            dt_start,
+            offset_from,
+            offset_to: offset_to?,
+            transitions,
+            rrule: unexpanded,
+        })
+    }
+
+    fn last_transition(&self, utc: NaiveDateTime) -> Option<NaiveDateTime> {
+        let mut last = self
+            .transitions
+            .partition_point(|onset| *onset <= utc)
+            .checked_sub(1)
+            .map(|idx| self.transitions[idx]);
+
+        if let Some((rrule, start)) = &self.rrule
+            && utc > transition_horizon()
+        {
+            let offset_from = offset_delta(self.offset_from);
+            for onset in RRuleIter::new(rrule, start, true) {
+                let onset = onset.naive_local() - offset_from;
+                if onset > utc {
+                    break;
+                }
+                if last.is_none_or(|last| onset > last) {
+                    last = Some(onset);
+                }
+            }
+        }
+
+        last
+    }
+}
+
+fn transition_horizon() -> NaiveDateTime {
+    NaiveDate::from_ymd_opt(TRANSITION_HORIZON_YEAR, 1, 1)
+        .unwrap_or_default()
+        .and_time(NaiveTime::MIN)
+}
+
+fn offset_delta(offset: FixedOffset) -> TimeDelta {
+    TimeDelta::seconds(offset.local_minus_utc() as i64)
+}
+
+impl PartialDateTime {
+    pub fn to_date_time_with(
+        &self,
+        tz_id: Option<&str>,
+        resolver: &TzResolver<impl Borrow<str> + Hash + Eq>,
+    ) -> Option<DateTime<Tz>> {
+        self.to_date_time()?.to_date_time_with(tz_id, resolver)
+    }
+
+    pub fn to_utc(
+        &self,
+        tz_id: Option<&str>,
+        resolver: &TzResolver<impl Borrow<str> + Hash + Eq>,
+    ) -> Option<DateTime<Utc>> {
+        // Floating times are interpreted as UTC
+        self.to_date_time_with(tz_id, resolver)
+            .map(|dt| dt.with_timezone(&Utc))
+    }
+
+    pub fn cmp_chronological(
+        &self,
+        tz_id: Option<&str>,
+        other: &PartialDateTime,
+        other_tz_id: Option<&str>,
+        resolver: &TzResolver<impl Borrow<str> + Hash + Eq>,
+    ) -> Option<Ordering> {
+        let dt = self.to_date_time_with(tz_id, resolver)?;
+        let other_dt = other.to_date_time_with(other_tz_id, resolver)?;
+
+        // Floating times are only comparable with each other
+        match (
+            dt.timezone().is_floating(),
+            other_dt.timezone().is_floating(),
+        ) {
+            (true, true) => Some(dt.naive_local().cmp(&other_dt.naive_local())),
+            (false, false) => Some(dt.cmp(&other_dt)),
+            _ => None,
+        }
+    }
+}
+
+impl DateTimeResult {
+    pub fn to_date_time_with(
+        &self,
+        tz_id: Option<&str>,
+        resolver: &TzResolver<impl Borrow<str> + Hash + Eq>,
+    ) -> Option<DateTime<Tz>> {
+        if self.offset.is_some() {
+            return self.to_date_time_with_tz(Tz::Floating);
+        }
+
+        let tz = if let Some(tz_id) = tz_id
+            && chrono_tz::Tz::from_str(tz_id).is_err()
+            && let Some(offset) = resolver.offset(tz_id, self.date_time)
+        {
+            if offset.local_minus_utc() == 0 {
+                Tz::UTC
+            } else {
+                Tz::Fixed(offset)
+            }
+        } else {
+            resolver.resolve_or_default(tz_id)
+        };
+
+        from_local_date_time(tz, &self.date_time)
+    }
 }
 
 impl ICalendar {
@@ -55,9 +290,97 @@ impl ICalendar {
         self.timezones().count() == 1
     }
 
+    pub fn map_windows_timezones(&mut self) -> Vec<String> {
+        let mut mappings: HashMap<String, Option<String>> = HashMap::new();
+        let mut unmapped = Vec::new();
+
+        for component in &self.components {
+            for entry in &component.entries {
+                let tz_id = match (&component.component_type, &entry.name, entry.values.first()) {
+                    (
+                        ICalendarComponentType::VTimezone,
+                        ICalendarProperty::Tzid,
+                        Some(ICalendarValue::Text(tz_id)),
+                    ) => tz_id.as_str(),
+                    _ => match entry.tz_id() {
+                        Some(tz_id) => tz_id,
+                        None => continue,
+                    },
+                };
+
+                if !mappings.contains_key(tz_id) {
+                    let mapping = if chrono_tz::Tz::from_str(tz_id).is_ok() {
+                        None
+                    } else if let Ok(tz @ Tz::Tz(_)) = Tz::from_str(tz_id) {
+                        tz.name().map(|name| name.into_owned())
+                    } else {
+                        unmapped.push(tz_id.to_string());
+                        None
+                    };
+                    mappings.insert(tz_id.to_string(), mapping);
+                }
+            }
+        }
+
+        let mut renamed_ids = Vec::new();
+        for (component_id, component) in self.components.iter_mut().enumerate() {
+            let is_timezone = component.component_type == ICalendarComponentType::VTimezone;
+            for entry in &mut component.entries {
+                if is_timezone
+                    && entry.name == ICalendarProperty::Tzid
+                    && let Some(ICalendarValue::Text(tz_id)) = entry.values.first_mut()
+                    && let Some(Some(new_tz_id)) = mappings.get(tz_id.as_str())
+                {
+                    *tz_id = new_tz_id.clone();
+                    renamed_ids.push(component_id as u32);
+                }
+                for param in &mut entry.params {
+                    if param.name == ICalendarParameterName::Tzid
+                        && let ICalendarParameterValue::Text(tz_id) = &mut param.value
+                        && let Some(Some(new_tz_id)) = mappings.get(tz_id.as_str())
+                    {
+                        *tz_id = new_tz_id.clone();
+                    }
+                }
+            }
+        }
+
+        // Drop renamed definitions that are now duplicates, preferring existing IANA ones
+        let mut timezones = self
+            .components
+            .iter()
+            .enumerate()
+            .filter(|(_, component)| component.component_type == ICalendarComponentType::VTimezone)
+            .filter_map(|(component_id, component)| {
+                let tz_id = component
+                    .property(&ICalendarProperty::Tzid)?
+                    .values
+                    .first()?
+                    .as_text()?;
+                Some((
+                    tz_id,
+                    renamed_ids.contains(&(component_id as u32)),
+                    component_id as u32,
+                ))
+            })
+            .collect::<Vec<_>>();
+        timezones.sort_unstable();
+        let remove_ids = timezones
+            .windows(2)
+            .filter(|pair| pair[0].0 == pair[1].0)
+            .map(|pair| pair[1].2)
+            .collect::<Vec<_>>();
+        if !remove_ids.is_empty() {
+            self.remove_component_ids(&remove_ids);
+        }
+
+        unmapped
+    }
+
     pub fn build_tz_resolver(&self) -> TzResolver<&'_ str> {
         TzResolver {
             tzs: self.timezones().filter_map(|tz| tz.timezone()).collect(),
+            zones: self.timezone_observances().collect(),
             default: Tz::Floating,
         }
     }
@@ -69,9 +392,36 @@ impl ICalendar {
                 .filter_map(|tz| tz.timezone())
                 .map(|(name, tz)| (name.to_string(), tz))
                 .collect(),
+            zones: self
+                .timezone_observances()
+                .map(|(name, observances)| (name.to_string(), observances))
+                .collect(),
             default: Tz::Floating,
         }
     }
+
+    fn timezone_observances(&self) -> impl Iterator<Item = (&str, Vec<TimezoneObservance>)> {
+        self.timezones().filter_map(|tz| {
+            let tz_id = tz
+                .property(&ICalendarProperty::Tzid)
+                .and_then(|entry| entry.values.first())
+                .and_then(|value| value.as_text())?;
+            let observances = tz
+                .component_ids
+                .iter()
+                .filter_map(|id| self.components.get(*id as usize))
+                .filter(|comp| {
+                    matches!(
+                        comp.component_type,
+                        ICalendarComponentType::Standard | ICalendarComponentType::Daylight
+                    )
+                })
+                .filter_map(TimezoneObservance::parse)
+                .collect::<Vec<_>>();
+
+            (!observances.is_empty()).then_some((tz_id, observances))
+        })
+    }
 }
 
 impl ICalendarComponent {
@@ -106,9 +456,512 @@ impl ICalendarComponent {
     }
 }
 
+impl ICalendarComponent {
+    pub fn effective_end(
+        &self,
+        resolver: &TzResolver<impl Borrow<str> + Hash + Eq>,
+    ) -> Option<DateTimeResult> {
+        let mut start = None;
+        let mut end = None;
+        let mut duration = None;
+
+        for entry in &self.entries {
+            match (&entry.name, entry.values.first()) {
+                (ICalendarProperty::Dtstart, Some(ICalendarValue::PartialDateTime(dt))) => {
+                    start = Some((dt, entry.tz_id()));
+                }
+                (ICalendarProperty::Dtend, Some(ICalendarValue::PartialDateTime(dt))) => {
+                    end = Some((dt, entry.tz_id()));
+                }
+                (ICalendarProperty::Duration, Some(ICalendarValue::Duration(dur))) => {
+                    duration = Some(dur);
+                }
+                _ => (),
+            }
+        }
+
+        if let Some((end, tz_id)) = end {
+            return end
+                .to_date_time_with(tz_id, resolver)
+                .map(to_date_time_result);
+        }
+
+        let (start, tz_id) = start?;
+        let duration = match duration {
+            Some(duration) => duration.clone(),
+            None if !start.has_time() => ICalendarDuration {
+                days: 1,
+                ..Default::default()
+            },
+            None => ICalendarDuration::default(),
+        };
+
+        let (nominal, exact) = split_nominal(&duration)?;
+        start
+            .add_duration(&nominal)?
+            .to_date_time_with(tz_id, resolver)?
+            .checked_add_signed(exact)
+            .map(to_date_time_result)
+    }
+
+    pub fn start_in(
+        &self,
+        tz: &Tz,
+        resolver: &TzResolver<impl Borrow<str> + Hash + Eq>,
+    ) -> Option<DateTime<Tz>> {
+        let entry = self.property(&ICalendarProperty::Dtstart)?;
+        let start = entry
+            .values
+            .first()?
+            .as_partial_date_time()?
+            .to_date_time()?;
+
+        match entry.tz_id() {
+            // Floating times keep their wall-clock time in the target zone
+            None if start.offset.is_none() => from_local_date_time(*tz, &start.date_time),
+            tz_id => start
+                .to_date_time_with(tz_id, resolver)
+                .map(|dt| dt.with_timezone(tz)),
+        }
+    }
+}
+
+// Ambiguous times resolve to the first occurrence and nonexistent ones are interpreted
+// using the offset before the gap, as described in RFC 5545, Section 3.3.5
+fn from_local_date_time(tz: Tz, local: &NaiveDateTime) -> Option<DateTime<Tz>> {
+    tz.from_local_datetime(local).earliest().or_else(|| {
+        (1..=48)
+            .find_map(|hours| {
+                tz.from_local_datetime(&(*local - TimeDelta::hours(hours)))
+                    .earliest()
+            })
+            .map(|before| tz.from_utc_datetime(&(*local - offset_delta(before.offset().fix()))))
+    })
+}
+
+pub(crate) fn add_duration(
+    dt: &DateTime<Tz>,
+    duration: &ICalendarDuration,
+) -> Option<DateTime<Tz>> {
+    let (nominal, exact) = split_nominal(duration)?;
+    from_local_date_time(
+        dt.timezone(),
+        &dt.naive_local()
+            .checked_add_signed(nominal.to_time_delta()?)?,
+    )?
+    .checked_add_signed(exact)
+}
+
+// Weeks and days follow the wall clock, while the time part is an exact duration
+fn split_nominal(duration: &ICalendarDuration) -> Option<(ICalendarDuration, TimeDelta)> {
+    let nominal = ICalendarDuration {
+        neg: duration.neg,
+        weeks: duration.weeks,
+        days: duration.days,
+        ..Default::default()
+    };
+    let exact = duration.to_time_delta()? - nominal.to_time_delta()?;
+    Some((nominal, exact))
+}
+
+fn to_date_time_result(dt: DateTime<Tz>) -> DateTimeResult {
+    DateTimeResult {
+        date_time: dt.naive_local(),
+        offset: (!dt.timezone().is_floating()).then(|| dt.offset().fix()),
+    }
+}
+
 impl ICalendarEntry {
     pub fn tz_id(&self) -> Option<&str> {
         self.parameters(&ICalendarParameterName::Tzid)
             .find_map(|v| v.as_text())
     }
 }
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use chrono::NaiveDate;
+
+    #[test]
+    fn map_windows_timezones() {
+        let mut ical = ICalendar::parse(concat!(
+            "BEGIN:VCALENDAR\r\n",
+            "BEGIN:VTIMEZONE\r\n",
+            "TZID:Europe/Berlin\r\n",
+            "BEGIN:STANDARD\r\n",
+            "DTSTART:19701025T030000\r\n",
+            "TZOFFSETFROM:+0200\r\n",
+            "TZOFFSETTO:+0100\r\n",
+            "END:STANDARD\r\n",
+            "END:VTIMEZONE\r\n",
+            "BEGIN:VTIMEZONE\r\n",
+            "TZID:W. Europe Standard Time\r\n",
+            "BEGIN:STANDARD\r\n",
+            "DTSTART:16010101T030000\r\n",
+            "TZOFFSETFROM:+0200\r\n",
+            "TZOFFSETTO:+0100\r\n",
+            "END:STANDARD\r\n",
+            "END:VTIMEZONE\r\n",
+            "BEGIN:VTIMEZONE\r\n",
+            "TZID:Pacific Standard Time\r\n",
+            "BEGIN:STANDARD\r\n",
+            "DTSTART:16010101T020000\r\n",
+            "TZOFFSETFROM:-0700\r\n",
+            "TZOFFSETTO:-0800\r\n",
+            "END:STANDARD\r\n",
+            "END:VTIMEZONE\r\n",
+            "BEGIN:VEVENT\r\n",
+            "DTSTART;TZID=\"W. Europe Standard Time\":20240101T090000\r\n",
+            "DTEND;TZID=Pacific Standard Time:20240101T100000\r\n",
+            "EXDATE;TZID=My Custom Zone:20240102T090000\r\n",
+            "END:VEVENT\r\n",
+            "END:VCALENDAR\r\n"
+        ))
+        .unwrap();
+
+        assert_eq!(
+            ical.map_windows_timezones(),
+            vec!["My Custom Zone".to_string()]
+        );
+        assert_eq!(
+            ical.timezones()
+                .filter_map(|tz| tz
+                    .property(&ICalendarProperty::Tzid)?
+                    .values
+                    .first()?
+                    .as_text())
+                .collect::<Vec<_>>(),
+            vec!["Europe/Berlin", "America/Los_Angeles"]
+        );
+        let event = ical
+            .components
+            .iter()
+            .find(|component| component.component_type == ICalendarComponentType::VEvent)
+            .unwrap();
+        assert_eq!(
+            event
+                .entries
+                .iter()
+                .map(|entry| entry.tz_id().unwrap())
+                .collect::<Vec<_>>(),
+            vec!["Europe/Berlin", "America/Los_Angeles", "My Custom Zone"]
+        );
+        assert_eq!(ical.components[0].component_ids.len(), 3);
+    }
+
+    #[test]
+    fn embedded_timezone_resolver() {
+        let ical = ICalendar::parse(concat!(
+            "BEGIN:VCALENDAR\r\n",
+            "BEGIN:VTIMEZONE\r\n",
+            "TZID:Custom Central European\r\n",
+            "BEGIN:DAYLIGHT\r\n",
+            "DTSTART:19700329T020000\r\n",
+            "RRULE:FREQ=YEARLY;BYMONTH=3;BYDAY=-1SU\r\n",
+            "TZOFFSETFROM:+0100\r\n",
+            "TZOFFSETTO:+0200\r\n",
+            "END:DAYLIGHT\r\n",
+            "BEGIN:STANDARD\r\n",
+            "DTSTART:19701025T030000\r\n",
+            "RRULE:FREQ=YEARLY;BYMONTH=10;BYDAY=-1SU\r\n",
+            "TZOFFSETFROM:+0200\r\n",
+            "TZOFFSETTO:+0100\r\n",
+            "END:STANDARD\r\n",
+            "END:VTIMEZONE\r\n",
+            "END:VCALENDAR\r\n"
+        ))
+        .unwrap();
+        let resolver = ical.build_tz_resolver();
+
+        for (local, expected) in [
+            // Winter and summer
+            ("20240115T120000", 3600),
+            ("20240715T120000", 7200),
+            // Spring forward gap
+            ("20240331T023000", 3600),
+            ("20240331T030000", 7200),
+            // Fall back fold
+            ("20241027T023000", 7200),
+            ("20241027T030000", 3600),
+            // Past the precomputed transitions
+            ("21500115T120000", 3600),
+            ("21500715T120000", 7200),
+        ] {
+            let dt = NaiveDateTime::parse_from_str(local, "%Y%m%dT%H%M%S").unwrap();
+            assert_eq!(
+                resolver
+                    .offset("Custom Central European", dt)
+                    .map(|offset| offset.local_minus_utc()),
+                Some(expected),
+                "failed for {local}"
+            );
+        }
+
+        let dt = PartialDateTime {
+            year: Some(2024),
+            month: Some(7),
+            day: Some(15),
+            hour: Some(12),
+            minute: Some(0),
+            second: Some(0),
+            ..Default::default()
+        };
+        assert_eq!(
+            dt.to_date_time_with(Some("Custom Central European"), &resolver)
+                .unwrap()
+                .timestamp(),
+            NaiveDate::from_ymd_opt(2024, 7, 15)
+                .unwrap()
+                .and_hms_opt(10, 0, 0)
+                .unwrap()
+                .and_utc()
+                .timestamp()
+        );
+        assert_eq!(
+            dt.to_date_time_with(Some("America/New_York"), &resolver)
+                .unwrap()
+                .timestamp(),
+            NaiveDate::from_ymd_opt(2024, 7, 15)
+                .unwrap()
+                .and_hms_opt(16, 0, 0)
+                .unwrap()
+                .and_utc()
+                .timestamp()
+        );
+    }
+
+    #[test]
+    fn cmp_chronological() {
+        let ical = ICalendar::parse(concat!(
+            "BEGIN:VCALENDAR\r\n",
+            "BEGIN:VEVENT\r\n",
+            "DTSTART;TZID=Europe/Berlin:20240115T100000\r\n",
+            "DTSTART:20240115T083000Z\r\n",
+            "DTSTART;TZID=America/New_York:20240115T040000\r\n",
+            "DTSTART:20240115T100000\r\n",
+            "DTSTART;VALUE=DATE:20240115\r\n",
+            "END:VEVENT\r\n",
+            "END:VCALENDAR\r\n"
+        ))
+        .unwrap();
+        let resolver = ical.build_tz_resolver();
+        let starts = ical.components[1]
+            .entries
+            .iter()
+            .filter_map(|entry| {
+                Some((entry.values.first()?.as_partial_date_time()?, entry.tz_id()))
+            })
+            .collect::<Vec<_>>();
+        let cmp = |a: usize, b: usize| {
+            starts[a]
+                .0
+                .cmp_chronological(starts[a].1, starts[b].0, starts[b].1, &resolver)
+        };
+
+        // 10:00 Berlin is 09:00 UTC, 04:00 New York is 09:00 UTC
+        assert_eq!(cmp(0, 1), Some(Ordering::Greater));
+        assert_eq!(cmp(0, 2), Some(Ordering::Equal));
+        assert_eq!(cmp(1, 2), Some(Ordering::Less));
+        assert_eq!(cmp(3, 4), Some(Ordering::Greater));
+        assert_eq!(cmp(0, 3), None);
+        let year_only = PartialDateTime {
+            year: Some(2024),
+            ..Default::default()
+        };
+        assert_eq!(
+            starts[4]
+                .0
+                .cmp_chronological(None, &year_only, None, &resolver),
+            None
+        );
+
+        assert!(starts[1].0.is_utc());
+        assert!(!starts[0].0.is_utc());
+        assert!(starts[4].0.is_date_only());
+        assert!(!starts[3].0.is_date_only());
+        assert!(!year_only.is_date_only());
+    }
+
+    #[test]
+    fn chrono_conversion() {
+        let ical = ICalendar::parse(concat!(
+            "BEGIN:VCALENDAR\r\n",
+            "BEGIN:VEVENT\r\n",
+            "DTSTART;TZID=Europe/Berlin:20240115T100000\r\n",
+            "DTSTART:20240115T100000\r\n",
+            "DTSTART;VALUE=DATE:20240115\r\n",
+            "END:VEVENT\r\n",
+            "END:VCALENDAR\r\n"
+        ))
+        .unwrap();
+        let resolver = ical.build_tz_resolver();
+        let utc = ical.components[1]
+            .entries
+            .iter()
+            .filter_map(|entry| {
+                let dt = entry.values.first()?.as_partial_date_time()?;
+                Some((dt.to_utc(entry.tz_id(), &resolver)?, dt.is_date_only()))
+            })
+            .map(|(dt, is_date_only)| (dt.to_rfc3339(), is_date_only))
+            .collect::<Vec<_>>();
+        assert_eq!(
+            utc,
+            [
+                ("2024-01-15T09:00:00+00:00".to_string(), false),
+                ("2024-01-15T10:00:00+00:00".to_string(), false),
+                ("2024-01-15T00:00:00+00:00".to_string(), true),
+            ]
+        );
+
+        let offset = FixedOffset::west_opt(5 * 3600 + 30 * 60).unwrap();
+        let dt = offset.with_ymd_and_hms(2024, 7, 15, 12, 30, 45).unwrap();
+        let partial = PartialDateTime::from_chrono(&dt);
+        assert_eq!(
+            partial,
+            PartialDateTime {
+                year: Some(2024),
+                month: Some(7),
+                day: Some(15),
+                hour: Some(12),
+                minute: Some(30),
+                second: Some(45),
+                tz_hour: Some(5),
+                tz_minute: Some(30),
+                tz_minus: true,
+            }
+        );
+        assert_eq!(
+            partial.to_utc(None, &resolver).unwrap(),
+            dt.with_timezone(&Utc)
+        );
+    }
+
+    #[test]
+    fn effective_end() {
+        let ical = ICalendar::parse(concat!(
+            "BEGIN:VCALENDAR\r\n",
+            "BEGIN:VEVENT\r\n",
+            "UID:dtend\r\n",
+            "DTSTART;TZID=Europe/Berlin:20241231T220000\r\n",
+            "DTEND;TZID=America/New_York:20241231T180000\r\n",
+            "END:VEVENT\r\n",
+            "BEGIN:VEVENT\r\n",
+            "UID:duration\r\n",
+            "DTSTART:20241231T230000Z\r\n",
+            "DURATION:P1DT2H\r\n",
+            "END:VEVENT\r\n",
+            "BEGIN:VEVENT\r\n",
+            "UID:dst\r\n",
+            "DTSTART;TZID=America/New_York:20240310T013000\r\n",
+            "DURATION:PT1H\r\n",
+            "END:VEVENT\r\n",
+            "BEGIN:VEVENT\r\n",
+            "UID:all-day\r\n",
+            "DTSTART;VALUE=DATE:20240228\r\n",
+            "END:VEVENT\r\n",
+            "BEGIN:VEVENT\r\n",
+            "UID:instant\r\n",
+            "DTSTART:20240115T090000\r\n",
+            "END:VEVENT\r\n",
+            "BEGIN:VEVENT\r\n",
+            "UID:no-start\r\n",
+            "END:VEVENT\r\n",
+            "END:VCALENDAR\r\n"
+        ))
+        .unwrap();
+        let resolver = ical.build_tz_resolver();
+
+        assert_eq!(
+            ical.components
+                .iter()
+                .filter(|comp| comp.component_type == ICalendarComponentType::VEvent)
+                .map(|comp| comp.effective_end(&resolver).map(|end| (
+                    end.date_time.format("%Y%m%dT%H%M%S").to_string(),
+                    end.offset.map(|offset| offset.local_minus_utc())
+                )))
+                .collect::<Vec<_>>(),
+            [
+                Some(("20241231T180000".to_string(), Some(-18000))),
+                Some(("20250102T010000".to_string(), Some(0))),
+                Some(("20240310T033000".to_string(), Some(-14400))),
+                Some(("20240229T000000".to_string(), None)),
+                Some(("20240115T090000".to_string(), None)),
+                None,
+            ]
+        );
+
+        let dt = PartialDateTime {
+            year: Some(2023),
+            month: Some(12),
+            day: Some(31),
+            ..Default::default()
+        };
+        assert_eq!(
+            dt.add_duration(&ICalendarDuration {
+                days: 1,
+                ..Default::default()
+            }),
+            Some(PartialDateTime {
+                year: Some(2024),
+                month: Some(1),
+                day: Some(1),
+                ..Default::default()
+            })
+        );
+    }
+
+    #[test]
+    fn start_in_timezone() {
+        let ical = ICalendar::parse(concat!(
+            "BEGIN:VCALENDAR\r\n",
+            "BEGIN:VEVENT\r\n",
+            "UID:gap\r\n",
+            "DTSTART;TZID=America/New_York:20240310T023000\r\n",
+            "END:VEVENT\r\n",
+            "BEGIN:VEVENT\r\n",
+            "UID:overlap\r\n",
+            "DTSTART;TZID=Europe/Berlin:20241027T023000\r\n",
+            "END:VEVENT\r\n",
+            "BEGIN:VEVENT\r\n",
+            "UID:utc\r\n",
+            "DTSTART:20240115T120000Z\r\n",
+            "END:VEVENT\r\n",
+            "BEGIN:VEVENT\r\n",
+            "UID:floating\r\n",
+            "DTSTART:20240115T090000\r\n",
+            "END:VEVENT\r\n",
+            "BEGIN:VEVENT\r\n",
+            "UID:undated\r\n",
+            "END:VEVENT\r\n",
+            "END:VCALENDAR\r\n"
+        ))
+        .unwrap();
+        let resolver = ical.build_tz_resolver();
+        let start_in = |uid: &str, tz: &str| {
+            ical.events()
+                .find(|event| event.uid() == Some(uid))
+                .unwrap()
+                .start_in(&Tz::from_str(tz).unwrap(), &resolver)
+                .map(|dt| dt.to_rfc3339())
+        };
+
+        for (uid, tz, expected) in [
+            // 02:30 does not exist in New York, the offset before the gap is used
+            ("gap", "Europe/Paris", "2024-03-10T08:30:00+01:00"),
+            ("gap", "America/New_York", "2024-03-10T03:30:00-04:00"),
+            // 02:30 happens twice in Berlin, the first occurrence is used
+            ("overlap", "America/New_York", "2024-10-26T20:30:00-04:00"),
+            ("utc", "Asia/Tokyo", "2024-01-15T21:00:00+09:00"),
+            ("floating", "Asia/Tokyo", "2024-01-15T09:00:00+09:00"),
+            ("floating", "America/New_York", "2024-01-15T09:00:00-05:00"),
+        ] {
+            assert_eq!(
+                start_in(uid, tz).as_deref(),
+                Some(expected),
+                "failed for {uid} in {tz}"
+            );
+        }
+        assert_eq!(start_in("undated", "UTC"), None);
+    }
+}
@@ -5,7 +5,10 @@
  */
 
 use super::*;
-use crate::{Entry, Parser};
+use crate::{
+    Entry, Parser,
+    common::{EntrySpan, parser::decode_text},
+};
 use std::borrow::Cow;
 
 impl IanaParse for ICalendarProperty {
@@ -178,6 +181,11 @@ impl ICalendarProperty {
         }
     }
 
+    // Unknown names keep their original spelling so they can be written back as-is
+    pub fn eq_ignore_ascii_case(&self, other: &ICalendarProperty) -> bool {
+        self == other || self.as_str().eq_ignore_ascii_case(other.as_str())
+    }
+
     pub fn into_string(self) -> Cow<'static, str> {
         match self {
             ICalendarProperty::Calscale => "CALSCALE",
@@ -264,6 +272,13 @@ impl ICalendarProperty {
     }
 }
 
+impl From<&str> for ICalendarProperty {
+    fn from(value: &str) -> Self {
+        <ICalendarProperty as IanaParse>::parse(value.as_bytes())
+            .unwrap_or_else(|| ICalendarProperty::Other(value.to_string()))
+    }
+}
+
 impl ICalendarParameterName {
     pub fn try_parse(input: &[u8]) -> Option<Self> {
         hashify::tiny_map_ignore_case!(input,
@@ -348,6 +363,10 @@ impl ICalendarParameterName {
         }
     }
 
+    pub fn eq_ignore_ascii_case(&self, other: &ICalendarParameterName) -> bool {
+        self == other || self.as_str().eq_ignore_ascii_case(other.as_str())
+    }
+
     pub fn into_string(self) -> Cow<'static, str> {
         match self {
             ICalendarParameterName::Altrep => "ALTREP",
@@ -395,6 +414,50 @@ impl ICalendarParameterName {
         Self::try_parse(input.as_bytes())
             .unwrap_or_else(|| ICalendarParameterName::Other(input.to_string()))
     }
+
+    pub fn canonical_value(&self, value: &str) -> Option<&'static str> {
+        let value = value.as_bytes();
+        match self {
+            ICalendarParameterName::Cutype => ICalendarUserTypes::parse(value).map(|v| v.as_str()),
+            ICalendarParameterName::Fbtype => {
+                ICalendarFreeBusyType::parse(value).map(|v| v.as_str())
+            }
+            ICalendarParameterName::Partstat => {
+                ICalendarParticipationStatus::parse(value).map(|v| v.as_str())
+            }
+            ICalendarParameterName::Range => value
+                .eq_ignore_ascii_case(b"THISANDFUTURE")
+                .then_some("THISANDFUTURE"),
+            ICalendarParameterName::Related => ICalendarRelated::parse(value).map(|v| v.as_str()),
+            ICalendarParameterName::Reltype => {
+                ICalendarRelationshipType::parse(value).map(|v| v.as_str())
+            }
+            ICalendarParameterName::Role => {
+                ICalendarParticipationRole::parse(value).map(|v| v.as_str())
+            }
+            ICalendarParameterName::ScheduleAgent => {
+                ICalendarScheduleAgentValue::parse(value).map(|v| v.as_str())
+            }
+            ICalendarParameterName::ScheduleForceSend => {
+                ICalendarScheduleForceSendValue::parse(value).map(|v| v.as_str())
+            }
+            ICalendarParameterName::Value => ICalendarValueType::parse(value).map(|v| v.as_str()),
+            ICalendarParameterName::Display => {
+                ICalendarDisplayType::parse(value).map(|v| v.as_str())
+            }
+            ICalendarParameterName::Feature => {
+                ICalendarFeatureType::parse(value).map(|v| v.as_str())
+            }
+            ICalendarParameterName::Linkrel => LinkRelation::parse(value).map(|v| v.as_str()),
+            _ => None,
+        }
+    }
+}
+
+impl From<&str> for ICalendarParameterName {
+    fn from(value: &str) -> Self {
+        ICalendarParameterName::parse(value)
+    }
 }
 
 impl IanaParse for ICalendarFrequency {
@@ -473,6 +536,7 @@ impl IanaString for ICalendarWeekday {
     }
 }
 
+#[cfg(feature = "datetime")]
 impl From<ICalendarWeekday> for chrono::Weekday {
     fn from(value: ICalendarWeekday) -> Self {
         match value {
@@ -1359,6 +1423,20 @@ impl ICalendar {
             other => Err(other),
         }
     }
+
+    pub fn parse_with_spans(input: &str) -> Result<(Self, Vec<EntrySpan>), Entry> {
+        let mut parser = Parser::new(input).with_spans();
+        match parser.entry() {
+            Entry::ICalendar(icalendar) => Ok((icalendar, parser.take_spans())),
+            other => Err(other),
+        }
+    }
+
+    pub fn parse_bytes(value: &[u8]) -> Result<Self, Entry> {
+        decode_text(value)
+            .ok_or(Entry::InvalidEncoding)
+            .and_then(Self::parse)
+    }
 }
 
 impl Hash for ICalendarValue {
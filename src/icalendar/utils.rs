@@ -4,17 +4,78 @@
  * SPDX-License-Identifier: Apache-2.0 OR MIT
  */
 
-use std::borrow::Cow;
+use std::borrow::{Borrow, Cow};
 
+#[cfg(feature = "datetime")]
+use super::timezone::TzResolver;
 use super::{
     ICalendar, ICalendarComponent, ICalendarComponentType, ICalendarDuration, ICalendarEntry,
-    ICalendarParameterName, ICalendarProperty, ICalendarRecurrenceRule, ICalendarStatus,
-    ICalendarTransparency, ICalendarValue, Uri,
+    ICalendarFeatureType, ICalendarParameterName, ICalendarParticipantType,
+    ICalendarParticipationRole, ICalendarParticipationStatus, ICalendarPeriod, ICalendarProperty,
+    ICalendarRecurrenceRule, ICalendarStatus, ICalendarTransparency, ICalendarUserTypes,
+    ICalendarValue, Uri,
 };
+#[cfg(feature = "datetime")]
+use crate::common::timezone::Tz;
 use crate::{
-    common::{IanaString, IanaType, PartialDateTime},
+    common::{IanaString, IanaType, PartialDateTime, UtcOffset, color::color_to_rgb},
     icalendar::{ICalendarParameterValue, ICalendarValueType},
 };
+#[cfg(feature = "datetime")]
+use chrono::{Datelike, Timelike};
+#[cfg(feature = "datetime")]
+use std::hash::Hash;
+
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct ICalendarContact<'x> {
+    pub text: &'x str,
+    pub altrep: Option<&'x str>,
+    pub language: Option<&'x str>,
+}
+
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct Conference<'x> {
+    pub uri: &'x str,
+    pub features: Vec<IanaType<&'x ICalendarFeatureType, &'x str>>,
+    pub label: Option<&'x str>,
+}
+
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct Attendee<'x> {
+    pub address: &'x str,
+    pub name: Option<&'x str>,
+    pub participation_status: Option<&'x ICalendarParticipationStatus>,
+    pub role: Option<&'x ICalendarParticipationRole>,
+    pub rsvp: bool,
+    pub kind: Option<&'x ICalendarUserTypes>,
+    pub entry: &'x ICalendarEntry,
+}
+
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct Organizer<'x> {
+    pub address: &'x str,
+    pub name: Option<&'x str>,
+    pub entry: &'x ICalendarEntry,
+}
+
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct UnifiedParticipant<'x> {
+    pub calendar_address: Option<&'x str>,
+    pub name: Option<&'x str>,
+    pub kind: Option<&'x ICalendarUserTypes>,
+    pub role: Option<&'x ICalendarParticipationRole>,
+    pub participation_status: Option<&'x ICalendarParticipationStatus>,
+    pub participant_types: Vec<&'x ICalendarParticipantType>,
+    pub links: Vec<&'x str>,
+    pub attendee: Option<&'x ICalendarEntry>,
+    pub participant: Option<&'x ICalendarComponent>,
+}
+
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum EventEnd<'x> {
+    Dtend(&'x PartialDateTime),
+    Duration(&'x ICalendarDuration),
+}
 
 pub fn strip_mailto_scheme(value: &str) -> &str {
     value
@@ -23,6 +84,10 @@ pub fn strip_mailto_scheme(value: &str) -> &str {
         .map_or(value, |(_, address)| address)
 }
 
+pub fn sort_by_order<T: Borrow<ICalendarEntry>>(entries: &mut [T]) {
+    entries.sort_by_key(|entry| entry.borrow().order().unwrap_or(u32::MAX));
+}
+
 impl ICalendar {
     pub fn uids(&self) -> impl Iterator<Item = &str> {
         self.components
@@ -41,6 +106,28 @@ impl ICalendar {
         self.components.get(id as usize)
     }
 
+    pub fn children(&self, component_index: usize) -> impl Iterator<Item = &ICalendarComponent> {
+        self.components
+            .get(component_index)
+            .map_or(&[][..], |c| c.component_ids.as_slice())
+            .iter()
+            .filter_map(|id| self.component_by_id(*id))
+    }
+
+    // Components that are not nested in any other, normally the VCALENDAR
+    pub fn root_components(&self) -> impl Iterator<Item = &ICalendarComponent> {
+        self.components
+            .iter()
+            .enumerate()
+            .filter_map(|(idx, component)| {
+                (!self
+                    .components
+                    .iter()
+                    .any(|parent| parent.component_ids.contains(&(idx as u32))))
+                .then_some(component)
+            })
+    }
+
     pub fn alarms_for_id(&self, id: u32) -> impl Iterator<Item = &ICalendarComponent> {
         self.component_by_id(id)
             .map_or(&[][..], |c| c.component_ids.as_slice())
@@ -50,6 +137,27 @@ impl ICalendar {
                     .filter(|c| c.component_type == ICalendarComponentType::VAlarm)
             })
     }
+
+    pub fn events(&self) -> impl Iterator<Item = &ICalendarComponent> {
+        self.top_level(ICalendarComponentType::VEvent)
+    }
+
+    pub fn todos(&self) -> impl Iterator<Item = &ICalendarComponent> {
+        self.top_level(ICalendarComponentType::VTodo)
+    }
+
+    pub fn journals(&self) -> impl Iterator<Item = &ICalendarComponent> {
+        self.top_level(ICalendarComponentType::VJournal)
+    }
+
+    // Direct children of the VCALENDAR, so alarms and other nested components are skipped
+    fn top_level(
+        &self,
+        component_type: ICalendarComponentType,
+    ) -> impl Iterator<Item = &ICalendarComponent> {
+        self.children(0)
+            .filter(move |component| component.component_type == component_type)
+    }
 }
 
 impl ICalendarComponent {
@@ -65,6 +173,79 @@ impl ICalendarComponent {
             .and_then(|v| v.as_text())
     }
 
+    pub fn summary(&self) -> Option<&str> {
+        self.property(&ICalendarProperty::Summary)
+            .and_then(|e| e.values.first())
+            .and_then(|v| v.as_text())
+    }
+
+    pub fn color(&self) -> Option<&str> {
+        self.property(&ICalendarProperty::Color)
+            .and_then(|entry| entry.values.first())
+            .and_then(|value| value.as_text())
+    }
+
+    pub fn color_rgb(&self) -> Option<(u8, u8, u8)> {
+        self.color().and_then(color_to_rgb)
+    }
+
+    pub fn description(&self) -> Option<&str> {
+        self.descriptions().next()
+    }
+
+    // VJOURNAL may carry more than one DESCRIPTION
+    pub fn descriptions(&self) -> impl Iterator<Item = &str> {
+        self.properties(&ICalendarProperty::Description)
+            .filter_map(|e| e.values.first())
+            .filter_map(|v| v.as_text())
+    }
+
+    pub fn is_journal(&self) -> bool {
+        self.component_type.is_journal()
+    }
+
+    pub fn dtstart(&self) -> Option<&PartialDateTime> {
+        self.property(&ICalendarProperty::Dtstart)
+            .and_then(|e| e.values.first())
+            .and_then(|v| v.as_partial_date_time())
+    }
+
+    pub fn dtend_or_duration(&self) -> Option<EventEnd<'_>> {
+        self.entries
+            .iter()
+            .find_map(|entry| match (&entry.name, entry.values.first()) {
+                (ICalendarProperty::Dtend, Some(ICalendarValue::PartialDateTime(dt))) => {
+                    Some(EventEnd::Dtend(dt))
+                }
+                (ICalendarProperty::Duration, Some(ICalendarValue::Duration(duration))) => {
+                    Some(EventEnd::Duration(duration))
+                }
+                _ => None,
+            })
+    }
+
+    pub fn rrule(&self) -> Option<&ICalendarRecurrenceRule> {
+        self.entries
+            .iter()
+            .find_map(|entry| match (&entry.name, entry.values.first()) {
+                (ICalendarProperty::Rrule, Some(ICalendarValue::RecurrenceRule(rrule))) => {
+                    Some(rrule.as_ref())
+                }
+                _ => None,
+            })
+    }
+
+    pub fn geo(&self) -> Option<(f64, f64)> {
+        match self.property(&ICalendarProperty::Geo)?.values.as_slice() {
+            [
+                ICalendarValue::Float(latitude),
+                ICalendarValue::Float(longitude),
+                ..,
+            ] => Some((*latitude, *longitude)),
+            _ => None,
+        }
+    }
+
     pub fn property(&self, prop: &ICalendarProperty) -> Option<&ICalendarEntry> {
         self.entries.iter().find(|entry| &entry.name == prop)
     }
@@ -73,6 +254,13 @@ impl ICalendarComponent {
         self.entries.iter_mut().find(|entry| &entry.name == prop)
     }
 
+    pub fn preferred(&self, prop: &ICalendarProperty) -> Option<&ICalendarEntry> {
+        self.entries
+            .iter()
+            .filter(|entry| &entry.name == prop)
+            .min_by_key(|entry| entry.order().unwrap_or(u32::MAX))
+    }
+
     pub fn has_property(&self, prop: &ICalendarProperty) -> bool {
         self.entries.iter().any(|entry| &entry.name == prop)
     }
@@ -97,6 +285,248 @@ impl ICalendarComponent {
         self.entries.iter().map(|entry| entry.size()).sum()
     }
 
+    pub fn content_eq(&self, other: &ICalendarComponent) -> bool {
+        self.content_eq_ignoring(other, &[ICalendarProperty::Dtstamp])
+    }
+
+    pub fn content_eq_ignoring(
+        &self,
+        other: &ICalendarComponent,
+        ignore: &[ICalendarProperty],
+    ) -> bool {
+        self.component_type == other.component_type
+            && self
+                .entries
+                .iter()
+                .filter(|entry| !ignore.contains(&entry.name))
+                .eq(other
+                    .entries
+                    .iter()
+                    .filter(|entry| !ignore.contains(&entry.name)))
+    }
+
+    pub fn contacts(&self) -> Vec<ICalendarContact<'_>> {
+        self.properties(&ICalendarProperty::Contact)
+            .filter_map(|entry| {
+                entry
+                    .values
+                    .first()
+                    .and_then(|v| v.as_text())
+                    .map(|text| ICalendarContact {
+                        text,
+                        altrep: entry
+                            .parameter(&ICalendarParameterName::Altrep)
+                            .and_then(|v| match v {
+                                ICalendarParameterValue::Uri(Uri::Location(uri)) => {
+                                    Some(uri.as_str())
+                                }
+                                _ => v.as_text(),
+                            }),
+                        language: entry
+                            .parameter(&ICalendarParameterName::Language)
+                            .and_then(|v| v.as_text()),
+                    })
+            })
+            .collect()
+    }
+
+    pub fn repeat_count(&self) -> Option<u32> {
+        if self.component_type != ICalendarComponentType::VAlarm {
+            return None;
+        }
+        self.property(&ICalendarProperty::Repeat)
+            .and_then(|entry| entry.values.first())
+            .and_then(|v| v.as_integer())
+            .and_then(|v| u32::try_from(v).ok())
+    }
+
+    pub fn repeat_interval(&self) -> Option<&ICalendarDuration> {
+        if self.component_type != ICalendarComponentType::VAlarm {
+            return None;
+        }
+        self.property(&ICalendarProperty::Duration)
+            .and_then(|entry| entry.values.first())
+            .and_then(|v| v.as_duration())
+    }
+
+    pub fn conferences(&self) -> impl Iterator<Item = Conference<'_>> {
+        self.entries
+            .iter()
+            .filter(|entry| entry.name == ICalendarProperty::Conference)
+            .filter_map(|entry| {
+                entry
+                    .values
+                    .first()
+                    .and_then(|v| v.as_text())
+                    .map(|uri| Conference {
+                        uri,
+                        features: entry
+                            .parameters(&ICalendarParameterName::Feature)
+                            .filter_map(|v| v.as_feature())
+                            .collect(),
+                        label: entry
+                            .parameter(&ICalendarParameterName::Label)
+                            .and_then(|v| v.as_text()),
+                    })
+            })
+    }
+
+    pub fn attendees(&self) -> impl Iterator<Item = Attendee<'_>> {
+        self.entries
+            .iter()
+            .filter(|entry| entry.name == ICalendarProperty::Attendee)
+            .filter_map(|entry| {
+                let mut attendee = Attendee {
+                    address: entry.calendar_address()?,
+                    name: None,
+                    participation_status: None,
+                    role: None,
+                    rsvp: false,
+                    kind: None,
+                    entry,
+                };
+
+                for param in &entry.params {
+                    match (&param.name, &param.value) {
+                        (ICalendarParameterName::Cn, value) => {
+                            attendee.name = value.as_text();
+                        }
+                        (
+                            ICalendarParameterName::Partstat,
+                            ICalendarParameterValue::Partstat(v),
+                        ) => {
+                            attendee.participation_status = Some(v);
+                        }
+                        (ICalendarParameterName::Role, ICalendarParameterValue::Role(v)) => {
+                            attendee.role = Some(v);
+                        }
+                        (ICalendarParameterName::Rsvp, ICalendarParameterValue::Bool(v)) => {
+                            attendee.rsvp = *v;
+                        }
+                        (ICalendarParameterName::Cutype, ICalendarParameterValue::Cutype(v)) => {
+                            attendee.kind = Some(v);
+                        }
+                        _ => (),
+                    }
+                }
+
+                Some(attendee)
+            })
+    }
+
+    pub fn organizer(&self) -> Option<Organizer<'_>> {
+        let entry = self.property(&ICalendarProperty::Organizer)?;
+        Some(Organizer {
+            address: entry.calendar_address()?,
+            name: entry
+                .parameter(&ICalendarParameterName::Cn)
+                .and_then(|v| v.as_text()),
+            entry,
+        })
+    }
+
+    pub fn unified_participants<'x>(
+        &'x self,
+        calendar: &'x ICalendar,
+    ) -> Vec<UnifiedParticipant<'x>> {
+        let mut participants: Vec<UnifiedParticipant<'x>> = self
+            .properties(&ICalendarProperty::Attendee)
+            .map(|entry| {
+                let mut participant = UnifiedParticipant {
+                    calendar_address: entry.calendar_address(),
+                    name: None,
+                    kind: None,
+                    role: None,
+                    participation_status: None,
+                    participant_types: vec![],
+                    links: vec![],
+                    attendee: Some(entry),
+                    participant: None,
+                };
+
+                for param in &entry.params {
+                    match (&param.name, &param.value) {
+                        (ICalendarParameterName::Cn, value) => {
+                            participant.name = value.as_text();
+                        }
+                        (ICalendarParameterName::Cutype, ICalendarParameterValue::Cutype(v)) => {
+                            participant.kind = Some(v);
+                        }
+                        (ICalendarParameterName::Role, ICalendarParameterValue::Role(v)) => {
+                            participant.role = Some(v);
+                        }
+                        (
+                            ICalendarParameterName::Partstat,
+                            ICalendarParameterValue::Partstat(v),
+                        ) => {
+                            participant.participation_status = Some(v);
+                        }
+                        _ => (),
+                    }
+                }
+
+                participant
+            })
+            .collect();
+
+        for component in self
+            .component_ids
+            .iter()
+            .filter_map(|id| calendar.component_by_id(*id))
+            .filter(|component| component.component_type.is_participant())
+        {
+            let calendar_address = component
+                .property(&ICalendarProperty::CalendarAddress)
+                .and_then(|entry| entry.calendar_address());
+            let participant = if let Some(participant) = calendar_address.and_then(|address| {
+                participants.iter_mut().find(|p| {
+                    p.participant.is_none()
+                        && p.calendar_address
+                            .is_some_and(|a| a.eq_ignore_ascii_case(address))
+                })
+            }) {
+                participant
+            } else {
+                participants.push(UnifiedParticipant {
+                    calendar_address,
+                    name: None,
+                    kind: None,
+                    role: None,
+                    participation_status: None,
+                    participant_types: vec![],
+                    links: vec![],
+                    attendee: None,
+                    participant: None,
+                });
+                participants.last_mut().unwrap()
+            };
+
+            participant.participant = Some(component);
+
+            for entry in &component.entries {
+                match (&entry.name, entry.values.first()) {
+                    (
+                        ICalendarProperty::ParticipantType,
+                        Some(ICalendarValue::ParticipantType(v)),
+                    ) => {
+                        participant.participant_types.push(v);
+                    }
+                    (ICalendarProperty::Url, Some(value)) => {
+                        if let Some(url) = value.as_text() {
+                            participant.links.push(url);
+                        }
+                    }
+                    (ICalendarProperty::Summary, Some(value)) if participant.name.is_none() => {
+                        participant.name = value.as_text();
+                    }
+                    _ => (),
+                }
+            }
+        }
+
+        participants
+    }
+
     pub fn is_recurrent(&self) -> bool {
         self.entries.iter().any(|entry| {
             matches!(
@@ -123,6 +553,14 @@ impl ICalendarComponent {
         })
     }
 
+    pub fn sequence(&self) -> u32 {
+        // An absent SEQUENCE is equivalent to 0
+        self.property(&ICalendarProperty::Sequence)
+            .and_then(|entry| entry.values.first()?.as_integer())
+            .and_then(|sequence| u32::try_from(sequence).ok())
+            .unwrap_or(0)
+    }
+
     pub fn status(&self) -> Option<&ICalendarStatus> {
         self.entries
             .iter()
@@ -233,6 +671,48 @@ impl ICalendarValue {
         }
     }
 
+    #[inline]
+    pub fn as_date_time(&self) -> Option<&PartialDateTime> {
+        self.as_partial_date_time()
+    }
+
+    pub fn as_utc_offset(&self) -> Option<UtcOffset> {
+        match self {
+            ICalendarValue::PartialDateTime(dt) if !dt.has_date() && !dt.has_time() => {
+                dt.utc_offset()
+            }
+            _ => None,
+        }
+    }
+
+    pub fn as_duration(&self) -> Option<&ICalendarDuration> {
+        match self {
+            ICalendarValue::Duration(d) => Some(d),
+            _ => None,
+        }
+    }
+
+    pub fn as_period(&self) -> Option<&ICalendarPeriod> {
+        match self {
+            ICalendarValue::Period(p) => Some(p),
+            _ => None,
+        }
+    }
+
+    pub fn as_recurrence_rule(&self) -> Option<&ICalendarRecurrenceRule> {
+        match self {
+            ICalendarValue::RecurrenceRule(r) => Some(r),
+            _ => None,
+        }
+    }
+
+    pub fn as_uri(&self) -> Option<&Uri> {
+        match self {
+            ICalendarValue::Uri(u) => Some(u),
+            _ => None,
+        }
+    }
+
     pub fn as_binary(&self) -> Option<&[u8]> {
         match self {
             ICalendarValue::Binary(d) => Some(d.as_slice()),
@@ -272,6 +752,13 @@ impl ICalendarEntry {
         self.params.iter().any(|param| &param.name == prop)
     }
 
+    // RFC 9073 ORDER plays the role of the vCard PREF parameter
+    pub fn order(&self) -> Option<u32> {
+        self.parameter(&ICalendarParameterName::Order)
+            .and_then(|v| v.as_integer())
+            .and_then(|v| u32::try_from(v).ok())
+    }
+
     pub fn jsid(&self) -> Option<&str> {
         self.parameter(&ICalendarParameterName::Jsid)
             .and_then(|v| v.as_text())
@@ -357,6 +844,14 @@ impl ICalendarParameterValue {
         }
     }
 
+    pub fn as_feature(&self) -> Option<IanaType<&ICalendarFeatureType, &str>> {
+        match self {
+            ICalendarParameterValue::Feature(v) => Some(IanaType::Iana(v)),
+            ICalendarParameterValue::Text(v) => Some(IanaType::Other(v.as_str())),
+            _ => None,
+        }
+    }
+
     pub fn as_integer(&self) -> Option<u64> {
         match self {
             ICalendarParameterValue::Integer(v) => Some(*v),
@@ -458,36 +953,50 @@ impl Uri {
 
 impl ICalendarDuration {
     pub fn from_seconds(seconds: i64) -> Self {
-        let mut secs = seconds;
-        let neg = secs < 0;
-        if neg {
-            secs = -secs;
-        }
-        let weeks = (secs / 604800) as u32;
-        secs %= 604800;
-        let days = (secs / 86400) as u32;
-        secs %= 86400;
-        let hours = (secs / 3600) as u32;
-        secs %= 3600;
-        let minutes = (secs / 60) as u32;
-        secs %= 60;
-        let seconds = secs as u32;
+        let neg = seconds < 0;
+        let secs = seconds.unsigned_abs();
 
+        if secs != 0 && secs.is_multiple_of(604800) {
+            Self {
+                neg,
+                weeks: (secs / 604800) as u32,
+                ..Default::default()
+            }
+        } else {
+            Self::from_day_seconds(neg, secs)
+        }
+    }
+
+    fn from_day_seconds(neg: bool, secs: u64) -> Self {
         Self {
-            weeks,
-            days,
-            hours,
-            minutes,
-            seconds,
-            neg,
+            neg: neg && secs != 0,
+            weeks: 0,
+            days: (secs / 86400) as u32,
+            hours: (secs % 86400 / 3600) as u32,
+            minutes: (secs % 3600 / 60) as u32,
+            seconds: (secs % 60) as u32,
         }
     }
 
+    pub fn normalize(&self) -> Self {
+        if self.is_weeks_only() {
+            Self {
+                neg: self.neg && self.weeks != 0,
+                weeks: self.weeks,
+                ..Default::default()
+            }
+        } else {
+            // Weeks cannot be combined with other units, fold them into days
+            Self::from_day_seconds(self.neg, self.to_seconds().unsigned_abs())
+        }
+    }
+
+    #[cfg(feature = "datetime")]
     pub fn to_time_delta(&self) -> Option<chrono::TimeDelta> {
-        chrono::TimeDelta::new(self.as_seconds(), 0)
+        chrono::TimeDelta::new(self.to_seconds(), 0)
     }
 
-    pub fn as_seconds(&self) -> i64 {
+    pub fn to_seconds(&self) -> i64 {
         let secs = self.seconds as i64
             + self.minutes as i64 * 60
             + self.hours as i64 * 3600
@@ -497,6 +1006,15 @@ impl ICalendarDuration {
         if self.neg { -secs } else { secs }
     }
 
+    #[deprecated(note = "use `to_seconds` instead")]
+    pub fn as_seconds(&self) -> i64 {
+        self.to_seconds()
+    }
+
+    fn is_weeks_only(&self) -> bool {
+        self.days == 0 && self.hours == 0 && self.minutes == 0 && self.seconds == 0
+    }
+
     pub fn is_empty(&self) -> bool {
         self.weeks == 0
             && self.days == 0
@@ -505,3 +1023,749 @@ impl ICalendarDuration {
             && self.seconds == 0
     }
 }
+
+impl std::ops::Add for ICalendarDuration {
+    type Output = Self;
+
+    fn add(self, rhs: Self) -> Self {
+        let secs = self.to_seconds() + rhs.to_seconds();
+        if self.is_weeks_only() && rhs.is_weeks_only() {
+            Self::from_seconds(secs)
+        } else {
+            Self::from_day_seconds(secs < 0, secs.unsigned_abs())
+        }
+    }
+}
+
+impl std::ops::Sub for ICalendarDuration {
+    type Output = Self;
+
+    fn sub(self, rhs: Self) -> Self {
+        self + Self {
+            neg: !rhs.neg,
+            ..rhs
+        }
+    }
+}
+
+#[cfg(feature = "datetime")]
+impl PartialDateTime {
+    pub fn add_duration(&self, duration: &ICalendarDuration) -> Option<PartialDateTime> {
+        let dt = self
+            .to_date_time()?
+            .date_time
+            .checked_add_signed(duration.to_time_delta()?)?;
+        let has_time = self.has_time() || dt.time() != chrono::NaiveTime::MIN;
+
+        Some(PartialDateTime {
+            year: Some(u16::try_from(dt.year()).ok()?),
+            month: Some(dt.month() as u8),
+            day: Some(dt.day() as u8),
+            hour: has_time.then_some(dt.hour() as u8),
+            minute: has_time.then_some(dt.minute() as u8),
+            second: has_time.then_some(dt.second() as u8),
+            tz_hour: self.tz_hour,
+            tz_minute: self.tz_minute,
+            tz_minus: self.tz_minus,
+        })
+    }
+}
+
+#[cfg(feature = "datetime")]
+impl ICalendarPeriod {
+    pub fn start(&self) -> &PartialDateTime {
+        match self {
+            ICalendarPeriod::Range { start, .. } | ICalendarPeriod::Duration { start, .. } => start,
+        }
+    }
+
+    pub fn end(&self) -> PartialDateTime {
+        match self {
+            ICalendarPeriod::Range { end, .. } => end.clone(),
+            ICalendarPeriod::Duration { start, duration } => start
+                .add_duration(duration)
+                .unwrap_or_else(|| start.clone()),
+        }
+    }
+
+    pub fn duration(&self) -> ICalendarDuration {
+        match self {
+            ICalendarPeriod::Range { start, end } => start
+                .to_date_time()
+                .and_then(|start| start.to_date_time_with_tz(Tz::Floating))
+                .zip(
+                    end.to_date_time()
+                        .and_then(|end| end.to_date_time_with_tz(Tz::Floating)),
+                )
+                .map(|(start, end)| {
+                    ICalendarDuration::from_seconds(end.timestamp() - start.timestamp())
+                })
+                .unwrap_or_default(),
+            ICalendarPeriod::Duration { duration, .. } => duration.clone(),
+        }
+    }
+
+    pub fn contains(
+        &self,
+        dt: &PartialDateTime,
+        resolver: &TzResolver<impl Borrow<str> + Hash + Eq>,
+    ) -> bool {
+        let timestamp = |dt: &PartialDateTime| {
+            dt.to_date_time_with(None, resolver)
+                .map(|dt| dt.timestamp())
+        };
+
+        matches!(
+            (timestamp(self.start()), timestamp(&self.end()), timestamp(dt)),
+            (Some(start), Some(end), Some(dt)) if start <= dt && dt < end
+        )
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use crate::common::{IanaType, UtcOffset};
+    use crate::icalendar::{
+        ICalendar, ICalendarComponent, ICalendarComponentType, ICalendarDuration, ICalendarEntry,
+        ICalendarFeatureType, ICalendarFrequency, ICalendarParticipantType,
+        ICalendarParticipationRole, ICalendarParticipationStatus, ICalendarProperty,
+        ICalendarUserTypes,
+        utils::{Conference, EventEnd, ICalendarContact, sort_by_order},
+    };
+
+    #[test]
+    fn event_accessors() {
+        let ical = ICalendar::parse(concat!(
+            "BEGIN:VCALENDAR\r\n",
+            "BEGIN:VEVENT\r\n",
+            "UID:event-1\r\n",
+            "SUMMARY:Standup\r\n",
+            "DTSTART:20250110T090000Z\r\n",
+            "DTEND:20250110T091500Z\r\n",
+            "RRULE:FREQ=DAILY;COUNT=5\r\n",
+            "END:VEVENT\r\n",
+            "BEGIN:VEVENT\r\n",
+            "UID:event-2\r\n",
+            "SUMMARY:\r\n",
+            "DTSTART;VALUE=DATE:20250111\r\n",
+            "DURATION:PT1H\r\n",
+            "END:VEVENT\r\n",
+            "BEGIN:VEVENT\r\n",
+            "UID:event-3\r\n",
+            "END:VEVENT\r\n",
+            "END:VCALENDAR\r\n"
+        ))
+        .unwrap();
+        let (first, second, third) = (
+            &ical.components[1],
+            &ical.components[2],
+            &ical.components[3],
+        );
+
+        assert_eq!(first.uid(), Some("event-1"));
+        assert_eq!(first.summary(), Some("Standup"));
+        let dtstart = first.dtstart().unwrap();
+        assert_eq!((dtstart.day, dtstart.hour), (Some(10), Some(9)));
+        assert!(
+            matches!(first.dtend_or_duration(), Some(EventEnd::Dtend(dt)) if dt.minute == Some(15))
+        );
+        let rrule = first.rrule().unwrap();
+        assert_eq!(
+            (&rrule.freq, rrule.count),
+            (&ICalendarFrequency::Daily, Some(5))
+        );
+
+        assert!(
+            second
+                .property(&ICalendarProperty::Summary)
+                .unwrap()
+                .values
+                .is_empty()
+        );
+        assert_eq!(second.summary(), None);
+        assert_eq!(second.dtstart().unwrap().hour, None);
+        assert_eq!(
+            second.dtend_or_duration(),
+            Some(EventEnd::Duration(&ICalendarDuration {
+                hours: 1,
+                ..Default::default()
+            }))
+        );
+        assert_eq!(second.rrule(), None);
+
+        assert_eq!(third.summary(), None);
+        assert_eq!(third.dtstart(), None);
+        assert_eq!(third.dtend_or_duration(), None);
+    }
+
+    #[test]
+    fn component_tree() {
+        let ical = ICalendar::parse(concat!(
+            "BEGIN:VCALENDAR\r\n",
+            "BEGIN:VTIMEZONE\r\n",
+            "TZID:Europe/Madrid\r\n",
+            "BEGIN:STANDARD\r\n",
+            "DTSTART:19701025T030000\r\n",
+            "TZOFFSETFROM:+0200\r\n",
+            "TZOFFSETTO:+0100\r\n",
+            "END:STANDARD\r\n",
+            "END:VTIMEZONE\r\n",
+            "BEGIN:VEVENT\r\n",
+            "UID:event\r\n",
+            "BEGIN:VALARM\r\n",
+            "ACTION:DISPLAY\r\n",
+            "TRIGGER:-PT15M\r\n",
+            "END:VALARM\r\n",
+            "END:VEVENT\r\n",
+            "END:VCALENDAR\r\n"
+        ))
+        .unwrap();
+        let types = |components: Vec<&ICalendarComponent>| {
+            components
+                .into_iter()
+                .map(|component| component.component_type.clone())
+                .collect::<Vec<_>>()
+        };
+
+        assert_eq!(
+            types(ical.root_components().collect()),
+            [ICalendarComponentType::VCalendar]
+        );
+        assert_eq!(
+            types(ical.children(0).collect()),
+            [
+                ICalendarComponentType::VTimezone,
+                ICalendarComponentType::VEvent
+            ]
+        );
+        let event = ical
+            .components
+            .iter()
+            .position(|component| component.uid() == Some("event"))
+            .unwrap();
+        assert_eq!(
+            types(ical.children(event).collect()),
+            [ICalendarComponentType::VAlarm]
+        );
+        assert_eq!(ical.children(99).count(), 0);
+    }
+
+    #[test]
+    fn typed_component_iterators() {
+        let ical = ICalendar::parse(concat!(
+            "BEGIN:VCALENDAR\r\n",
+            "BEGIN:VTIMEZONE\r\n",
+            "TZID:Europe/Paris\r\n",
+            "END:VTIMEZONE\r\n",
+            "BEGIN:VEVENT\r\n",
+            "UID:meeting\r\n",
+            "RRULE:FREQ=WEEKLY\r\n",
+            "BEGIN:VALARM\r\n",
+            "ACTION:DISPLAY\r\n",
+            "TRIGGER:-PT15M\r\n",
+            "END:VALARM\r\n",
+            "END:VEVENT\r\n",
+            "BEGIN:VEVENT\r\n",
+            "UID:meeting\r\n",
+            "RECURRENCE-ID:20240108T100000Z\r\n",
+            "END:VEVENT\r\n",
+            "BEGIN:VTODO\r\n",
+            "UID:task\r\n",
+            "END:VTODO\r\n",
+            "BEGIN:VJOURNAL\r\n",
+            "UID:journal\r\n",
+            "END:VJOURNAL\r\n",
+            "END:VCALENDAR\r\n"
+        ))
+        .unwrap();
+        let uids = |components: Vec<&ICalendarComponent>| {
+            components
+                .into_iter()
+                .map(|component| {
+                    (
+                        component.uid().unwrap().to_string(),
+                        component.is_recurrent(),
+                    )
+                })
+                .collect::<Vec<_>>()
+        };
+
+        assert_eq!(
+            uids(ical.events().collect()),
+            [
+                ("meeting".to_string(), true),
+                ("meeting".to_string(), false)
+            ]
+        );
+        assert_eq!(uids(ical.todos().collect()), [("task".to_string(), false)]);
+        assert_eq!(
+            uids(ical.journals().collect()),
+            [("journal".to_string(), false)]
+        );
+        assert_eq!(ICalendar::default().events().count(), 0);
+    }
+
+    #[test]
+    fn journal_accessors() {
+        let ical = ICalendar::parse(concat!(
+            "BEGIN:VCALENDAR\r\n",
+            "BEGIN:VEVENT\r\n",
+            "UID:event\r\n",
+            "END:VEVENT\r\n",
+            "BEGIN:VJOURNAL\r\n",
+            "UID:journal\r\n",
+            "SUMMARY:Minutes\r\n",
+            "DESCRIPTION:Agreed on the plan.\r\n",
+            "DESCRIPTION:Follow up next week.\r\n",
+            "END:VJOURNAL\r\n",
+            "END:VCALENDAR\r\n"
+        ))
+        .unwrap();
+
+        let journals = ical.journals().collect::<Vec<_>>();
+        assert_eq!(journals.len(), 1);
+        let journal = journals[0];
+        assert!(journal.is_journal());
+        assert!(!ical.components[1].is_journal());
+        assert_eq!(journal.uid(), Some("journal"));
+        assert_eq!(journal.summary(), Some("Minutes"));
+        assert_eq!(journal.description(), Some("Agreed on the plan."));
+        assert_eq!(
+            journal.descriptions().collect::<Vec<_>>(),
+            ["Agreed on the plan.", "Follow up next week."]
+        );
+    }
+
+    #[test]
+    fn content_eq_ignores_dtstamp() {
+        let a = ICalendar::parse(
+            "BEGIN:VCALENDAR\r\nBEGIN:VEVENT\r\nUID:1\r\nDTSTAMP:20240101T000000Z\r\nSEQUENCE:1\r\nSUMMARY:Meeting\r\nEND:VEVENT\r\nEND:VCALENDAR\r\n",
+        )
+        .unwrap();
+        let b = ICalendar::parse(
+            "BEGIN:VCALENDAR\r\nBEGIN:VEVENT\r\nUID:1\r\nDTSTAMP:20250601T120000Z\r\nSEQUENCE:2\r\nSUMMARY:Meeting\r\nEND:VEVENT\r\nEND:VCALENDAR\r\n",
+        )
+        .unwrap();
+        let (a, b) = (&a.components[1], &b.components[1]);
+
+        assert!(!a.content_eq(b));
+        assert!(a.content_eq_ignoring(
+            b,
+            &[ICalendarProperty::Dtstamp, ICalendarProperty::Sequence]
+        ));
+        assert!(!a.content_eq_ignoring(b, &[ICalendarProperty::Sequence]));
+    }
+
+    #[test]
+    fn attendees_and_organizer() {
+        let ical = ICalendar::parse(concat!(
+            "BEGIN:VCALENDAR\r\n",
+            "BEGIN:VEVENT\r\n",
+            "UID:1\r\n",
+            "ORGANIZER;CN=\"Jane Doe\":mailto:jane@example.com\r\n",
+            "ATTENDEE;CN=John;PARTSTAT=ACCEPTED;ROLE=CHAIR;RSVP=TRUE:mailto:john@example.com\r\n",
+            "ATTENDEE;CUTYPE=ROOM:MAILTO:room@example.com\r\n",
+            "END:VEVENT\r\n",
+            "END:VCALENDAR\r\n"
+        ))
+        .unwrap();
+        let event = &ical.components[1];
+
+        let organizer = event.organizer().unwrap();
+        assert_eq!(organizer.address, "jane@example.com");
+        assert_eq!(organizer.name, Some("Jane Doe"));
+
+        let attendees = event.attendees().collect::<Vec<_>>();
+        assert_eq!(attendees.len(), 2);
+        assert_eq!(attendees[0].address, "john@example.com");
+        assert_eq!(attendees[0].name, Some("John"));
+        assert_eq!(
+            attendees[0].participation_status,
+            Some(&ICalendarParticipationStatus::Accepted)
+        );
+        assert_eq!(attendees[0].role, Some(&ICalendarParticipationRole::Chair));
+        assert!(attendees[0].rsvp);
+        assert_eq!(attendees[0].kind, None);
+        assert_eq!(attendees[1].address, "room@example.com");
+        assert_eq!(attendees[1].name, None);
+        assert!(!attendees[1].rsvp);
+        assert_eq!(attendees[1].kind, Some(&ICalendarUserTypes::Room));
+        assert_eq!(
+            attendees[1].entry,
+            event
+                .properties(&ICalendarProperty::Attendee)
+                .nth(1)
+                .unwrap()
+        );
+    }
+
+    #[test]
+    fn conferences() {
+        let ical = ICalendar::parse(concat!(
+            "BEGIN:VCALENDAR\r\n",
+            "BEGIN:VEVENT\r\n",
+            "UID:1\r\n",
+            "CONFERENCE;VALUE=URI;FEATURE=AUDIO,VIDEO;LABEL=\"Web video chat\":https://chat.example.com/?id=1\r\n",
+            "CONFERENCE;VALUE=URI;FEATURE=PHONE,X-CUSTOM:tel:+1-555-0100\r\n",
+            "CONFERENCE;VALUE=URI:xmpp:room@chat.example.com\r\n",
+            "END:VEVENT\r\n",
+            "END:VCALENDAR\r\n"
+        ))
+        .unwrap();
+        let expected = vec![
+            Conference {
+                uri: "https://chat.example.com/?id=1",
+                features: vec![
+                    IanaType::Iana(&ICalendarFeatureType::Audio),
+                    IanaType::Iana(&ICalendarFeatureType::Video),
+                ],
+                label: Some("Web video chat"),
+            },
+            Conference {
+                uri: "tel:+1-555-0100",
+                features: vec![
+                    IanaType::Iana(&ICalendarFeatureType::Phone),
+                    IanaType::Other("X-CUSTOM"),
+                ],
+                label: None,
+            },
+            Conference {
+                uri: "xmpp:room@chat.example.com",
+                features: vec![],
+                label: None,
+            },
+        ];
+        assert_eq!(
+            ical.components[1].conferences().collect::<Vec<_>>(),
+            expected
+        );
+
+        let ical = ICalendar::parse(ical.to_string()).unwrap();
+        assert_eq!(
+            ical.components[1].conferences().collect::<Vec<_>>(),
+            expected
+        );
+    }
+
+    #[test]
+    fn contacts_with_altrep() {
+        let ical = ICalendar::parse(
+            "BEGIN:VCALENDAR\r\nBEGIN:VEVENT\r\nUID:1\r\nCONTACT;ALTREP=\"http://example.com/pdi/jdoe.vcf\":Jim Dolittle\\, ABC Industries\\, +1-919-555-1234\r\nCONTACT;LANGUAGE=en:Jane Doe\r\nEND:VEVENT\r\nEND:VCALENDAR\r\n",
+        )
+        .unwrap();
+
+        assert_eq!(
+            ical.components[1].contacts(),
+            vec![
+                ICalendarContact {
+                    text: "Jim Dolittle, ABC Industries, +1-919-555-1234",
+                    altrep: Some("http://example.com/pdi/jdoe.vcf"),
+                    language: None,
+                },
+                ICalendarContact {
+                    text: "Jane Doe",
+                    altrep: None,
+                    language: Some("en"),
+                }
+            ]
+        );
+    }
+
+    #[test]
+    fn unified_participants() {
+        let ical = ICalendar::parse(
+            "BEGIN:VCALENDAR\r\nBEGIN:VEVENT\r\nUID:1\r\nATTENDEE;CN=Jane Doe;PARTSTAT=ACCEPTED:mailto:jane@example.com\r\nATTENDEE;PARTSTAT=DECLINED:mailto:bob@example.com\r\nBEGIN:PARTICIPANT\r\nUID:p1\r\nPARTICIPANT-TYPE:SPEAKER\r\nCALENDAR-ADDRESS:mailto:JANE@example.com\r\nURL:https://example.com/jane\r\nEND:PARTICIPANT\r\nBEGIN:PARTICIPANT\r\nUID:p2\r\nPARTICIPANT-TYPE:SPONSOR\r\nSUMMARY:ACME Corp\r\nEND:PARTICIPANT\r\nEND:VEVENT\r\nEND:VCALENDAR\r\n",
+        )
+        .unwrap();
+
+        let participants = ical.components[1].unified_participants(&ical);
+        assert_eq!(participants.len(), 3);
+
+        assert_eq!(participants[0].calendar_address, Some("jane@example.com"));
+        assert_eq!(participants[0].name, Some("Jane Doe"));
+        assert_eq!(
+            participants[0].participation_status,
+            Some(&ICalendarParticipationStatus::Accepted)
+        );
+        assert_eq!(
+            participants[0].participant_types,
+            vec![&ICalendarParticipantType::Speaker]
+        );
+        assert_eq!(participants[0].links, vec!["https://example.com/jane"]);
+        assert!(participants[0].participant.is_some());
+
+        assert_eq!(participants[1].calendar_address, Some("bob@example.com"));
+        assert!(participants[1].participant.is_none());
+
+        assert_eq!(participants[2].calendar_address, None);
+        assert_eq!(participants[2].name, Some("ACME Corp"));
+        assert!(participants[2].attendee.is_none());
+    }
+
+    #[test]
+    fn duration_arithmetic() {
+        use crate::common::IanaParse;
+
+        let duration = |value: &str| ICalendarDuration::parse(value.as_bytes()).unwrap();
+
+        assert_eq!(ICalendarDuration::from_seconds(1209600), duration("P2W"));
+        assert_eq!(
+            ICalendarDuration::from_seconds(-694800),
+            duration("-P8DT1H")
+        );
+        assert_eq!(
+            ICalendarDuration::from_seconds(0),
+            ICalendarDuration::default()
+        );
+        assert_eq!(duration("-P1DT2H").to_seconds(), -93600);
+        assert_eq!(duration("P1W").to_seconds(), 604800);
+
+        assert_eq!(
+            ICalendarDuration {
+                hours: 25,
+                minutes: 59,
+                seconds: 61,
+                ..Default::default()
+            }
+            .normalize(),
+            duration("P1DT2H0M1S")
+        );
+        assert_eq!(
+            ICalendarDuration {
+                weeks: 1,
+                days: 1,
+                ..Default::default()
+            }
+            .normalize(),
+            duration("P8D")
+        );
+        assert_eq!(duration("P3W").normalize(), duration("P3W"));
+
+        assert_eq!(duration("P1W") + duration("P2W"), duration("P3W"));
+        assert_eq!(duration("P3D") + duration("P4D"), duration("P7D"));
+        assert_eq!(duration("P1W") + duration("PT1H"), duration("P7DT1H"));
+        assert_eq!(duration("PT1H") - duration("PT90M"), duration("-PT30M"));
+        assert_eq!(
+            duration("P1D") - duration("P1D"),
+            ICalendarDuration::default()
+        );
+    }
+
+    #[test]
+    fn preferred_entries() {
+        let ical = ICalendar::parse(concat!(
+            "BEGIN:VCALENDAR\r\n",
+            "BEGIN:VEVENT\r\n",
+            "UID:event\r\n",
+            "LOCATION:Room 1\r\n",
+            "LOCATION;ORDER=2:Room 2\r\n",
+            "LOCATION;ORDER=1:Room 3\r\n",
+            "COMMENT:First\r\n",
+            "COMMENT:Second\r\n",
+            "END:VEVENT\r\n",
+            "END:VCALENDAR\r\n"
+        ))
+        .unwrap();
+        let event = &ical.components[1];
+        let value = |entry: &ICalendarEntry| entry.values[0].as_text().unwrap().to_string();
+
+        assert_eq!(
+            event.preferred(&ICalendarProperty::Location).map(value),
+            Some("Room 3".to_string())
+        );
+        assert_eq!(
+            event.preferred(&ICalendarProperty::Comment).map(value),
+            Some("First".to_string())
+        );
+
+        let mut entries = event
+            .properties(&ICalendarProperty::Location)
+            .collect::<Vec<_>>();
+        sort_by_order(&mut entries);
+        assert_eq!(
+            entries.into_iter().map(value).collect::<Vec<_>>(),
+            ["Room 3", "Room 2", "Room 1"]
+        );
+    }
+
+    #[test]
+    fn value_accessors() {
+        let ical = ICalendar::parse(concat!(
+            "BEGIN:VCALENDAR\r\n",
+            "BEGIN:VEVENT\r\n",
+            "UID:event\r\n",
+            "DTSTART:20240115T100000Z\r\n",
+            "DURATION:PT1H\r\n",
+            "RRULE:FREQ=WEEKLY;COUNT=4\r\n",
+            "RDATE;VALUE=PERIOD:20240120T100000Z/PT2H\r\n",
+            "URL:https://example.com/event\r\n",
+            "ATTACH;ENCODING=BASE64;VALUE=BINARY://4=\r\n",
+            "PRIORITY:5\r\n",
+            "END:VEVENT\r\n",
+            "END:VCALENDAR\r\n"
+        ))
+        .unwrap();
+        let event = &ical.components[1];
+        let value = |prop: ICalendarProperty| &event.property(&prop).unwrap().values[0];
+
+        assert_eq!(
+            value(ICalendarProperty::Dtstart)
+                .as_date_time()
+                .and_then(|dt| dt.year),
+            Some(2024)
+        );
+        assert_eq!(
+            value(ICalendarProperty::Duration)
+                .as_duration()
+                .map(|d| d.to_seconds()),
+            Some(3600)
+        );
+        assert_eq!(
+            value(ICalendarProperty::Rrule)
+                .as_recurrence_rule()
+                .map(|r| (&r.freq, r.count)),
+            Some((&ICalendarFrequency::Weekly, Some(4)))
+        );
+        assert!(value(ICalendarProperty::Rdate).as_period().is_some());
+        assert!(value(ICalendarProperty::Url).as_uri().is_some());
+        assert_eq!(
+            value(ICalendarProperty::Attach).as_binary(),
+            Some(&[0xff, 0xfe][..])
+        );
+        assert_eq!(value(ICalendarProperty::Priority).as_integer(), Some(5));
+        assert!(value(ICalendarProperty::Uid).as_duration().is_none());
+    }
+
+    #[test]
+    fn colors() {
+        let ical = ICalendar::parse(concat!(
+            "BEGIN:VCALENDAR\r\n",
+            "COLOR:CornflowerBlue\r\n",
+            "BEGIN:VEVENT\r\n",
+            "UID:named\r\n",
+            "COLOR:turquoise\r\n",
+            "END:VEVENT\r\n",
+            "BEGIN:VEVENT\r\n",
+            "UID:hex\r\n",
+            "COLOR:#f80\r\n",
+            "END:VEVENT\r\n",
+            "BEGIN:VEVENT\r\n",
+            "UID:invalid\r\n",
+            "COLOR:not-a-color\r\n",
+            "END:VEVENT\r\n",
+            "END:VCALENDAR\r\n"
+        ))
+        .unwrap();
+        let colors = ical
+            .components
+            .iter()
+            .map(|component| (component.color(), component.color_rgb()))
+            .collect::<Vec<_>>();
+
+        assert_eq!(
+            colors,
+            [
+                (Some("CornflowerBlue"), Some((100, 149, 237))),
+                (Some("turquoise"), Some((64, 224, 208))),
+                (Some("#f80"), Some((255, 136, 0))),
+                (Some("not-a-color"), None),
+            ]
+        );
+        assert!(ical.to_string().contains("COLOR:not-a-color\r\n"));
+    }
+
+    #[test]
+    fn utc_offsets() {
+        let ical = ICalendar::parse(concat!(
+            "BEGIN:VCALENDAR\r\n",
+            "BEGIN:VTIMEZONE\r\n",
+            "TZID:Europe/Amsterdam\r\n",
+            "BEGIN:STANDARD\r\n",
+            "DTSTART:18350101T000000\r\n",
+            "TZOFFSETFROM:+001932\r\n",
+            "TZOFFSETTO:-0000\r\n",
+            "END:STANDARD\r\n",
+            "END:VTIMEZONE\r\n",
+            "END:VCALENDAR\r\n"
+        ))
+        .unwrap();
+        let standard = &ical.components[2];
+        let offset = |prop: ICalendarProperty| {
+            standard.property(&prop).unwrap().values[0]
+                .as_utc_offset()
+                .unwrap()
+        };
+
+        let from = offset(ICalendarProperty::Tzoffsetfrom);
+        assert_eq!((from.hours, from.minutes, from.seconds), (0, 19, 32));
+        assert_eq!(from.to_seconds(), 1172);
+        assert_eq!(from.to_string(), "+001932");
+        assert_eq!(offset(ICalendarProperty::Tzoffsetto), UtcOffset::default());
+        assert_eq!(UtcOffset::parse("-05:30"), UtcOffset::parse("-0530"));
+        assert_eq!(UtcOffset::parse("+0530").unwrap().to_string(), "+0530");
+        assert!(UtcOffset::parse("+2500").is_none());
+        assert!(
+            standard
+                .property(&ICalendarProperty::Dtstart)
+                .unwrap()
+                .values[0]
+                .as_utc_offset()
+                .is_none()
+        );
+        #[cfg(feature = "datetime")]
+        assert_eq!(from.as_fixed_offset(), chrono::FixedOffset::east_opt(1172));
+        assert!(
+            ical.to_string()
+                .contains("TZOFFSETFROM:+001932\r\nTZOFFSETTO:-0000\r\n")
+        );
+    }
+
+    #[cfg(feature = "datetime")]
+    #[test]
+    fn period_helpers() {
+        use crate::{common::PartialDateTime, icalendar::ICalendarValue};
+
+        let ical = ICalendar::parse(concat!(
+            "BEGIN:VCALENDAR\r\n",
+            "BEGIN:VFREEBUSY\r\n",
+            "FREEBUSY:20240110T100000Z/PT1H30M,20240110T150000Z/20240111T160000Z\r\n",
+            "END:VFREEBUSY\r\n",
+            "END:VCALENDAR\r\n"
+        ))
+        .unwrap();
+        let resolver = ical.build_tz_resolver();
+        let periods = ical.components[1]
+            .property(&ICalendarProperty::Freebusy)
+            .unwrap()
+            .values
+            .iter()
+            .filter_map(|value| match value {
+                ICalendarValue::Period(period) => Some(period),
+                _ => None,
+            })
+            .collect::<Vec<_>>();
+        let dt = |day: u8, hour: u8, minute: u8| PartialDateTime {
+            year: Some(2024),
+            month: Some(1),
+            day: Some(day),
+            hour: Some(hour),
+            minute: Some(minute),
+            second: Some(0),
+            tz_hour: Some(0),
+            tz_minute: Some(0),
+            tz_minus: false,
+        };
+
+        assert_eq!(periods[0].end(), dt(10, 11, 30));
+        assert_eq!(periods[0].duration().to_string(), "PT1H30M");
+        assert_eq!(periods[1].end(), dt(11, 16, 0));
+        assert_eq!(periods[1].duration().to_string(), "P1DT1H");
+
+        assert!(periods[0].contains(&dt(10, 10, 0), &resolver));
+        assert!(periods[0].contains(&dt(10, 11, 29), &resolver));
+        assert!(!periods[0].contains(&dt(10, 11, 30), &resolver));
+        assert!(!periods[0].contains(&dt(10, 9, 59), &resolver));
+        assert!(periods[1].contains(&dt(11, 0, 0), &resolver));
+        assert!(!periods[1].contains(&dt(11, 16, 0), &resolver));
+    }
+}
@@ -11,20 +11,33 @@ use super::{
 use crate::{
     common::{
         CalendarScale, IanaString, PartialDateTime,
-        writer::{write_bytes, write_param_value, write_text},
+        writer::{WriterOptions, write_bytes, write_io, write_param_value, write_text},
     },
     icalendar::{
-        ICalendarMonth, ICalendarParameterName, ICalendarParameterValue, ICalendarValue, Uri,
-        ValueSeparator,
+        ICalendarMonth, ICalendarParameterName, ICalendarParameterValue, ICalendarProperty,
+        ICalendarValue, Uri, ValueSeparator,
     },
 };
 use std::{
     fmt::{Display, Write},
+    io,
     slice::Iter,
 };
 
 impl ICalendar {
     pub fn write_to(&self, out: &mut impl Write) -> std::fmt::Result {
+        self.write_with_options(out, &WriterOptions::default())
+    }
+
+    pub fn write_to_io<W: io::Write>(&self, out: &mut W) -> io::Result<()> {
+        write_io(out, |out| self.write_to(out))
+    }
+
+    pub fn write_with_options(
+        &self,
+        out: &mut impl Write,
+        options: &WriterOptions,
+    ) -> std::fmt::Result {
         let mut component_iter: Iter<'_, u32> = [0].iter();
         let mut component_stack = Vec::with_capacity(4);
 
@@ -33,8 +46,28 @@ impl ICalendar {
                 let component = self.components.get(*component_id as usize).unwrap();
                 write!(out, "BEGIN:{}\r\n", component.component_type.as_str())?;
 
-                for entry in &component.entries {
-                    entry.write_to(out)?;
+                // Overridden VERSION and PRODID are written first and replace any existing ones
+                let mut overrides = Vec::new();
+                if *component_id == 0 {
+                    if options.force_version {
+                        overrides.push(
+                            ICalendarEntry::new(ICalendarProperty::Version).with_value("2.0"),
+                        );
+                    }
+                    if let Some(prodid) = &options.prodid {
+                        overrides.push(
+                            ICalendarEntry::new(ICalendarProperty::Prodid)
+                                .with_value(prodid.to_string()),
+                        );
+                    }
+                }
+                for entry in overrides.iter().chain(
+                    component
+                        .entries
+                        .iter()
+                        .filter(|entry| overrides.iter().all(|o| o.name != entry.name)),
+                ) {
+                    entry.write_with_options(out, options)?;
                 }
 
                 if !component.component_ids.is_empty() {
@@ -55,9 +88,113 @@ impl ICalendar {
     }
 }
 
+impl ICalendar {
+    pub fn canonicalize(&mut self) {
+        for component in &mut self.components {
+            let mut entries = std::mem::take(&mut component.entries)
+                .into_iter()
+                .map(|mut entry| {
+                    entry
+                        .params
+                        .sort_by(|a, b| a.name.as_str().cmp(b.name.as_str()));
+                    let mut text = String::new();
+                    let _ = entry.write_to(&mut text);
+                    (text, entry)
+                })
+                .collect::<Vec<_>>();
+            entries.sort_by(|(a_text, a), (b_text, b)| {
+                (a.name != ICalendarProperty::Uid, a.name.as_str(), a_text).cmp(&(
+                    b.name != ICalendarProperty::Uid,
+                    b.name.as_str(),
+                    b_text,
+                ))
+            });
+            component.entries = entries.into_iter().map(|(_, entry)| entry).collect();
+        }
+
+        // Sort sub-components by their serialized form, children before parents
+        let mut post_order = Vec::with_capacity(self.components.len());
+        let mut stack = vec![(0u32, false)];
+        let mut seen = vec![false; self.components.len()];
+        while let Some((id, is_visited)) = stack.pop() {
+            if is_visited {
+                post_order.push(id as usize);
+            } else if let Some(component) = self.components.get(id as usize)
+                && !std::mem::replace(&mut seen[id as usize], true)
+            {
+                stack.push((id, true));
+                stack.extend(component.component_ids.iter().map(|&id| (id, false)));
+            }
+        }
+
+        let mut texts = vec![String::new(); self.components.len()];
+        for id in post_order {
+            let component = &mut self.components[id];
+            component
+                .component_ids
+                .retain(|&child_id| seen.get(child_id as usize).copied().unwrap_or(false));
+            component
+                .component_ids
+                .sort_by(|&a, &b| texts[a as usize].cmp(&texts[b as usize]));
+
+            let mut text = format!("BEGIN:{}\r\n", component.component_type.as_str());
+            for entry in &component.entries {
+                let _ = entry.write_to(&mut text);
+            }
+            for &child_id in &component.component_ids {
+                text.push_str(&std::mem::take(&mut texts[child_id as usize]));
+            }
+            let _ = write!(text, "END:{}\r\n", component.component_type.as_str());
+            texts[id] = text;
+        }
+
+        // Renumber components in the order they are written
+        let mut ids = vec![None; self.components.len()];
+        let mut order = Vec::with_capacity(self.components.len());
+        let mut stack = vec![0u32];
+        while let Some(id) = stack.pop() {
+            if let Some(component) = self.components.get(id as usize)
+                && ids[id as usize].is_none()
+            {
+                ids[id as usize] = Some(order.len() as u32);
+                order.push(id as usize);
+                stack.extend(component.component_ids.iter().rev());
+            }
+        }
+        order.extend((0..self.components.len()).filter(|&id| ids[id].is_none()));
+        for (new_id, &id) in order.iter().enumerate() {
+            ids[id] = Some(new_id as u32);
+        }
+
+        let mut components = std::mem::take(&mut self.components)
+            .into_iter()
+            .map(Some)
+            .collect::<Vec<_>>();
+        self.components = order
+            .into_iter()
+            .filter_map(|id| components[id].take())
+            .map(|mut component| {
+                for child_id in &mut component.component_ids {
+                    *child_id = ids[*child_id as usize].unwrap_or(*child_id);
+                }
+                component
+            })
+            .collect();
+    }
+}
+
 impl ICalendarEntry {
     pub fn write_to(&self, out: &mut impl Write) -> std::fmt::Result {
+        self.write_with_options(out, &WriterOptions::default())
+    }
+
+    pub fn write_with_options(
+        &self,
+        out: &mut impl Write,
+        options: &WriterOptions,
+    ) -> std::fmt::Result {
         let mut line_len = 0;
+        let fold_width = options.max_line_len();
 
         let entry_name = self.name.as_str();
         write!(out, "{}", entry_name)?;
@@ -76,7 +213,7 @@ impl ICalendarEntry {
                 write!(out, ",")?;
                 line_len += 1;
 
-                if line_len + 1 > 75 {
+                if line_len + 1 > fold_width {
                     write!(out, "\r\n ")?;
                     line_len = 1;
                 }
@@ -85,7 +222,7 @@ impl ICalendarEntry {
                 line_len += 1;
                 let name = param.name.as_str();
                 let need_len = name.len() + 1;
-                if line_len + need_len > 75 {
+                if line_len + need_len > fold_width {
                     write!(out, "\r\n ")?;
                     line_len = 1;
                 }
@@ -100,7 +237,12 @@ impl ICalendarEntry {
 
             match &param.value {
                 ICalendarParameterValue::Text(v) => {
-                    write_param_value(out, &mut line_len, v)?;
+                    let v = if options.canonical_param_case {
+                        param.name.canonical_value(v).unwrap_or(v)
+                    } else {
+                        v
+                    };
+                    write_param_value(out, &mut line_len, fold_width, v)?;
                 }
                 ICalendarParameterValue::Integer(i) => {
                     write!(out, "{i}")?;
@@ -117,49 +259,49 @@ impl ICalendarEntry {
                 }
                 ICalendarParameterValue::Uri(uri) => {
                     write!(out, "\"")?;
-                    write_uri(out, &mut line_len, uri, false)?;
+                    write_uri(out, &mut line_len, fold_width, uri, false)?;
                     write!(out, "\"")?;
                 }
                 ICalendarParameterValue::Cutype(v) => {
-                    write_param_value(out, &mut line_len, v.as_str())?;
+                    write_param_value(out, &mut line_len, fold_width, v.as_str())?;
                 }
                 ICalendarParameterValue::Fbtype(v) => {
-                    write_param_value(out, &mut line_len, v.as_str())?;
+                    write_param_value(out, &mut line_len, fold_width, v.as_str())?;
                 }
                 ICalendarParameterValue::Partstat(v) => {
-                    write_param_value(out, &mut line_len, v.as_str())?;
+                    write_param_value(out, &mut line_len, fold_width, v.as_str())?;
                 }
                 ICalendarParameterValue::Related(v) => {
-                    write_param_value(out, &mut line_len, v.as_str())?;
+                    write_param_value(out, &mut line_len, fold_width, v.as_str())?;
                 }
                 ICalendarParameterValue::Reltype(v) => {
-                    write_param_value(out, &mut line_len, v.as_str())?;
+                    write_param_value(out, &mut line_len, fold_width, v.as_str())?;
                 }
                 ICalendarParameterValue::Role(v) => {
-                    write_param_value(out, &mut line_len, v.as_str())?;
+                    write_param_value(out, &mut line_len, fold_width, v.as_str())?;
                 }
                 ICalendarParameterValue::ScheduleAgent(v) => {
-                    write_param_value(out, &mut line_len, v.as_str())?;
+                    write_param_value(out, &mut line_len, fold_width, v.as_str())?;
                 }
                 ICalendarParameterValue::ScheduleForceSend(v) => {
-                    write_param_value(out, &mut line_len, v.as_str())?;
+                    write_param_value(out, &mut line_len, fold_width, v.as_str())?;
                 }
                 ICalendarParameterValue::Value(v) => {
                     types = Some(v);
-                    write_param_value(out, &mut line_len, v.as_str())?;
+                    write_param_value(out, &mut line_len, fold_width, v.as_str())?;
                 }
                 ICalendarParameterValue::Display(v) => {
-                    write_param_value(out, &mut line_len, v.as_str())?;
+                    write_param_value(out, &mut line_len, fold_width, v.as_str())?;
                 }
                 ICalendarParameterValue::Feature(v) => {
-                    write_param_value(out, &mut line_len, v.as_str())?;
+                    write_param_value(out, &mut line_len, fold_width, v.as_str())?;
                 }
                 ICalendarParameterValue::Duration(v) => {
                     write!(out, "{v}")?;
                     line_len += 14;
                 }
                 ICalendarParameterValue::Linkrel(v) => {
-                    write_param_value(out, &mut line_len, v.as_str())?;
+                    write_param_value(out, &mut line_len, fold_width, v.as_str())?;
                 }
                 ICalendarParameterValue::Null => {
                     last_param = None;
@@ -183,14 +325,14 @@ impl ICalendarEntry {
                 line_len += 1;
             }
 
-            if line_len + 1 > 75 {
+            if line_len + 1 > fold_width {
                 write!(out, "\r\n ")?;
                 line_len = 1;
             }
 
             let text = match value {
                 ICalendarValue::Binary(v) => {
-                    write_bytes(out, Some(&mut line_len), v)?;
+                    write_bytes(out, Some(&mut line_len), fold_width, v)?;
                     continue;
                 }
                 ICalendarValue::Boolean(v) => {
@@ -200,7 +342,7 @@ impl ICalendarEntry {
                     continue;
                 }
                 ICalendarValue::Uri(v) => {
-                    write_uri(out, &mut line_len, v, true)?;
+                    write_uri(out, &mut line_len, fold_width, v, true)?;
                     continue;
                 }
                 ICalendarValue::PartialDateTime(v) => {
@@ -234,7 +376,7 @@ impl ICalendarEntry {
                     continue;
                 }
                 ICalendarValue::Text(v) => {
-                    write_text(out, &mut line_len, v, true, true)?;
+                    write_text(out, &mut line_len, fold_width, v, true, true)?;
                     continue;
                 }
                 ICalendarValue::CalendarScale(v) => v.as_str(),
@@ -260,6 +402,7 @@ impl ICalendarEntry {
 pub(crate) fn write_uri(
     out: &mut impl Write,
     line_len: &mut usize,
+    fold_width: usize,
     value: &Uri,
     escape: bool,
 ) -> std::fmt::Result {
@@ -277,9 +420,9 @@ pub(crate) fn write_uri(
                 write!(out, "base64,")?;
             }
             *line_len += 8;
-            write_bytes(out, Some(line_len), &v.data)
+            write_bytes(out, Some(line_len), fold_width, &v.data)
         }
-        Uri::Location(v) => write_text(out, line_len, v, escape, escape),
+        Uri::Location(v) => write_text(out, line_len, fold_width, v, escape, escape),
     }
 }
 
@@ -518,18 +661,7 @@ impl PartialDateTime {
         }
 
         if matches!(fmt, ICalendarValueType::UtcOffset) {
-            if self.tz_minus {
-                write!(out, "-")?;
-            } else {
-                write!(out, "+")?;
-            }
-
-            write!(
-                out,
-                "{:02}{:02}",
-                self.tz_hour.unwrap_or_default(),
-                self.tz_minute.unwrap_or_default(),
-            )?;
+            write!(out, "{}", self.utc_offset().unwrap_or_default())?;
         }
 
         Ok(())
@@ -541,3 +673,186 @@ impl Display for ICalendar {
         self.write_to(f)
     }
 }
+
+#[cfg(test)]
+mod tests {
+    use crate::{
+        common::writer::WriterOptions,
+        icalendar::{
+            ICalendar, ICalendarEntry, ICalendarParameter, ICalendarProperty, ICalendarValue,
+            builder::EventBuilder,
+        },
+    };
+
+    #[test]
+    fn canonical_param_case() {
+        let entry = ICalendarEntry::new(ICalendarProperty::Attendee)
+            .with_param(ICalendarParameter::partstat("accepted".to_string()))
+            .with_param(ICalendarParameter::role("x-Custom".to_string()))
+            .with_value("mailto:jane@example.com");
+
+        let mut out = String::new();
+        entry.write_to(&mut out).unwrap();
+        assert_eq!(
+            out,
+            "ATTENDEE;PARTSTAT=accepted;ROLE=x-Custom:mailto:jane@example.com\r\n"
+        );
+
+        let mut out = String::new();
+        entry
+            .write_with_options(
+                &mut out,
+                &WriterOptions::default().canonical_param_case(true),
+            )
+            .unwrap();
+        assert_eq!(
+            out,
+            "ATTENDEE;PARTSTAT=ACCEPTED;ROLE=x-Custom:mailto:jane@example.com\r\n"
+        );
+
+        // Parsed IANA values are already typed and written in canonical case
+        let ical = ICalendar::parse(concat!(
+            "BEGIN:VCALENDAR\r\n",
+            "BEGIN:VEVENT\r\n",
+            "DTSTART;value=date:20250101\r\n",
+            "END:VEVENT\r\n",
+            "END:VCALENDAR\r\n"
+        ))
+        .unwrap();
+        assert!(ical.to_string().contains("DTSTART;VALUE=DATE:20250101\r\n"));
+    }
+
+    #[test]
+    fn canonicalize() {
+        let a = concat!(
+            "BEGIN:VCALENDAR\r\n",
+            "VERSION:2.0\r\n",
+            "PRODID:-//Example//EN\r\n",
+            "BEGIN:VEVENT\r\n",
+            "SUMMARY:Second\r\n",
+            "UID:b\r\n",
+            "CATEGORIES:work,travel\r\n",
+            "END:VEVENT\r\n",
+            "BEGIN:VEVENT\r\n",
+            "DTSTART;VALUE=DATE:20240101\r\n",
+            "ATTENDEE;RSVP=TRUE;CN=Jane:mailto:jane@example.com\r\n",
+            "ATTENDEE:mailto:bob@example.com\r\n",
+            "UID:a\r\n",
+            "BEGIN:VALARM\r\n",
+            "TRIGGER:-PT15M\r\n",
+            "ACTION:DISPLAY\r\n",
+            "END:VALARM\r\n",
+            "END:VEVENT\r\n",
+            "END:VCALENDAR\r\n"
+        );
+        let b = concat!(
+            "BEGIN:VCALENDAR\r\n",
+            "PRODID:-//Example//EN\r\n",
+            "VERSION:2.0\r\n",
+            "BEGIN:VEVENT\r\n",
+            "UID:a\r\n",
+            "ATTENDEE:mailto:bob@example.com\r\n",
+            "ATTENDEE;CN=Jane;RSVP=TRUE:mailto:jane@example.com\r\n",
+            "BEGIN:VALARM\r\n",
+            "ACTION:DISPLAY\r\n",
+            "TRIGGER:-PT15M\r\n",
+            "END:VALARM\r\n",
+            "DTSTART;VALUE=DATE:20240101\r\n",
+            "END:VEVENT\r\n",
+            "BEGIN:VEVENT\r\n",
+            "CATEGORIES:work,travel\r\n",
+            "UID:b\r\n",
+            "SUMMARY:Second\r\n",
+            "END:VEVENT\r\n",
+            "END:VCALENDAR\r\n"
+        );
+
+        // The default form preserves the input order
+        assert_eq!(ICalendar::parse(a).unwrap().to_string(), a);
+
+        let mut a = ICalendar::parse(a).unwrap();
+        let mut b = ICalendar::parse(b).unwrap();
+        a.canonicalize();
+        b.canonicalize();
+        assert_eq!(a, b);
+        assert_eq!(
+            a.to_string(),
+            concat!(
+                "BEGIN:VCALENDAR\r\n",
+                "PRODID:-//Example//EN\r\n",
+                "VERSION:2.0\r\n",
+                "BEGIN:VEVENT\r\n",
+                "UID:a\r\n",
+                "ATTENDEE:mailto:bob@example.com\r\n",
+                "ATTENDEE;CN=Jane;RSVP=TRUE:mailto:jane@example.com\r\n",
+                "DTSTART;VALUE=DATE:20240101\r\n",
+                "BEGIN:VALARM\r\n",
+                "ACTION:DISPLAY\r\n",
+                "TRIGGER:-PT15M\r\n",
+                "END:VALARM\r\n",
+                "END:VEVENT\r\n",
+                "BEGIN:VEVENT\r\n",
+                "UID:b\r\n",
+                "CATEGORIES:work,travel\r\n",
+                "SUMMARY:Second\r\n",
+                "END:VEVENT\r\n",
+                "END:VCALENDAR\r\n"
+            )
+        );
+    }
+
+    #[test]
+    fn escape_text() {
+        let event = EventBuilder::new()
+            .uid("lunch")
+            .summary("Lunch, then meeting\nRoom 3; bring C:\\notes")
+            .build();
+        let mut out = String::new();
+        event
+            .property(&ICalendarProperty::Summary)
+            .unwrap()
+            .write_to(&mut out)
+            .unwrap();
+        assert_eq!(
+            out,
+            "SUMMARY:Lunch\\, then meeting\\nRoom 3\\; bring C:\\\\notes\r\n"
+        );
+
+        let ical = ICalendar::parse(concat!(
+            "BEGIN:VCALENDAR\r\n",
+            "BEGIN:VEVENT\r\n",
+            "SUMMARY:Lunch\\, then meeting\\nRoom 3\r\n",
+            "END:VEVENT\r\n",
+            "END:VCALENDAR\r\n"
+        ))
+        .unwrap();
+        assert_eq!(
+            ical.components[1]
+                .property(&ICalendarProperty::Summary)
+                .unwrap()
+                .values,
+            [ICalendarValue::Text(
+                "Lunch, then meeting\nRoom 3".to_string()
+            )]
+        );
+        assert_eq!(
+            ICalendar::parse(ical.to_string()).unwrap().components[1].entries,
+            ical.components[1].entries
+        );
+
+        // Escape sequences are never split across folded lines
+        let mut out = String::new();
+        EventBuilder::new()
+            .summary(format!("{},,,,", "x".repeat(65)))
+            .build()
+            .property(&ICalendarProperty::Summary)
+            .unwrap()
+            .write_to(&mut out)
+            .unwrap();
+        assert!(out.split("\r\n").all(|line| line.len() <= 75));
+        assert_eq!(
+            out,
+            format!("SUMMARY:{}\\,\r\n \\,\\,\\,\r\n", "x".repeat(65))
+        );
+    }
+}
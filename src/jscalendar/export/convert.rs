@@ -5,7 +5,10 @@
  */
 
 use crate::{
-    common::{IanaParse, PartialDateTime, timezone::Tz},
+    common::{
+        GeoUri, IanaParse, PartialDateTime,
+        timezone::{Tz, TzTimestamp},
+    },
     icalendar::*,
     jscalendar::{
         export::{ConvertedComponent, State},
@@ -13,7 +16,7 @@ use crate::{
     },
 };
 use ahash::{AHashMap, AHashSet};
-use chrono::{TimeDelta, TimeZone};
+use chrono::{NaiveTime, TimeDelta, TimeZone};
 use jmap_tools::{JsonPointer, JsonPointerHandler, JsonPointerItem, Key, Map, Value};
 use std::str::FromStr;
 
@@ -62,6 +65,7 @@ impl ICalendar {
         let mut component_type = None;
         let mut description_content_type = None;
         let mut overrides = None;
+        let mut is_all_day = false;
 
         for (key, value) in state.entries.as_mut_vec() {
             match (key, value) {
@@ -84,6 +88,9 @@ impl ICalendar {
                 (Key::Property(JSCalendarProperty::Uid), Value::Str(text)) => {
                     uid = Some(std::mem::take(text));
                 }
+                (Key::Property(JSCalendarProperty::ShowWithoutTime), Value::Bool(true)) => {
+                    is_all_day = true;
+                }
                 (Key::Property(JSCalendarProperty::Locale), Value::Str(text)) => {
                     locale = Some(std::mem::take(text));
                 }
@@ -197,11 +204,36 @@ impl ICalendar {
             state.start = Some(start);
         }
         if let Some(dt) = state.start {
-            component.entries.push(
-                ICalendarEntry::new(ICalendarProperty::Dtstart)
-                    .import_converted(&[JSCalendarProperty::Start], &mut root_conversions)
-                    .with_date_time(dt),
-            );
+            let mut entry = ICalendarEntry::new(ICalendarProperty::Dtstart)
+                .import_converted(&[JSCalendarProperty::Start], &mut root_conversions);
+
+            // Floating all-day events start on a date
+            if is_all_day
+                && dt.timezone().is_floating()
+                && dt.naive_local().time() == NaiveTime::MIN
+                && entry.parameter(&ICalendarParameterName::Value).is_none()
+                && entry.tz_id().is_none()
+            {
+                entry = entry.with_param(ICalendarParameter::value(ICalendarValueType::Date));
+            }
+
+            let mut entry = entry.with_date_time(dt);
+            if matches!(
+                entry.parameter(&ICalendarParameterName::Value),
+                Some(ICalendarParameterValue::Value(ICalendarValueType::Date))
+            ) {
+                for value in &mut entry.values {
+                    if let ICalendarValue::PartialDateTime(dt) = value {
+                        dt.hour = None;
+                        dt.minute = None;
+                        dt.second = None;
+                        dt.tz_hour = None;
+                        dt.tz_minute = None;
+                        dt.tz_minus = false;
+                    }
+                }
+            }
+            component.entries.push(entry);
         }
 
         // Add UID
@@ -1248,7 +1280,16 @@ impl ICalendar {
                                             .from_local_datetime(&dt)
                                             .single()
                                     })
-                                    .map(|dt| PartialDateTime::from_utc_timestamp(dt.timestamp()));
+                                    .map(|dt| {
+                                        // Floating recurrences end on a floating time
+                                        if dt.timezone().is_floating() {
+                                            PartialDateTime::from_naive_timestamp(
+                                                dt.to_naive_timestamp(),
+                                            )
+                                        } else {
+                                            PartialDateTime::from_utc_timestamp(dt.timestamp())
+                                        }
+                                    });
                             }
                             (JSCalendarProperty::Count, Value::Number(value)) => {
                                 rrule.count = Some(value.cast_to_u64() as u32);
@@ -1458,9 +1499,29 @@ impl ICalendar {
                         ICalendarProperty::Dtend
                     })
                     .import_converted(&[JSCalendarProperty::Duration], &mut root_conversions);
+
+                    // A one day duration is implied for events starting on a date
+                    if entry.name == ICalendarProperty::Duration
+                        && entry.params.is_empty()
+                        && duration
+                            == (ICalendarDuration {
+                                days: 1,
+                                ..Default::default()
+                            })
+                        && component.entries.iter().any(|e| {
+                            e.name == ICalendarProperty::Dtstart
+                                && matches!(
+                                    e.parameter(&ICalendarParameterName::Value),
+                                    Some(ICalendarParameterValue::Value(ICalendarValueType::Date))
+                                )
+                        })
+                    {
+                        continue;
+                    }
+
                     if entry.name == ICalendarProperty::Dtend {
                         if let Some(end) = state.start.and_then(|start| {
-                            start.checked_add_signed(TimeDelta::seconds(duration.as_seconds()))
+                            start.checked_add_signed(TimeDelta::seconds(duration.to_seconds()))
                         }) {
                             component.entries.push(
                                 entry.with_date_time(
@@ -2049,16 +2110,13 @@ impl ICalendarComponent {
 }
 
 fn parse_geo(text: Cow<'_, str>) -> Vec<ICalendarValue> {
-    if let Some((a, b)) = text
-        .strip_prefix("geo:")
-        .and_then(|v| v.trim().split_once(','))
-        .and_then(|(a, b)| {
-            let a = a.parse::<f64>().ok()?;
-            let b = b.parse::<f64>().ok()?;
-            Some((a, b))
-        })
+    if let Some(geo) =
+        GeoUri::parse(&text).filter(|geo| geo.altitude.is_none() && geo.params.is_none())
     {
-        vec![ICalendarValue::Float(a), ICalendarValue::Float(b)]
+        vec![
+            ICalendarValue::Float(geo.latitude),
+            ICalendarValue::Float(geo.longitude),
+        ]
     } else {
         vec![ICalendarValue::Text(text.into_owned())]
     }
@@ -5,7 +5,7 @@
  */
 
 use crate::{
-    common::timezone::TzTimestamp,
+    common::{GeoUri, timezone::TzTimestamp},
     icalendar::{timezone::TzResolver, *},
     jscalendar::{
         import::{ConversionOptions, EntryState, State, params::ExtractParams},
@@ -13,6 +13,7 @@ use crate::{
     },
 };
 use jmap_tools::{JsonPointer, Key, Map, Value};
+use std::sync::Arc;
 
 impl ICalendar {
     pub fn into_jscalendar<I: JSCalendarId, B: JSCalendarId>(
@@ -23,6 +24,7 @@ impl ICalendar {
                 &self.build_owned_tz_resolver(),
                 0,
                 &ConversionOptions::default(),
+                &None,
             )
             .into_object(),
         )
@@ -33,7 +35,19 @@ impl ICalendar {
         options: ConversionOptions,
     ) -> JSCalendar<'static, I, B> {
         JSCalendar(
-            self.to_jscalendar(&self.build_owned_tz_resolver(), 0, &options)
+            self.to_jscalendar(&self.build_owned_tz_resolver(), 0, &options, &None)
+                .into_object(),
+        )
+    }
+
+    pub fn into_jscalendar_with_id_generator<I: JSCalendarId, B: JSCalendarId>(
+        mut self,
+        options: ConversionOptions,
+        id_generator: impl IdGenerator + 'static,
+    ) -> JSCalendar<'static, I, B> {
+        let id_generator: Option<Arc<dyn IdGenerator>> = Some(Arc::new(id_generator));
+        JSCalendar(
+            self.to_jscalendar(&self.build_owned_tz_resolver(), 0, &options, &id_generator)
                 .into_object(),
         )
     }
@@ -46,9 +60,11 @@ impl ICalendar {
         tz_resolver: &TzResolver<String>,
         component_id: u32,
         options: &ConversionOptions,
+        id_generator: &Option<Arc<dyn IdGenerator>>,
     ) -> State<I, B> {
         let mut state = State {
             include_ical_components: options.include_ical_components,
+            id_generator: id_generator.clone(),
             ..Default::default()
         };
 
@@ -75,7 +91,12 @@ impl ICalendar {
                     ICalendarComponentType::VCalendar,
                     ICalendarComponentType::VEvent | ICalendarComponentType::VTodo,
                 ) => {
-                    group_components.push(self.to_jscalendar(tz_resolver, component_id, options));
+                    group_components.push(self.to_jscalendar(
+                        tz_resolver,
+                        component_id,
+                        options,
+                        id_generator,
+                    ));
                 }
                 (
                     ICalendarComponentType::VEvent | ICalendarComponentType::VTodo,
@@ -91,7 +112,7 @@ impl ICalendar {
                         _ => unreachable!(),
                     });
                     let mut subcomponent_state =
-                        self.to_jscalendar(tz_resolver, component_id, options);
+                        self.to_jscalendar(tz_resolver, component_id, options, id_generator);
                     let jsid = subcomponent_state.jsid.take();
                     let subcomponent = subcomponent_state.into_object();
                     entries.insert_named(jsid, subcomponent);
@@ -150,7 +171,7 @@ impl ICalendar {
                     .get_mut_object_or_insert(JSCalendarProperty::Alerts)
                     .insert_unchecked(
                         Key::from(jsid),
-                        self.to_jscalendar(tz_resolver, component_id, options)
+                        self.to_jscalendar(tz_resolver, component_id, options, id_generator)
                             .into_object(),
                     );
             }
@@ -274,6 +295,13 @@ impl ICalendar {
             _ => 3,
         });
         let mut start_date = None;
+        let mut is_all_day = false;
+        let has_end = entries.iter().any(|entry| {
+            matches!(
+                entry.name,
+                ICalendarProperty::Dtend | ICalendarProperty::Duration
+            )
+        });
         let mut has_owner = false;
 
         for entry in entries {
@@ -546,7 +574,7 @@ impl ICalendar {
                     ICalendarComponentType::Participant,
                 ) => {
                     if state.jsid.is_none() {
-                        state.jsid = Some(uuid5(&value));
+                        state.jsid = Some(state.generate_id(&value));
                     }
                     state.entries.insert(
                         Key::Property(JSCalendarProperty::CalendarAddress),
@@ -681,7 +709,7 @@ impl ICalendar {
                     ICalendarComponentType::VLocation,
                 ) => {
                     if state.jsid.is_none() {
-                        state.jsid = Some(uuid5(&value));
+                        state.jsid = Some(state.generate_id(&value));
                     }
                     state.entries.insert(
                         Key::Property(JSCalendarProperty::Coordinates),
@@ -700,7 +728,7 @@ impl ICalendar {
 
                     state.entries.insert(
                         Key::Property(JSCalendarProperty::Coordinates),
-                        Value::Str(format!("geo:{coord1},{coord2}").into()),
+                        Value::Str(GeoUri::from_coordinates(coord1, coord2).to_string().into()),
                     );
                     entry.set_converted_to::<I>(&[JSCalendarProperty::Coordinates::<I>
                         .to_string()
@@ -728,7 +756,9 @@ impl ICalendar {
                         [
                             (
                                 Key::Property(JSCalendarProperty::Coordinates),
-                                Value::Str(format!("geo:{coord1},{coord2}").into()),
+                                Value::Str(
+                                    GeoUri::from_coordinates(coord1, coord2).to_string().into(),
+                                ),
                             ),
                             (
                                 Key::Property(JSCalendarProperty::Type),
@@ -744,7 +774,7 @@ impl ICalendar {
                     ICalendarComponentType::VLocation,
                 ) => {
                     if state.jsid.is_none() {
-                        state.jsid = Some(uuid5(&value));
+                        state.jsid = Some(state.generate_id(&value));
                     }
                     state.entries.insert(
                         Key::Property(JSCalendarProperty::Name),
@@ -799,7 +829,7 @@ impl ICalendar {
                         main_location_id = Some(location_id.to_string());
                         location_id
                     } else {
-                        main_location_id = Some(uuid5(&value));
+                        main_location_id = Some(state.generate_id(&value));
                         main_location_id.as_deref().unwrap()
                     };
 
@@ -967,6 +997,7 @@ impl ICalendar {
                                 Key::Property(JSCalendarProperty::ShowWithoutTime),
                                 Value::Bool(true),
                             );
+                            is_all_day = true;
                         }
                         entry.set_converted_to::<I>(&[JSCalendarProperty::Start::<I>
                             .to_string()
@@ -1823,6 +1854,17 @@ impl ICalendar {
             state.add_conversion_props(entry);
         }
 
+        // All-day events without an end last for one day
+        if is_all_day && !has_end && state.component_type == ICalendarComponentType::VEvent {
+            state.entries.insert(
+                Key::Property(JSCalendarProperty::Duration),
+                Value::Element(JSCalendarValue::Duration(ICalendarDuration {
+                    days: 1,
+                    ..Default::default()
+                })),
+            );
+        }
+
         if !group_objects.is_empty() {
             state.entries.insert(
                 Key::Property(JSCalendarProperty::Entries),
@@ -13,8 +13,9 @@ use crate::{
     icalendar::{
         ICalendarComponentType, ICalendarEntry, ICalendarParameterName, ICalendarProperty,
     },
-    jscalendar::{JSCalendarId, JSCalendarProperty, JSCalendarValue},
+    jscalendar::{IdGenerator, JSCalendarId, JSCalendarProperty, JSCalendarValue},
 };
+use std::sync::Arc;
 
 pub mod convert;
 pub mod params;
@@ -50,6 +51,8 @@ struct State<I: JSCalendarId, B: JSCalendarId> {
     map_component: bool,
     is_recurrence_instance: bool,
     include_ical_components: bool,
+    id_generator: Option<Arc<dyn IdGenerator>>,
+    generated_ids: AHashMap<String, String>,
 }
 
 #[derive(Debug, Default)]
@@ -20,6 +20,19 @@ use jmap_tools::{JsonPointer, JsonPointerHandler, Key, Map, Property, Value};
 use std::{borrow::Cow, collections::hash_map::Entry};
 
 impl<I: JSCalendarId, B: JSCalendarId> State<I, B> {
+    // Custom generators run once per seed, so ids derived from the same value
+    // (such as mainLocationId and its location) agree
+    pub(super) fn generate_id(&mut self, seed: &str) -> String {
+        match &self.id_generator {
+            Some(generator) => self
+                .generated_ids
+                .entry(seed.to_string())
+                .or_insert_with(|| generator.generate(seed))
+                .clone(),
+            None => uuid5(seed),
+        }
+    }
+
     pub(super) fn map_named_entry(
         &mut self,
         entry: &mut EntryState,
@@ -49,7 +62,7 @@ impl<I: JSCalendarId, B: JSCalendarId> State<I, B> {
         let mut parameters = AHashMap::new();
         let js_id = parameters
             .extract_params(&mut entry.entry, extract)
-            .unwrap_or_else(|| uuid5(value));
+            .unwrap_or_else(|| self.generate_id(value));
 
         // Set converted props
         entry.set_converted_to::<I>(&[
@@ -7,6 +7,7 @@
 pub mod export;
 pub mod import;
 pub mod parser;
+pub mod typed;
 pub mod types;
 
 use crate::{
@@ -412,6 +413,8 @@ impl ICalendarComponentType {
             ICalendarComponentType::VAlarm => Some(JSCalendarType::Alert),
             ICalendarComponentType::Participant => Some(JSCalendarType::Participant),
             ICalendarComponentType::VLocation => Some(JSCalendarType::Location),
+            // JSCalendar has no journal type, VJOURNAL components are preserved
+            // verbatim under the group's "iCalendar" components instead
             ICalendarComponentType::Standard
             | ICalendarComponentType::Daylight
             | ICalendarComponentType::VAvailability
@@ -452,6 +455,29 @@ pub(crate) fn uuid5(text: impl AsRef<[u8]>) -> String {
         .to_string()
 }
 
+pub trait IdGenerator: Send + Sync {
+    fn generate(&self, seed: &str) -> String;
+}
+
+// Derives ids from the converted value, so converting the same data yields the same ids
+#[derive(Debug, Default, Clone, Copy)]
+pub struct Uuid5IdGenerator;
+
+impl IdGenerator for Uuid5IdGenerator {
+    fn generate(&self, seed: &str) -> String {
+        uuid5(seed)
+    }
+}
+
+impl<F> IdGenerator for F
+where
+    F: Fn(&str) -> String + Send + Sync,
+{
+    fn generate(&self, seed: &str) -> String {
+        self(seed)
+    }
+}
+
 #[cfg(test)]
 impl<I: JSCalendarId, B: JSCalendarId> std::fmt::Display for JSCalendar<'_, I, B> {
     fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
@@ -469,10 +495,14 @@ impl<I: JSCalendarId> Display for JSCalendarProperty<I> {
 #[cfg(test)]
 mod tests {
     use crate::{
+        common::{IanaString, IanaType},
         icalendar::{ICalendar, ICalendarComponent, ICalendarProperty},
-        jscalendar::{JSCalendar, JSCalendarProperty, JSCalendarValue},
+        jscalendar::{
+            JSCalendar, JSCalendarProperty, JSCalendarValue, import::ConversionOptions, uuid5,
+        },
     };
-    use jmap_tools::Value;
+    use jmap_tools::{Key, Value};
+    use std::sync::atomic::{AtomicUsize, Ordering};
 
     #[derive(Debug, Default)]
     struct Test {
@@ -541,6 +571,146 @@ mod tests {
         }
     }
 
+    #[test]
+    fn custom_id_generator() {
+        let ical = ICalendar::parse(concat!(
+            "BEGIN:VCALENDAR\r\n",
+            "BEGIN:VEVENT\r\n",
+            "UID:meeting\r\n",
+            "ATTENDEE:mailto:a@example.com\r\n",
+            "ATTENDEE:mailto:b@example.com\r\n",
+            "END:VEVENT\r\n",
+            "END:VCALENDAR\r\n"
+        ))
+        .unwrap();
+        let participant_ids = |jscal: JSCalendar<'static, String, String>| {
+            let mut ids = jscal
+                .0
+                .into_object()
+                .unwrap()
+                .into_vec()
+                .into_iter()
+                .find(|(key, _)| key == &Key::Property(JSCalendarProperty::Participants))
+                .and_then(|(_, value)| value.into_object())
+                .unwrap()
+                .into_vec()
+                .into_iter()
+                .map(|(key, _)| key.to_string().to_string())
+                .collect::<Vec<_>>();
+            ids.sort();
+            ids
+        };
+        let options = ConversionOptions::default().return_first(true);
+
+        let mut expected = vec![uuid5("mailto:a@example.com"), uuid5("mailto:b@example.com")];
+        expected.sort();
+        assert_eq!(
+            participant_ids(ical.clone().into_jscalendar_with_opt(options)),
+            expected
+        );
+
+        let counter = AtomicUsize::new(0);
+        assert_eq!(
+            participant_ids(
+                ical.into_jscalendar_with_id_generator(options, move |_: &str| {
+                    format!("p{}", counter.fetch_add(1, Ordering::Relaxed))
+                })
+            ),
+            ["p0", "p1"]
+        );
+    }
+
+    #[test]
+    fn group_roundtrip() {
+        let ical = ICalendar::parse(concat!(
+            "BEGIN:VCALENDAR\r\n",
+            "NAME:Team Calendar\r\n",
+            "COLOR:teal\r\n",
+            "PRODID:-//Example//Calendar//EN\r\n",
+            "SOURCE:https://example.com/team.ics\r\n",
+            "REFRESH-INTERVAL;VALUE=DURATION:P1W\r\n",
+            "BEGIN:VEVENT\r\n",
+            "UID:event-1\r\n",
+            "END:VEVENT\r\n",
+            "BEGIN:VEVENT\r\n",
+            "UID:event-2\r\n",
+            "END:VEVENT\r\n",
+            "END:VCALENDAR\r\n"
+        ))
+        .unwrap();
+        let roundtrip = ical
+            .clone()
+            .into_jscalendar::<String, String>()
+            .into_icalendar()
+            .unwrap();
+
+        assert_eq!(roundtrip.components[0].component_ids.len(), 2);
+        for prop in [
+            ICalendarProperty::Name,
+            ICalendarProperty::Color,
+            ICalendarProperty::Prodid,
+            ICalendarProperty::Source,
+            ICalendarProperty::RefreshInterval,
+        ] {
+            assert_eq!(
+                roundtrip.components[0]
+                    .property(&prop)
+                    .map(|entry| &entry.values),
+                ical.components[0]
+                    .property(&prop)
+                    .map(|entry| &entry.values),
+                "{prop:?}"
+            );
+        }
+    }
+
+    #[test]
+    fn conference_roundtrip() {
+        let ical = ICalendar::parse(concat!(
+            "BEGIN:VCALENDAR\r\n",
+            "BEGIN:VEVENT\r\n",
+            "UID:event-1\r\n",
+            "CONFERENCE;VALUE=URI;FEATURE=AUDIO,VIDEO;LABEL=Video call:https://chat.example.com/?id=1\r\n",
+            "CONFERENCE;VALUE=URI;FEATURE=PHONE,X-CUSTOM:tel:+1-555-0100\r\n",
+            "END:VEVENT\r\n",
+            "END:VCALENDAR\r\n"
+        ))
+        .unwrap();
+        let roundtrip = ical
+            .clone()
+            .into_jscalendar::<String, String>()
+            .into_icalendar()
+            .unwrap();
+
+        let conferences = |ical: &ICalendar| {
+            let mut conferences = ical
+                .components
+                .iter()
+                .flat_map(|component| component.conferences())
+                .map(|conference| {
+                    let mut features = conference
+                        .features
+                        .iter()
+                        .map(|feature| match feature {
+                            IanaType::Iana(feature) => feature.as_str().to_string(),
+                            IanaType::Other(feature) => feature.to_string(),
+                        })
+                        .collect::<Vec<_>>();
+                    features.sort();
+                    (
+                        conference.uri.to_string(),
+                        features,
+                        conference.label.map(|label| label.to_string()),
+                    )
+                })
+                .collect::<Vec<_>>();
+            conferences.sort();
+            conferences
+        };
+        assert_eq!(conferences(&roundtrip), conferences(&ical));
+        assert_eq!(conferences(&ical).len(), 2);
+    }
+
     impl Test {
         fn run(mut self) {
             if self.expect.is_empty() {
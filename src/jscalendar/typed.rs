@@ -0,0 +1,649 @@
+/*
+ * SPDX-FileCopyrightText: 2020 Stalwart Labs LLC <hello@stalw.art>
+ *
+ * SPDX-License-Identifier: Apache-2.0 OR MIT
+ */
+
+use crate::{
+    common::CalendarScale,
+    icalendar::{
+        ICalendarDay, ICalendarDuration, ICalendarFrequency, ICalendarMonth, ICalendarSkip,
+        ICalendarWeekday,
+    },
+    jscalendar::{
+        JSCalendar, JSCalendarDateTime, JSCalendarEventStatus, JSCalendarId, JSCalendarProgress,
+        JSCalendarProperty, JSCalendarType, JSCalendarValue,
+    },
+};
+use jmap_tools::{Key, Map, Value};
+use serde::Serialize;
+
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct JSCalendarEntry<'x, I: JSCalendarId, B: JSCalendarId> {
+    pub kind: JSCalendarType,
+    pub uid: Option<String>,
+    pub title: Option<String>,
+    pub description: Option<String>,
+    pub start: Option<JSCalendarDateTime>,
+    pub time_zone: Option<String>,
+    pub duration: Option<ICalendarDuration>,
+    pub due: Option<JSCalendarDateTime>,
+    pub show_without_time: Option<bool>,
+    pub status: Option<JSCalendarEventStatus>,
+    pub progress: Option<JSCalendarProgress>,
+    pub sequence: Option<u64>,
+    pub created: Option<JSCalendarDateTime>,
+    pub updated: Option<JSCalendarDateTime>,
+    pub recurrence_rule: Option<JSCalendarRecurrenceRule<'x, I, B>>,
+    pub extra: Map<'x, JSCalendarProperty<I>, JSCalendarValue<I, B>>,
+}
+
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct JSCalendarRecurrenceRule<'x, I: JSCalendarId, B: JSCalendarId> {
+    pub frequency: ICalendarFrequency,
+    pub interval: Option<u64>,
+    pub count: Option<u64>,
+    pub until: Option<JSCalendarDateTime>,
+    pub by_day: Vec<ICalendarDay>,
+    pub by_month: Vec<ICalendarMonth>,
+    pub by_month_day: Vec<i64>,
+    pub by_year_day: Vec<i64>,
+    pub by_week_no: Vec<i64>,
+    pub by_hour: Vec<i64>,
+    pub by_minute: Vec<i64>,
+    pub by_second: Vec<i64>,
+    pub by_set_position: Vec<i64>,
+    pub first_day_of_week: Option<ICalendarWeekday>,
+    pub rscale: Option<CalendarScale>,
+    pub skip: Option<ICalendarSkip>,
+    pub extra: Map<'x, JSCalendarProperty<I>, JSCalendarValue<I, B>>,
+}
+
+impl<'x, I: JSCalendarId, B: JSCalendarId> JSCalendarEntry<'x, I, B> {
+    pub fn parse(json: &'x str) -> Result<Self, String> {
+        JSCalendar::parse(json)?
+            .try_into()
+            .map_err(|_| "Expected an Event or Task object".to_string())
+    }
+}
+
+impl<'x, I: JSCalendarId, B: JSCalendarId> TryFrom<JSCalendar<'x, I, B>>
+    for JSCalendarEntry<'x, I, B>
+{
+    type Error = JSCalendar<'x, I, B>;
+
+    fn try_from(jscal: JSCalendar<'x, I, B>) -> Result<Self, Self::Error> {
+        let obj = match jscal.0 {
+            Value::Object(obj) => obj,
+            value => return Err(JSCalendar(value)),
+        };
+        let Some(kind) = obj
+            .as_vec()
+            .iter()
+            .find_map(|(key, value)| match (key, value) {
+                (
+                    Key::Property(JSCalendarProperty::Type),
+                    Value::Element(JSCalendarValue::Type(
+                        kind @ (JSCalendarType::Event | JSCalendarType::Task),
+                    )),
+                ) => Some(*kind),
+                _ => None,
+            })
+        else {
+            return Err(JSCalendar(Value::Object(obj)));
+        };
+
+        let mut entry = JSCalendarEntry {
+            kind,
+            uid: None,
+            title: None,
+            description: None,
+            start: None,
+            time_zone: None,
+            duration: None,
+            due: None,
+            show_without_time: None,
+            status: None,
+            progress: None,
+            sequence: None,
+            created: None,
+            updated: None,
+            recurrence_rule: None,
+            extra: Map::from(vec![]),
+        };
+        let mut extra = Vec::new();
+
+        for (key, value) in obj.into_vec() {
+            match (key, value) {
+                (Key::Property(JSCalendarProperty::Type), _) => {}
+                (Key::Property(JSCalendarProperty::Uid), Value::Str(text)) => {
+                    entry.uid = Some(text.into_owned());
+                }
+                (Key::Property(JSCalendarProperty::Title), Value::Str(text)) => {
+                    entry.title = Some(text.into_owned());
+                }
+                (Key::Property(JSCalendarProperty::Description), Value::Str(text)) => {
+                    entry.description = Some(text.into_owned());
+                }
+                (Key::Property(JSCalendarProperty::TimeZone), Value::Str(text)) => {
+                    entry.time_zone = Some(text.into_owned());
+                }
+                (
+                    Key::Property(JSCalendarProperty::Start),
+                    Value::Element(JSCalendarValue::DateTime(dt)),
+                ) => {
+                    entry.start = Some(dt);
+                }
+                (
+                    Key::Property(JSCalendarProperty::Due),
+                    Value::Element(JSCalendarValue::DateTime(dt)),
+                ) => {
+                    entry.due = Some(dt);
+                }
+                (
+                    Key::Property(JSCalendarProperty::Created),
+                    Value::Element(JSCalendarValue::DateTime(dt)),
+                ) => {
+                    entry.created = Some(dt);
+                }
+                (
+                    Key::Property(JSCalendarProperty::Updated),
+                    Value::Element(JSCalendarValue::DateTime(dt)),
+                ) => {
+                    entry.updated = Some(dt);
+                }
+                (
+                    Key::Property(JSCalendarProperty::Duration),
+                    Value::Element(JSCalendarValue::Duration(duration)),
+                ) => {
+                    entry.duration = Some(duration);
+                }
+                (Key::Property(JSCalendarProperty::ShowWithoutTime), Value::Bool(value)) => {
+                    entry.show_without_time = Some(value);
+                }
+                (
+                    Key::Property(JSCalendarProperty::Status),
+                    Value::Element(JSCalendarValue::EventStatus(status)),
+                ) => {
+                    entry.status = Some(status);
+                }
+                (
+                    Key::Property(JSCalendarProperty::Progress),
+                    Value::Element(JSCalendarValue::Progress(progress)),
+                ) => {
+                    entry.progress = Some(progress);
+                }
+                (Key::Property(JSCalendarProperty::Sequence), Value::Number(value)) => {
+                    entry.sequence = Some(value.cast_to_u64());
+                }
+                (Key::Property(JSCalendarProperty::RecurrenceRule), Value::Object(obj)) => {
+                    match JSCalendarRecurrenceRule::try_from_object(obj) {
+                        Ok(rule) => entry.recurrence_rule = Some(rule),
+                        Err(obj) => extra.push((
+                            Key::Property(JSCalendarProperty::RecurrenceRule),
+                            Value::Object(obj),
+                        )),
+                    }
+                }
+                (key, value) => extra.push((key, value)),
+            }
+        }
+
+        entry.extra = Map::from(extra);
+        Ok(entry)
+    }
+}
+
+impl<'x, I: JSCalendarId, B: JSCalendarId> From<JSCalendarEntry<'x, I, B>>
+    for JSCalendar<'x, I, B>
+{
+    fn from(entry: JSCalendarEntry<'x, I, B>) -> Self {
+        let mut obj = vec![(
+            Key::Property(JSCalendarProperty::Type),
+            Value::Element(JSCalendarValue::Type(entry.kind)),
+        )];
+
+        for (prop, value) in [
+            (
+                JSCalendarProperty::Uid,
+                entry.uid.map(|v| Value::Str(v.into())),
+            ),
+            (
+                JSCalendarProperty::Title,
+                entry.title.map(|v| Value::Str(v.into())),
+            ),
+            (
+                JSCalendarProperty::Description,
+                entry.description.map(|v| Value::Str(v.into())),
+            ),
+            (
+                JSCalendarProperty::Start,
+                entry
+                    .start
+                    .map(|v| Value::Element(JSCalendarValue::DateTime(v))),
+            ),
+            (
+                JSCalendarProperty::TimeZone,
+                entry.time_zone.map(|v| Value::Str(v.into())),
+            ),
+            (
+                JSCalendarProperty::Duration,
+                entry
+                    .duration
+                    .map(|v| Value::Element(JSCalendarValue::Duration(v))),
+            ),
+            (
+                JSCalendarProperty::Due,
+                entry
+                    .due
+                    .map(|v| Value::Element(JSCalendarValue::DateTime(v))),
+            ),
+            (
+                JSCalendarProperty::ShowWithoutTime,
+                entry.show_without_time.map(Value::Bool),
+            ),
+            (
+                JSCalendarProperty::Status,
+                entry
+                    .status
+                    .map(|v| Value::Element(JSCalendarValue::EventStatus(v))),
+            ),
+            (
+                JSCalendarProperty::Progress,
+                entry
+                    .progress
+                    .map(|v| Value::Element(JSCalendarValue::Progress(v))),
+            ),
+            (
+                JSCalendarProperty::Sequence,
+                entry.sequence.map(|v| Value::Number(v.into())),
+            ),
+            (
+                JSCalendarProperty::Created,
+                entry
+                    .created
+                    .map(|v| Value::Element(JSCalendarValue::DateTime(v))),
+            ),
+            (
+                JSCalendarProperty::Updated,
+                entry
+                    .updated
+                    .map(|v| Value::Element(JSCalendarValue::DateTime(v))),
+            ),
+            (
+                JSCalendarProperty::RecurrenceRule,
+                entry.recurrence_rule.map(|rule| rule.into_value()),
+            ),
+        ] {
+            if let Some(value) = value {
+                obj.push((Key::Property(prop), value));
+            }
+        }
+
+        obj.extend(entry.extra.into_vec());
+        JSCalendar(Value::Object(Map::from(obj)))
+    }
+}
+
+impl<I: JSCalendarId, B: JSCalendarId> Serialize for JSCalendarEntry<'_, I, B> {
+    fn serialize<S: serde::Serializer>(&self, serializer: S) -> Result<S::Ok, S::Error> {
+        JSCalendar::from(self.clone()).0.serialize(serializer)
+    }
+}
+
+impl<'x, I: JSCalendarId, B: JSCalendarId> JSCalendarRecurrenceRule<'x, I, B> {
+    fn try_from_object(
+        obj: Map<'x, JSCalendarProperty<I>, JSCalendarValue<I, B>>,
+    ) -> Result<Self, Map<'x, JSCalendarProperty<I>, JSCalendarValue<I, B>>> {
+        let Some(frequency) = obj
+            .as_vec()
+            .iter()
+            .find_map(|(key, value)| match (key, value) {
+                (
+                    Key::Property(JSCalendarProperty::Frequency),
+                    Value::Element(JSCalendarValue::Frequency(frequency)),
+                ) => Some(*frequency),
+                _ => None,
+            })
+        else {
+            return Err(obj);
+        };
+
+        let mut rule = JSCalendarRecurrenceRule {
+            frequency,
+            interval: None,
+            count: None,
+            until: None,
+            by_day: vec![],
+            by_month: vec![],
+            by_month_day: vec![],
+            by_year_day: vec![],
+            by_week_no: vec![],
+            by_hour: vec![],
+            by_minute: vec![],
+            by_second: vec![],
+            by_set_position: vec![],
+            first_day_of_week: None,
+            rscale: None,
+            skip: None,
+            extra: Map::from(vec![]),
+        };
+        let mut extra = Vec::new();
+
+        // Lists that do not fully convert are kept untouched in the extra map
+        for (key, value) in obj.into_vec() {
+            match (key, value) {
+                (
+                    Key::Property(JSCalendarProperty::Frequency),
+                    Value::Element(JSCalendarValue::Frequency(_)),
+                ) => {}
+                (Key::Property(JSCalendarProperty::Interval), Value::Number(value)) => {
+                    rule.interval = Some(value.cast_to_u64());
+                }
+                (Key::Property(JSCalendarProperty::Count), Value::Number(value)) => {
+                    rule.count = Some(value.cast_to_u64());
+                }
+                (
+                    Key::Property(JSCalendarProperty::Until),
+                    Value::Element(JSCalendarValue::DateTime(dt)),
+                ) => {
+                    rule.until = Some(dt);
+                }
+                (
+                    Key::Property(JSCalendarProperty::FirstDayOfWeek),
+                    Value::Element(JSCalendarValue::Weekday(weekday)),
+                ) => {
+                    rule.first_day_of_week = Some(weekday);
+                }
+                (
+                    Key::Property(JSCalendarProperty::Rscale),
+                    Value::Element(JSCalendarValue::CalendarScale(scale)),
+                ) => {
+                    rule.rscale = Some(scale);
+                }
+                (
+                    Key::Property(JSCalendarProperty::Skip),
+                    Value::Element(JSCalendarValue::Skip(skip)),
+                ) => {
+                    rule.skip = Some(skip);
+                }
+                (Key::Property(JSCalendarProperty::ByDay), Value::Array(values))
+                    if values.iter().all(|v| nday(v).is_some()) =>
+                {
+                    rule.by_day = values.iter().filter_map(nday).collect();
+                }
+                (Key::Property(JSCalendarProperty::ByMonth), Value::Array(values))
+                    if values.iter().all(|v| month(v).is_some()) =>
+                {
+                    rule.by_month = values.iter().filter_map(month).collect();
+                }
+                (Key::Property(JSCalendarProperty::ByMonthDay), Value::Array(values))
+                    if is_number_list(&values) =>
+                {
+                    rule.by_month_day = number_list(&values);
+                }
+                (Key::Property(JSCalendarProperty::ByYearDay), Value::Array(values))
+                    if is_number_list(&values) =>
+                {
+                    rule.by_year_day = number_list(&values);
+                }
+                (Key::Property(JSCalendarProperty::ByWeekNo), Value::Array(values))
+                    if is_number_list(&values) =>
+                {
+                    rule.by_week_no = number_list(&values);
+                }
+                (Key::Property(JSCalendarProperty::ByHour), Value::Array(values))
+                    if is_number_list(&values) =>
+                {
+                    rule.by_hour = number_list(&values);
+                }
+                (Key::Property(JSCalendarProperty::ByMinute), Value::Array(values))
+                    if is_number_list(&values) =>
+                {
+                    rule.by_minute = number_list(&values);
+                }
+                (Key::Property(JSCalendarProperty::BySecond), Value::Array(values))
+                    if is_number_list(&values) =>
+                {
+                    rule.by_second = number_list(&values);
+                }
+                (Key::Property(JSCalendarProperty::BySetPosition), Value::Array(values))
+                    if is_number_list(&values) =>
+                {
+                    rule.by_set_position = number_list(&values);
+                }
+                (key, value) => extra.push((key, value)),
+            }
+        }
+
+        rule.extra = Map::from(extra);
+        Ok(rule)
+    }
+
+    fn into_value(self) -> Value<'x, JSCalendarProperty<I>, JSCalendarValue<I, B>> {
+        let mut obj = vec![(
+            Key::Property(JSCalendarProperty::Frequency),
+            Value::Element(JSCalendarValue::Frequency(self.frequency)),
+        )];
+
+        for (prop, value) in [
+            (
+                JSCalendarProperty::Interval,
+                self.interval.map(|v| Value::Number(v.into())),
+            ),
+            (
+                JSCalendarProperty::Count,
+                self.count.map(|v| Value::Number(v.into())),
+            ),
+            (
+                JSCalendarProperty::Until,
+                self.until
+                    .map(|v| Value::Element(JSCalendarValue::DateTime(v))),
+            ),
+            (
+                JSCalendarProperty::FirstDayOfWeek,
+                self.first_day_of_week
+                    .map(|v| Value::Element(JSCalendarValue::Weekday(v))),
+            ),
+            (
+                JSCalendarProperty::Rscale,
+                self.rscale
+                    .map(|v| Value::Element(JSCalendarValue::CalendarScale(v))),
+            ),
+            (
+                JSCalendarProperty::Skip,
+                self.skip.map(|v| Value::Element(JSCalendarValue::Skip(v))),
+            ),
+        ] {
+            if let Some(value) = value {
+                obj.push((Key::Property(prop), value));
+            }
+        }
+
+        for (prop, values) in [
+            (
+                JSCalendarProperty::ByDay,
+                self.by_day
+                    .into_iter()
+                    .map(|day| {
+                        Value::Object(Map::from_iter(
+                            [
+                                Some((
+                                    Key::Property(JSCalendarProperty::Day),
+                                    Value::Element(JSCalendarValue::Weekday(day.weekday)),
+                                )),
+                                day.ordwk.map(|v| {
+                                    (
+                                        Key::Property(JSCalendarProperty::NthOfPeriod),
+                                        Value::Number((v as i64).into()),
+                                    )
+                                }),
+                            ]
+                            .into_iter()
+                            .flatten(),
+                        ))
+                    })
+                    .collect::<Vec<_>>(),
+            ),
+            (
+                JSCalendarProperty::ByMonth,
+                self.by_month
+                    .into_iter()
+                    .map(|v| Value::Element(JSCalendarValue::Month(v)))
+                    .collect(),
+            ),
+            (
+                JSCalendarProperty::ByMonthDay,
+                number_values(self.by_month_day),
+            ),
+            (
+                JSCalendarProperty::ByYearDay,
+                number_values(self.by_year_day),
+            ),
+            (JSCalendarProperty::ByWeekNo, number_values(self.by_week_no)),
+            (JSCalendarProperty::ByHour, number_values(self.by_hour)),
+            (JSCalendarProperty::ByMinute, number_values(self.by_minute)),
+            (JSCalendarProperty::BySecond, number_values(self.by_second)),
+            (
+                JSCalendarProperty::BySetPosition,
+                number_values(self.by_set_position),
+            ),
+        ] {
+            if !values.is_empty() {
+                obj.push((Key::Property(prop), Value::Array(values)));
+            }
+        }
+
+        obj.extend(self.extra.into_vec());
+        Value::Object(Map::from(obj))
+    }
+}
+
+fn nday<I: JSCalendarId, B: JSCalendarId>(
+    value: &Value<'_, JSCalendarProperty<I>, JSCalendarValue<I, B>>,
+) -> Option<ICalendarDay> {
+    let mut weekday = None;
+    let mut ordwk = None;
+
+    for (key, value) in value.as_object()?.as_vec() {
+        match (key, value) {
+            (
+                Key::Property(JSCalendarProperty::Day),
+                Value::Element(JSCalendarValue::Weekday(value)),
+            ) => {
+                weekday = Some(*value);
+            }
+            (Key::Property(JSCalendarProperty::NthOfPeriod), Value::Number(value)) => {
+                ordwk = Some(i16::try_from(value.cast_to_i64()).ok()?);
+            }
+            _ => return None,
+        }
+    }
+
+    weekday.map(|weekday| ICalendarDay { ordwk, weekday })
+}
+
+fn month<I: JSCalendarId, B: JSCalendarId>(
+    value: &Value<'_, JSCalendarProperty<I>, JSCalendarValue<I, B>>,
+) -> Option<ICalendarMonth> {
+    match value {
+        Value::Element(JSCalendarValue::Month(month)) => Some(*month),
+        Value::Str(text) => ICalendarMonth::parse(text.as_bytes()),
+        _ => None,
+    }
+}
+
+fn is_number_list<I: JSCalendarId, B: JSCalendarId>(
+    values: &[Value<'_, JSCalendarProperty<I>, JSCalendarValue<I, B>>],
+) -> bool {
+    values.iter().all(|v| v.as_i64().is_some())
+}
+
+fn number_list<I: JSCalendarId, B: JSCalendarId>(
+    values: &[Value<'_, JSCalendarProperty<I>, JSCalendarValue<I, B>>],
+) -> Vec<i64> {
+    values.iter().filter_map(|v| v.as_i64()).collect()
+}
+
+fn number_values<'x, I: JSCalendarId, B: JSCalendarId>(
+    values: Vec<i64>,
+) -> Vec<Value<'x, JSCalendarProperty<I>, JSCalendarValue<I, B>>> {
+    values
+        .into_iter()
+        .map(|v| Value::Number(v.into()))
+        .collect()
+}
+
+#[cfg(test)]
+mod tests {
+    use super::JSCalendarEntry;
+    use crate::{
+        icalendar::{ICalendarDay, ICalendarDuration, ICalendarFrequency, ICalendarWeekday},
+        jscalendar::{JSCalendar, JSCalendarType},
+    };
+
+    #[test]
+    fn typed_entry() {
+        let json = r#"{
+            "@type": "Event",
+            "uid": "a8df6573-0474-496d-8496-033ad45d7fea",
+            "title": "Standup",
+            "start": "2024-01-01T09:00:00",
+            "timeZone": "Europe/Berlin",
+            "duration": "PT15M",
+            "sequence": 2,
+            "recurrenceRule": {
+                "frequency": "monthly",
+                "count": 10,
+                "byDay": [{ "day": "mo", "nthOfPeriod": 1 }, { "day": "fr" }],
+                "byHour": [9]
+            },
+            "locations": {
+                "1": { "@type": "Location", "name": "Room 1" }
+            }
+        }"#;
+
+        let entry = JSCalendarEntry::<String, String>::parse(json).unwrap();
+        assert_eq!(entry.kind, JSCalendarType::Event);
+        assert_eq!(
+            entry.uid.as_deref(),
+            Some("a8df6573-0474-496d-8496-033ad45d7fea")
+        );
+        assert_eq!(entry.title.as_deref(), Some("Standup"));
+        assert_eq!(entry.time_zone.as_deref(), Some("Europe/Berlin"));
+        assert_eq!(
+            entry.start.map(|dt| dt.to_rfc3339()).as_deref(),
+            Some("2024-01-01T09:00:00")
+        );
+        assert_eq!(entry.duration, ICalendarDuration::parse(b"PT15M"));
+        assert_eq!(entry.sequence, Some(2));
+        let rule = entry.recurrence_rule.as_ref().unwrap();
+        assert_eq!(rule.frequency, ICalendarFrequency::Monthly);
+        assert_eq!(rule.count, Some(10));
+        assert_eq!(
+            rule.by_day,
+            [
+                ICalendarDay {
+                    ordwk: Some(1),
+                    weekday: ICalendarWeekday::Monday
+                },
+                ICalendarDay {
+                    ordwk: None,
+                    weekday: ICalendarWeekday::Friday
+                }
+            ]
+        );
+        assert_eq!(rule.by_hour, [9]);
+        assert!(rule.extra.as_vec().is_empty());
+        assert_eq!(entry.extra.as_vec().len(), 1);
+
+        // Converting back yields the same document
+        assert_eq!(
+            serde_json::to_value(&entry).unwrap(),
+            serde_json::to_value(&JSCalendar::<String, String>::parse(json).unwrap().0).unwrap()
+        );
+
+        // Groups are not entries
+        assert!(JSCalendarEntry::try_from(JSCalendar::<String, String>::default()).is_err());
+    }
+}
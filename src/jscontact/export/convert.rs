@@ -1189,6 +1189,7 @@ where
                     JSContactProperty::Organizations => {
                         for (name, value) in value.into_expanded_object() {
                             let mut entry = VCardEntry::new(VCardProperty::Org);
+                            let mut sort_as = vec![None];
 
                             for (sub_property, value) in value.into_expanded_object() {
                                 match sub_property {
@@ -1220,6 +1221,12 @@ where
                                                             entry.values.push(value);
                                                         }
                                                     }
+                                                    Key::Property(JSContactProperty::SortAs) => {
+                                                        if sort_as.len() < index + 2 {
+                                                            sort_as.resize(index + 2, None);
+                                                        }
+                                                        sort_as[index + 1] = value.into_string();
+                                                    }
                                                     _ => {
                                                         state.insert_jsprop(
                                                             &[
@@ -1237,11 +1244,7 @@ where
                                         }
                                     }
                                     Key::Property(JSContactProperty::SortAs) => {
-                                        if let Some(sort_as) = value.into_string() {
-                                            entry.add_param(VCardParameter::sort_as(
-                                                sort_as.into_owned(),
-                                            ));
-                                        }
+                                        sort_as[0] = value.into_string();
                                     }
                                     Key::Property(JSContactProperty::Contexts) => {
                                         if let Some(types) = convert_types(value, true) {
@@ -1263,6 +1266,16 @@ where
                                 }
                             }
 
+                            if sort_as.iter().any(Option::is_some) {
+                                entry.add_param(VCardParameter::sort_as(
+                                    sort_as
+                                        .iter()
+                                        .map(|v| v.as_deref().unwrap_or_default())
+                                        .collect::<Vec<_>>()
+                                        .join(","),
+                                ));
+                            }
+
                             state.insert_vcard(
                                 &[JSContactProperty::Organizations],
                                 entry.with_param(VCardParameter::prop_id(name.into_string())),
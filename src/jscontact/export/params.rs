@@ -34,6 +34,7 @@ where
                 };
 
             // Try mapping X-ABLabel
+            let mut label_pos = None;
             if let Some(prop_id_) = prop_id {
                 let mut remove_pos = None;
                 for (param_pos, param) in entry.params.iter().enumerate() {
@@ -52,6 +53,7 @@ where
                                     .with_value(label.to_string()),
                             );
                             remove_pos = Some(param_pos);
+                            label_pos = Some(self.vcard.entries.len() - 1);
                         }
                         break;
                     }
@@ -122,6 +124,17 @@ where
                     break;
                 }
             }
+
+            // X-ABLabel is tied to its property only through a shared group
+            if let Some(label_pos) = label_pos {
+                let group = entry
+                    .group
+                    .clone()
+                    .or_else(|| self.vcard.entries[label_pos].group.clone())
+                    .unwrap_or_else(|| self.vcard.new_group_name());
+                self.vcard.entries[label_pos].group = Some(group.clone());
+                entry.group = Some(group);
+            }
         }
 
         if let Some(lang) = &self.language {
@@ -1025,29 +1025,54 @@ impl VCard {
                     );
                 }
                 VCardProperty::Org => {
+                    let mut sort_as = Vec::new();
+                    entry.entry.params.retain(|param| {
+                        if param.name == VCardParameterName::SortAs
+                            && let Some(value) = param.value.as_text()
+                        {
+                            sort_as.extend(value.split(',').map(|v| v.trim().to_string()));
+                            false
+                        } else {
+                            true
+                        }
+                    });
+                    let mut sort_as = sort_as.into_iter().map(|v| (!v.is_empty()).then_some(v));
+                    let org_sort_as = sort_as.next().flatten();
+
                     let units = entry
                         .text_parts_borrowed()
                         .skip(1)
                         .map(|unit| {
-                            Value::Object(Map::from(vec![(
+                            let mut unit = vec![(
                                 Key::Property(JSContactProperty::Name),
                                 Value::Str(unit.to_string().into()),
-                            )]))
+                            )];
+                            if let Some(sort_as) = sort_as.next().flatten() {
+                                unit.push((
+                                    Key::Property(JSContactProperty::SortAs),
+                                    Value::Str(sort_as.into()),
+                                ));
+                            }
+                            Value::Object(Map::from(unit))
                         })
                         .collect::<Vec<_>>();
 
                     state.map_named_entry(
                         &mut entry,
-                        &[
-                            VCardParameterName::Type,
-                            VCardParameterName::PropId,
-                            VCardParameterName::SortAs,
-                        ],
+                        &[VCardParameterName::Type, VCardParameterName::PropId],
                         JSContactProperty::Organizations,
                         JSContactProperty::Name,
-                        [(!units.is_empty()).then_some({
-                            (Key::Property(JSContactProperty::Units), Value::Array(units))
-                        })]
+                        [
+                            (!units.is_empty()).then_some({
+                                (Key::Property(JSContactProperty::Units), Value::Array(units))
+                            }),
+                            org_sort_as.map(|sort_as| {
+                                (
+                                    Key::Property(JSContactProperty::SortAs),
+                                    Value::Str(sort_as.into()),
+                                )
+                            }),
+                        ]
                         .into_iter()
                         .flatten(),
                     );
@@ -1262,6 +1287,7 @@ impl VCard {
                 | VCardProperty::Xml
                 | VCardProperty::Gender
                 | VCardProperty::Clientpidmap
+                | VCardProperty::Agent
                 | VCardProperty::Other(_) => (),
                 VCardProperty::Begin | VCardProperty::End => {
                     continue;
@@ -421,7 +421,10 @@ where
     }
 
     pub(super) fn add_conversion_props(&mut self, mut entry: EntryState) {
-        if self.include_vcard_converted {
+        // Vendor properties have no JSContact counterpart and are always preserved
+        if self.include_vcard_converted
+            || (entry.converted_to.is_none() && matches!(entry.entry.name, VCardProperty::Other(_)))
+        {
             if let Some(converted_to) = entry.converted_to.take() {
                 if entry.map_name || !entry.entry.params.is_empty() || entry.entry.group.is_some() {
                     let mut value_type = None;
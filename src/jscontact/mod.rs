@@ -17,6 +17,8 @@ use std::{
 pub mod export;
 pub mod import;
 pub mod parser;
+pub mod patch;
+pub mod typed;
 pub mod types;
 
 #[cfg(test)]
@@ -447,7 +449,7 @@ mod tests {
     use jmap_tools::{Key, Value};
 
     use crate::{
-        jscontact::{JSContact, JSContactProperty, JSContactValue},
+        jscontact::{JSContact, JSContactProperty, JSContactValue, import::ConversionOptions},
         vcard::{VCard, VCardProperty},
     };
 
@@ -460,6 +462,41 @@ mod tests {
         line_num: usize,
     }
 
+    #[test]
+    fn preserve_vendor_properties() {
+        let vcard = VCard::parse(concat!(
+            "BEGIN:VCARD\r\n",
+            "VERSION:4.0\r\n",
+            "FN:Jane Doe\r\n",
+            "item1.X-PHONETIC-FIRST-NAME;X-SOURCE=apple:Jeyn\r\n",
+            "X-TAG;X-LEVEL=1:one\r\n",
+            "X-TAG:two\r\n",
+            "END:VCARD\r\n"
+        ))
+        .unwrap();
+        let vendor_entries = |vcard: &VCard| {
+            vcard
+                .entries
+                .iter()
+                .filter(|entry| matches!(entry.name, VCardProperty::Other(_)))
+                .cloned()
+                .collect::<Vec<_>>()
+        };
+        let expected = vendor_entries(&vcard);
+        assert_eq!(expected.len(), 3);
+
+        for include_vcard_parameters in [true, false] {
+            let roundtrip = vcard
+                .clone()
+                .into_jscontact_with_options::<String, String>(
+                    ConversionOptions::default().include_vcard_parameters(include_vcard_parameters),
+                )
+                .into_vcard()
+                .unwrap();
+            assert_eq!(vendor_entries(&roundtrip), expected);
+        }
+    }
+
     #[test]
     fn convert_jscontact() {
         // Read all test files in the test directory
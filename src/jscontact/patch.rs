@@ -0,0 +1,198 @@
+/*
+ * SPDX-FileCopyrightText: 2020 Stalwart Labs LLC <hello@stalw.art>
+ *
+ * SPDX-License-Identifier: Apache-2.0 OR MIT
+ */
+
+use crate::jscontact::{JSContact, JSContactId, JSContactProperty, JSContactValue};
+use jmap_tools::{JsonPointer, JsonPointerHandler, JsonPointerItem, Value};
+
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct PatchOp<'x, I, B>
+where
+    I: JSContactId,
+    B: JSContactId,
+{
+    pub path: JsonPointer<JSContactProperty<I>>,
+    pub value: Value<'x, JSContactProperty<I>, JSContactValue<I, B>>,
+}
+
+impl<'x, I, B> JSContact<'x, I, B>
+where
+    I: JSContactId,
+    B: JSContactId,
+{
+    pub fn diff(&self, other: &JSContact<'x, I, B>) -> Vec<PatchOp<'x, I, B>> {
+        let mut ops = Vec::new();
+        diff_values(&self.0, &other.0, &mut Vec::new(), &mut ops);
+        ops
+    }
+
+    pub fn apply_patch_ops(&mut self, ops: impl IntoIterator<Item = PatchOp<'x, I, B>>) {
+        for op in ops {
+            if matches!(op.value, Value::Null) {
+                remove_path(&mut self.0, &op.path.into_inner());
+            } else {
+                self.0.patch_jptr(op.path.iter(), op.value);
+            }
+        }
+    }
+}
+
+fn diff_values<'x, I, B>(
+    old: &Value<'x, JSContactProperty<I>, JSContactValue<I, B>>,
+    new: &Value<'x, JSContactProperty<I>, JSContactValue<I, B>>,
+    path: &mut Vec<String>,
+    ops: &mut Vec<PatchOp<'x, I, B>>,
+) where
+    I: JSContactId,
+    B: JSContactId,
+{
+    let (Some(old_obj), Some(new_obj)) = (old.as_object(), new.as_object()) else {
+        if old != new {
+            ops.push(PatchOp {
+                path: encode_path(path),
+                value: new.clone(),
+            });
+        }
+        return;
+    };
+
+    for (key, _) in old_obj.as_vec() {
+        let key = key.to_string();
+        if !new_obj.as_vec().iter().any(|(k, _)| k.to_string() == key) {
+            path.push(key.into_owned());
+            ops.push(PatchOp {
+                path: encode_path(path),
+                value: Value::Null,
+            });
+            path.pop();
+        }
+    }
+
+    for (key, new_value) in new_obj.as_vec() {
+        let key = key.to_string();
+        let old_value = old_obj
+            .as_vec()
+            .iter()
+            .find_map(|(k, v)| (k.to_string() == key).then_some(v));
+        path.push(key.into_owned());
+        match old_value {
+            Some(old_value) if old_value == new_value => {}
+            Some(old_value) => diff_values(old_value, new_value, path, ops),
+            None => ops.push(PatchOp {
+                path: encode_path(path),
+                value: new_value.clone(),
+            }),
+        }
+        path.pop();
+    }
+}
+
+fn encode_path<I: JSContactId>(path: &[String]) -> JsonPointer<JSContactProperty<I>> {
+    JsonPointer::parse(&JsonPointer::<JSContactProperty<I>>::encode(
+        &path.iter().map(String::as_str).collect::<Vec<_>>(),
+    ))
+}
+
+fn remove_path<I, B>(
+    value: &mut Value<'_, JSContactProperty<I>, JSContactValue<I, B>>,
+    ptr: &[JsonPointerItem<JSContactProperty<I>>],
+) where
+    I: JSContactId,
+    B: JSContactId,
+{
+    match ptr {
+        [JsonPointerItem::Root, rest @ ..] => remove_path(value, rest),
+        [JsonPointerItem::Key(key), rest @ ..] => {
+            let Some(obj) = value.as_object_mut() else {
+                return;
+            };
+            let key = key.to_string();
+            if rest.is_empty() {
+                obj.as_mut_vec().retain(|(k, _)| k.to_string() != key);
+            } else if let Some((_, value)) = obj
+                .as_mut_vec()
+                .iter_mut()
+                .find(|(k, _)| k.to_string() == key)
+            {
+                remove_path(value, rest);
+            }
+        }
+        [JsonPointerItem::Number(idx), rest @ ..] => {
+            let Some(arr) = value.as_array_mut() else {
+                return;
+            };
+            let idx = *idx as usize;
+            if rest.is_empty() {
+                if idx < arr.len() {
+                    arr.remove(idx);
+                }
+            } else if let Some(value) = arr.get_mut(idx) {
+                remove_path(value, rest);
+            }
+        }
+        [JsonPointerItem::Wildcard, ..] | [] => {}
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use crate::jscontact::JSContact;
+
+    #[test]
+    fn diff_and_patch() {
+        let old = JSContact::<String, String>::parse(
+            r#"{
+                "@type": "Card",
+                "version": "1.0",
+                "uid": "urn:uuid:1",
+                "name": { "full": "Jane Doe" },
+                "nicknames": { "k1": { "name": "JJ" } },
+                "notes": { "n1": { "note": "hello" } }
+            }"#,
+        )
+        .unwrap();
+        let new = JSContact::<String, String>::parse(
+            r#"{
+                "@type": "Card",
+                "version": "1.0",
+                "uid": "urn:uuid:1",
+                "name": { "full": "Jane Smith" },
+                "nicknames": { "k1": { "name": "JJ" }, "k2": { "name": "Janie" } },
+                "emails": { "e1": { "address": "jane@example.com" } }
+            }"#,
+        )
+        .unwrap();
+
+        let ops = old.diff(&new);
+        assert_eq!(
+            ops.iter()
+                .map(|op| (
+                    op.path.to_string(),
+                    serde_json::to_string(&op.value).unwrap()
+                ))
+                .collect::<Vec<_>>(),
+            vec![
+                ("notes".to_string(), "null".to_string()),
+                ("name/full".to_string(), r#""Jane Smith""#.to_string()),
+                (
+                    "nicknames/k2".to_string(),
+                    r#"{"name":"Janie"}"#.to_string()
+                ),
+                (
+                    "emails".to_string(),
+                    r#"{"e1":{"address":"jane@example.com"}}"#.to_string()
+                ),
+            ]
+        );
+
+        let mut patched = old.clone();
+        patched.apply_patch_ops(ops);
+        assert_eq!(
+            serde_json::to_value(&patched.0).unwrap(),
+            serde_json::to_value(&new.0).unwrap()
+        );
+        assert!(patched.diff(&new).is_empty());
+    }
+}
@@ -0,0 +1,810 @@
+/*
+ * SPDX-FileCopyrightText: 2020 Stalwart Labs LLC <hello@stalw.art>
+ *
+ * SPDX-License-Identifier: Apache-2.0 OR MIT
+ */
+
+use crate::jscontact::{
+    JSContact, JSContactId, JSContactKind, JSContactProperty, JSContactType, JSContactValue,
+};
+use jmap_tools::{Key, Map, Value};
+use serde::Serialize;
+
+type JSContactMap<'x, I, B> = Map<'x, JSContactProperty<I>, JSContactValue<I, B>>;
+type JSContactObject<'x, I, B> = Value<'x, JSContactProperty<I>, JSContactValue<I, B>>;
+
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct JSContactCard<'x, I: JSContactId, B: JSContactId> {
+    pub uid: Option<String>,
+    pub kind: Option<JSContactKind>,
+    pub name: Option<JSContactName<'x, I, B>>,
+    pub emails: Vec<JSContactEmailAddress<'x, I, B>>,
+    pub phones: Vec<JSContactPhone<'x, I, B>>,
+    pub addresses: Vec<JSContactAddress<'x, I, B>>,
+    pub organizations: Vec<JSContactOrganization<'x, I, B>>,
+    pub extra: JSContactMap<'x, I, B>,
+}
+
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct JSContactName<'x, I: JSContactId, B: JSContactId> {
+    pub full: Option<String>,
+    pub components: Vec<(JSContactKind, String)>,
+    pub extra: JSContactMap<'x, I, B>,
+}
+
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct JSContactEmailAddress<'x, I: JSContactId, B: JSContactId> {
+    pub id: String,
+    pub address: String,
+    pub contexts: Vec<String>,
+    pub pref: Option<u64>,
+    pub label: Option<String>,
+    pub extra: JSContactMap<'x, I, B>,
+}
+
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct JSContactPhone<'x, I: JSContactId, B: JSContactId> {
+    pub id: String,
+    pub number: String,
+    pub features: Vec<String>,
+    pub contexts: Vec<String>,
+    pub pref: Option<u64>,
+    pub label: Option<String>,
+    pub extra: JSContactMap<'x, I, B>,
+}
+
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct JSContactAddress<'x, I: JSContactId, B: JSContactId> {
+    pub id: String,
+    pub full: Option<String>,
+    pub components: Vec<(JSContactKind, String)>,
+    pub country_code: Option<String>,
+    pub contexts: Vec<String>,
+    pub pref: Option<u64>,
+    pub extra: JSContactMap<'x, I, B>,
+}
+
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct JSContactOrganization<'x, I: JSContactId, B: JSContactId> {
+    pub id: String,
+    pub name: Option<String>,
+    pub units: Vec<String>,
+    pub extra: JSContactMap<'x, I, B>,
+}
+
+trait TypedEntry<'x, I: JSContactId, B: JSContactId>: Sized {
+    fn try_from_value(
+        id: String,
+        value: JSContactObject<'x, I, B>,
+    ) -> Result<Self, JSContactObject<'x, I, B>>;
+
+    fn into_value(self) -> (String, JSContactObject<'x, I, B>);
+}
+
+impl<'x, I: JSContactId, B: JSContactId> JSContactCard<'x, I, B> {
+    pub fn parse(json: &'x str) -> Result<Self, String> {
+        JSContact::parse(json)?
+            .try_into()
+            .map_err(|_| "Expected a Card object".to_string())
+    }
+}
+
+impl<'x, I: JSContactId, B: JSContactId> TryFrom<JSContact<'x, I, B>> for JSContactCard<'x, I, B> {
+    type Error = JSContact<'x, I, B>;
+
+    fn try_from(jscontact: JSContact<'x, I, B>) -> Result<Self, Self::Error> {
+        let obj = match jscontact.0 {
+            Value::Object(obj) if is_card(&obj) => obj,
+            value => return Err(JSContact(value)),
+        };
+
+        let mut card = JSContactCard {
+            uid: None,
+            kind: None,
+            name: None,
+            emails: vec![],
+            phones: vec![],
+            addresses: vec![],
+            organizations: vec![],
+            extra: Map::from(vec![]),
+        };
+        let mut extra = Vec::new();
+
+        // Collections where any entry does not fully convert are kept untouched in the extra map
+        for (key, value) in obj.into_vec() {
+            match (key, value) {
+                (Key::Property(JSContactProperty::Type), _) => {}
+                (Key::Property(JSContactProperty::Uid), Value::Str(text)) => {
+                    card.uid = Some(text.into_owned());
+                }
+                (
+                    Key::Property(JSContactProperty::Kind),
+                    Value::Element(JSContactValue::Kind(kind)),
+                ) => {
+                    card.kind = Some(kind);
+                }
+                (key @ Key::Property(JSContactProperty::Name), value) => {
+                    match JSContactName::try_from_value(value) {
+                        Ok(name) => card.name = Some(name),
+                        Err(value) => extra.push((key, value)),
+                    }
+                }
+                (key @ Key::Property(JSContactProperty::Emails), value) => {
+                    match try_entries(value) {
+                        Ok(emails) => card.emails = emails,
+                        Err(value) => extra.push((key, value)),
+                    }
+                }
+                (key @ Key::Property(JSContactProperty::Phones), value) => {
+                    match try_entries(value) {
+                        Ok(phones) => card.phones = phones,
+                        Err(value) => extra.push((key, value)),
+                    }
+                }
+                (key @ Key::Property(JSContactProperty::Addresses), value) => {
+                    match try_entries(value) {
+                        Ok(addresses) => card.addresses = addresses,
+                        Err(value) => extra.push((key, value)),
+                    }
+                }
+                (key @ Key::Property(JSContactProperty::Organizations), value) => {
+                    match try_entries(value) {
+                        Ok(organizations) => card.organizations = organizations,
+                        Err(value) => extra.push((key, value)),
+                    }
+                }
+                (key, value) => extra.push((key, value)),
+            }
+        }
+
+        card.extra = Map::from(extra);
+        Ok(card)
+    }
+}
+
+impl<'x, I: JSContactId, B: JSContactId> From<JSContactCard<'x, I, B>> for JSContact<'x, I, B> {
+    fn from(card: JSContactCard<'x, I, B>) -> Self {
+        let mut obj = vec![(
+            Key::Property(JSContactProperty::Type),
+            Value::Element(JSContactValue::Type(JSContactType::Card)),
+        )];
+
+        for (prop, value) in [
+            (
+                JSContactProperty::Uid,
+                card.uid.map(|v| Value::Str(v.into())),
+            ),
+            (
+                JSContactProperty::Kind,
+                card.kind.map(|v| Value::Element(JSContactValue::Kind(v))),
+            ),
+            (
+                JSContactProperty::Name,
+                card.name.map(|name| name.into_value()),
+            ),
+            (JSContactProperty::Emails, entries_value(card.emails)),
+            (JSContactProperty::Phones, entries_value(card.phones)),
+            (JSContactProperty::Addresses, entries_value(card.addresses)),
+            (
+                JSContactProperty::Organizations,
+                entries_value(card.organizations),
+            ),
+        ] {
+            if let Some(value) = value {
+                obj.push((Key::Property(prop), value));
+            }
+        }
+
+        obj.extend(card.extra.into_vec());
+        JSContact(Value::Object(Map::from(obj)))
+    }
+}
+
+impl<I: JSContactId, B: JSContactId> Serialize for JSContactCard<'_, I, B> {
+    fn serialize<S: serde::Serializer>(&self, serializer: S) -> Result<S::Ok, S::Error> {
+        JSContact::from(self.clone()).0.serialize(serializer)
+    }
+}
+
+impl<'x, I: JSContactId, B: JSContactId> JSContactName<'x, I, B> {
+    fn try_from_value(value: JSContactObject<'x, I, B>) -> Result<Self, JSContactObject<'x, I, B>> {
+        let Value::Object(obj) = value else {
+            return Err(value);
+        };
+        let mut name = JSContactName {
+            full: None,
+            components: vec![],
+            extra: Map::from(vec![]),
+        };
+        let mut extra = Vec::new();
+
+        for (key, value) in obj.into_vec() {
+            match (key, value) {
+                (Key::Property(JSContactProperty::Full), Value::Str(text)) => {
+                    name.full = Some(text.into_owned());
+                }
+                (key @ Key::Property(JSContactProperty::Components), value) => {
+                    match components(&value) {
+                        Some(components) => name.components = components,
+                        None => extra.push((key, value)),
+                    }
+                }
+                (key, value) => extra.push((key, value)),
+            }
+        }
+
+        name.extra = Map::from(extra);
+        Ok(name)
+    }
+
+    fn into_value(self) -> JSContactObject<'x, I, B> {
+        let mut obj = Vec::new();
+        if let Some(full) = self.full {
+            obj.push((
+                Key::Property(JSContactProperty::Full),
+                Value::Str(full.into()),
+            ));
+        }
+        if let Some(components) = components_value(self.components) {
+            obj.push((Key::Property(JSContactProperty::Components), components));
+        }
+        obj.extend(self.extra.into_vec());
+        Value::Object(Map::from(obj))
+    }
+}
+
+impl<'x, I: JSContactId, B: JSContactId> TypedEntry<'x, I, B> for JSContactEmailAddress<'x, I, B> {
+    fn try_from_value(
+        id: String,
+        value: JSContactObject<'x, I, B>,
+    ) -> Result<Self, JSContactObject<'x, I, B>> {
+        let obj = match value {
+            Value::Object(obj) if has_text(&obj, JSContactProperty::Address) => obj,
+            value => return Err(value),
+        };
+        let mut email = JSContactEmailAddress {
+            id,
+            address: String::new(),
+            contexts: vec![],
+            pref: None,
+            label: None,
+            extra: Map::from(vec![]),
+        };
+        let mut extra = Vec::new();
+
+        for (key, value) in obj.into_vec() {
+            match (key, value) {
+                (Key::Property(JSContactProperty::Address), Value::Str(text)) => {
+                    email.address = text.into_owned();
+                }
+                (Key::Property(JSContactProperty::Label), Value::Str(text)) => {
+                    email.label = Some(text.into_owned());
+                }
+                (Key::Property(JSContactProperty::Pref), Value::Number(value)) => {
+                    email.pref = Some(value.cast_to_u64());
+                }
+                (key @ Key::Property(JSContactProperty::Contexts), value) => {
+                    match boolean_set(&value) {
+                        Some(contexts) => email.contexts = contexts,
+                        None => extra.push((key, value)),
+                    }
+                }
+                (key, value) => extra.push((key, value)),
+            }
+        }
+
+        email.extra = Map::from(extra);
+        Ok(email)
+    }
+
+    fn into_value(self) -> (String, JSContactObject<'x, I, B>) {
+        let mut obj = vec![(
+            Key::Property(JSContactProperty::Address),
+            Value::Str(self.address.into()),
+        )];
+
+        for (prop, value) in [
+            (
+                JSContactProperty::Contexts,
+                boolean_set_value(self.contexts),
+            ),
+            (
+                JSContactProperty::Pref,
+                self.pref.map(|v| Value::Number(v.into())),
+            ),
+            (
+                JSContactProperty::Label,
+                self.label.map(|v| Value::Str(v.into())),
+            ),
+        ] {
+            if let Some(value) = value {
+                obj.push((Key::Property(prop), value));
+            }
+        }
+
+        obj.extend(self.extra.into_vec());
+        (self.id, Value::Object(Map::from(obj)))
+    }
+}
+
+impl<'x, I: JSContactId, B: JSContactId> TypedEntry<'x, I, B> for JSContactPhone<'x, I, B> {
+    fn try_from_value(
+        id: String,
+        value: JSContactObject<'x, I, B>,
+    ) -> Result<Self, JSContactObject<'x, I, B>> {
+        let obj = match value {
+            Value::Object(obj) if has_text(&obj, JSContactProperty::Number) => obj,
+            value => return Err(value),
+        };
+        let mut phone = JSContactPhone {
+            id,
+            number: String::new(),
+            features: vec![],
+            contexts: vec![],
+            pref: None,
+            label: None,
+            extra: Map::from(vec![]),
+        };
+        let mut extra = Vec::new();
+
+        for (key, value) in obj.into_vec() {
+            match (key, value) {
+                (Key::Property(JSContactProperty::Number), Value::Str(text)) => {
+                    phone.number = text.into_owned();
+                }
+                (Key::Property(JSContactProperty::Label), Value::Str(text)) => {
+                    phone.label = Some(text.into_owned());
+                }
+                (Key::Property(JSContactProperty::Pref), Value::Number(value)) => {
+                    phone.pref = Some(value.cast_to_u64());
+                }
+                (key @ Key::Property(JSContactProperty::Features), value) => {
+                    match boolean_set(&value) {
+                        Some(features) => phone.features = features,
+                        None => extra.push((key, value)),
+                    }
+                }
+                (key @ Key::Property(JSContactProperty::Contexts), value) => {
+                    match boolean_set(&value) {
+                        Some(contexts) => phone.contexts = contexts,
+                        None => extra.push((key, value)),
+                    }
+                }
+                (key, value) => extra.push((key, value)),
+            }
+        }
+
+        phone.extra = Map::from(extra);
+        Ok(phone)
+    }
+
+    fn into_value(self) -> (String, JSContactObject<'x, I, B>) {
+        let mut obj = vec![(
+            Key::Property(JSContactProperty::Number),
+            Value::Str(self.number.into()),
+        )];
+
+        for (prop, value) in [
+            (
+                JSContactProperty::Features,
+                boolean_set_value(self.features),
+            ),
+            (
+                JSContactProperty::Contexts,
+                boolean_set_value(self.contexts),
+            ),
+            (
+                JSContactProperty::Pref,
+                self.pref.map(|v| Value::Number(v.into())),
+            ),
+            (
+                JSContactProperty::Label,
+                self.label.map(|v| Value::Str(v.into())),
+            ),
+        ] {
+            if let Some(value) = value {
+                obj.push((Key::Property(prop), value));
+            }
+        }
+
+        obj.extend(self.extra.into_vec());
+        (self.id, Value::Object(Map::from(obj)))
+    }
+}
+
+impl<'x, I: JSContactId, B: JSContactId> TypedEntry<'x, I, B> for JSContactAddress<'x, I, B> {
+    fn try_from_value(
+        id: String,
+        value: JSContactObject<'x, I, B>,
+    ) -> Result<Self, JSContactObject<'x, I, B>> {
+        let Value::Object(obj) = value else {
+            return Err(value);
+        };
+        let mut address = JSContactAddress {
+            id,
+            full: None,
+            components: vec![],
+            country_code: None,
+            contexts: vec![],
+            pref: None,
+            extra: Map::from(vec![]),
+        };
+        let mut extra = Vec::new();
+
+        for (key, value) in obj.into_vec() {
+            match (key, value) {
+                (Key::Property(JSContactProperty::Full), Value::Str(text)) => {
+                    address.full = Some(text.into_owned());
+                }
+                (Key::Property(JSContactProperty::CountryCode), Value::Str(text)) => {
+                    address.country_code = Some(text.into_owned());
+                }
+                (Key::Property(JSContactProperty::Pref), Value::Number(value)) => {
+                    address.pref = Some(value.cast_to_u64());
+                }
+                (key @ Key::Property(JSContactProperty::Components), value) => {
+                    match components(&value) {
+                        Some(components) => address.components = components,
+                        None => extra.push((key, value)),
+                    }
+                }
+                (key @ Key::Property(JSContactProperty::Contexts), value) => {
+                    match boolean_set(&value) {
+                        Some(contexts) => address.contexts = contexts,
+                        None => extra.push((key, value)),
+                    }
+                }
+                (key, value) => extra.push((key, value)),
+            }
+        }
+
+        address.extra = Map::from(extra);
+        Ok(address)
+    }
+
+    fn into_value(self) -> (String, JSContactObject<'x, I, B>) {
+        let mut obj = Vec::new();
+
+        for (prop, value) in [
+            (
+                JSContactProperty::Full,
+                self.full.map(|v| Value::Str(v.into())),
+            ),
+            (
+                JSContactProperty::Components,
+                components_value(self.components),
+            ),
+            (
+                JSContactProperty::CountryCode,
+                self.country_code.map(|v| Value::Str(v.into())),
+            ),
+            (
+                JSContactProperty::Contexts,
+                boolean_set_value(self.contexts),
+            ),
+            (
+                JSContactProperty::Pref,
+                self.pref.map(|v| Value::Number(v.into())),
+            ),
+        ] {
+            if let Some(value) = value {
+                obj.push((Key::Property(prop), value));
+            }
+        }
+
+        obj.extend(self.extra.into_vec());
+        (self.id, Value::Object(Map::from(obj)))
+    }
+}
+
+impl<'x, I: JSContactId, B: JSContactId> TypedEntry<'x, I, B> for JSContactOrganization<'x, I, B> {
+    fn try_from_value(
+        id: String,
+        value: JSContactObject<'x, I, B>,
+    ) -> Result<Self, JSContactObject<'x, I, B>> {
+        let Value::Object(obj) = value else {
+            return Err(value);
+        };
+        let mut organization = JSContactOrganization {
+            id,
+            name: None,
+            units: vec![],
+            extra: Map::from(vec![]),
+        };
+        let mut extra = Vec::new();
+
+        for (key, value) in obj.into_vec() {
+            match (key, value) {
+                (Key::Property(JSContactProperty::Name), Value::Str(text)) => {
+                    organization.name = Some(text.into_owned());
+                }
+                (key @ Key::Property(JSContactProperty::Units), value) => match units(&value) {
+                    Some(units) => organization.units = units,
+                    None => extra.push((key, value)),
+                },
+                (key, value) => extra.push((key, value)),
+            }
+        }
+
+        organization.extra = Map::from(extra);
+        Ok(organization)
+    }
+
+    fn into_value(self) -> (String, JSContactObject<'x, I, B>) {
+        let mut obj = Vec::new();
+        if let Some(name) = self.name {
+            obj.push((
+                Key::Property(JSContactProperty::Name),
+                Value::Str(name.into()),
+            ));
+        }
+        if !self.units.is_empty() {
+            obj.push((
+                Key::Property(JSContactProperty::Units),
+                Value::Array(
+                    self.units
+                        .into_iter()
+                        .map(|unit| {
+                            Value::Object(Map::from(vec![(
+                                Key::Property(JSContactProperty::Name),
+                                Value::Str(unit.into()),
+                            )]))
+                        })
+                        .collect(),
+                ),
+            ));
+        }
+        obj.extend(self.extra.into_vec());
+        (self.id, Value::Object(Map::from(obj)))
+    }
+}
+
+fn is_card<I: JSContactId, B: JSContactId>(obj: &JSContactMap<'_, I, B>) -> bool {
+    obj.as_vec().iter().any(|(key, value)| {
+        matches!(
+            (key, value),
+            (
+                Key::Property(JSContactProperty::Type),
+                Value::Element(JSContactValue::Type(JSContactType::Card)),
+            )
+        )
+    })
+}
+
+fn has_text<I: JSContactId, B: JSContactId>(
+    obj: &JSContactMap<'_, I, B>,
+    prop: JSContactProperty<I>,
+) -> bool {
+    obj.as_vec().iter().any(
+        |(key, value)| matches!((key, value), (Key::Property(key), Value::Str(_)) if key == &prop),
+    )
+}
+
+fn try_entries<'x, I: JSContactId, B: JSContactId, T: TypedEntry<'x, I, B>>(
+    value: JSContactObject<'x, I, B>,
+) -> Result<Vec<T>, JSContactObject<'x, I, B>> {
+    let Value::Object(obj) = value else {
+        return Err(value);
+    };
+    let entries = obj
+        .into_vec()
+        .into_iter()
+        .map(|(key, value)| {
+            let id = String::from(key.to_string());
+            T::try_from_value(id, value).map_err(|value| (key, value))
+        })
+        .collect::<Vec<_>>();
+
+    if entries.iter().all(Result::is_ok) {
+        Ok(entries.into_iter().flatten().collect())
+    } else {
+        Err(Value::Object(Map::from(
+            entries
+                .into_iter()
+                .map(|entry| match entry {
+                    Ok(entry) => {
+                        let (id, value) = entry.into_value();
+                        (Key::Owned(id), value)
+                    }
+                    Err(entry) => entry,
+                })
+                .collect::<Vec<_>>(),
+        )))
+    }
+}
+
+fn entries_value<'x, I: JSContactId, B: JSContactId, T: TypedEntry<'x, I, B>>(
+    entries: Vec<T>,
+) -> Option<JSContactObject<'x, I, B>> {
+    (!entries.is_empty()).then(|| {
+        Value::Object(Map::from(
+            entries
+                .into_iter()
+                .map(|entry| {
+                    let (id, value) = entry.into_value();
+                    (Key::Owned(id), value)
+                })
+                .collect::<Vec<_>>(),
+        ))
+    })
+}
+
+fn boolean_set<I: JSContactId, B: JSContactId>(
+    value: &JSContactObject<'_, I, B>,
+) -> Option<Vec<String>> {
+    value
+        .as_object()?
+        .as_vec()
+        .iter()
+        .map(|(key, value)| {
+            matches!(value, Value::Bool(true)).then(|| String::from(key.to_string()))
+        })
+        .collect()
+}
+
+fn boolean_set_value<'x, I: JSContactId, B: JSContactId>(
+    set: Vec<String>,
+) -> Option<JSContactObject<'x, I, B>> {
+    (!set.is_empty()).then(|| {
+        Value::Object(Map::from(
+            set.into_iter()
+                .map(|key| (Key::Owned(key), Value::Bool(true)))
+                .collect::<Vec<_>>(),
+        ))
+    })
+}
+
+// The @type of a component is implied and not kept
+fn components<I: JSContactId, B: JSContactId>(
+    value: &JSContactObject<'_, I, B>,
+) -> Option<Vec<(JSContactKind, String)>> {
+    let Value::Array(values) = value else {
+        return None;
+    };
+
+    values
+        .iter()
+        .map(|value| {
+            let mut kind = None;
+            let mut text = None;
+            for (key, value) in value.as_object()?.as_vec() {
+                match (key, value) {
+                    (
+                        Key::Property(JSContactProperty::Kind),
+                        Value::Element(JSContactValue::Kind(value)),
+                    ) => kind = Some(*value),
+                    (Key::Property(JSContactProperty::Value), Value::Str(value)) => {
+                        text = Some(value.to_string());
+                    }
+                    (
+                        Key::Property(JSContactProperty::Type),
+                        Value::Element(JSContactValue::Type(
+                            JSContactType::NameComponent | JSContactType::AddressComponent,
+                        )),
+                    ) => {}
+                    _ => return None,
+                }
+            }
+            kind.zip(text)
+        })
+        .collect()
+}
+
+fn components_value<'x, I: JSContactId, B: JSContactId>(
+    components: Vec<(JSContactKind, String)>,
+) -> Option<JSContactObject<'x, I, B>> {
+    (!components.is_empty()).then(|| {
+        Value::Array(
+            components
+                .into_iter()
+                .map(|(kind, value)| {
+                    Value::Object(Map::from(vec![
+                        (
+                            Key::Property(JSContactProperty::Kind),
+                            Value::Element(JSContactValue::Kind(kind)),
+                        ),
+                        (
+                            Key::Property(JSContactProperty::Value),
+                            Value::Str(value.into()),
+                        ),
+                    ]))
+                })
+                .collect(),
+        )
+    })
+}
+
+fn units<I: JSContactId, B: JSContactId>(value: &JSContactObject<'_, I, B>) -> Option<Vec<String>> {
+    let Value::Array(values) = value else {
+        return None;
+    };
+
+    values
+        .iter()
+        .map(|value| match &value.as_object()?.as_vec()[..] {
+            [(Key::Property(JSContactProperty::Name), Value::Str(name))] => Some(name.to_string()),
+            _ => None,
+        })
+        .collect()
+}
+
+#[cfg(test)]
+mod tests {
+    use super::JSContactCard;
+    use crate::jscontact::{JSContact, JSContactKind};
+
+    #[test]
+    fn typed_card() {
+        let json = r#"{
+            "@type": "Card",
+            "version": "1.0",
+            "uid": "urn:uuid:22fa1ef6-9e25-4dce-9a4c-8c2c4ec6ca34",
+            "kind": "individual",
+            "name": {
+                "components": [
+                    { "kind": "given", "value": "Jane" },
+                    { "kind": "surname", "value": "Doe" }
+                ],
+                "isOrdered": true
+            },
+            "emails": {
+                "e1": { "address": "jane@example.com", "contexts": { "work": true }, "pref": 1 }
+            },
+            "phones": {
+                "p1": { "number": "+1-555-0100", "features": { "mobile": true } },
+                "p2": { "features": { "voice": true } }
+            },
+            "addresses": {
+                "a1": {
+                    "components": [{ "kind": "locality", "value": "Springfield" }],
+                    "countryCode": "US"
+                }
+            },
+            "organizations": {
+                "o1": { "name": "ACME", "units": [{ "name": "Sales" }] }
+            },
+            "notes": {
+                "n1": { "note": "Met at the conference" }
+            }
+        }"#;
+
+        let card = JSContactCard::<String, String>::parse(json).unwrap();
+        assert_eq!(
+            card.uid.as_deref(),
+            Some("urn:uuid:22fa1ef6-9e25-4dce-9a4c-8c2c4ec6ca34")
+        );
+        assert_eq!(card.kind, Some(JSContactKind::Individual));
+        let name = card.name.as_ref().unwrap();
+        assert_eq!(
+            name.components,
+            [
+                (JSContactKind::Given, "Jane".to_string()),
+                (JSContactKind::Surname, "Doe".to_string())
+            ]
+        );
+        assert_eq!(name.extra.as_vec().len(), 1);
+        assert_eq!(card.emails.len(), 1);
+        assert_eq!(card.emails[0].id, "e1");
+        assert_eq!(card.emails[0].address, "jane@example.com");
+        assert_eq!(card.emails[0].contexts, ["work"]);
+        assert_eq!(card.emails[0].pref, Some(1));
+        // A phone without a number cannot be typed, so the whole map is kept as is
+        assert!(card.phones.is_empty());
+        assert_eq!(card.addresses[0].country_code.as_deref(), Some("US"));
+        assert_eq!(
+            card.addresses[0].components,
+            [(JSContactKind::Locality, "Springfield".to_string())]
+        );
+        assert_eq!(card.organizations[0].name.as_deref(), Some("ACME"));
+        assert_eq!(card.organizations[0].units, ["Sales"]);
+        assert_eq!(card.extra.as_vec().len(), 3);
+
+        // Converting back yields the same document
+        assert_eq!(
+            serde_json::to_value(&card).unwrap(),
+            serde_json::to_value(&JSContact::<String, String>::parse(json).unwrap().0).unwrap()
+        );
+
+        assert!(JSContactCard::try_from(JSContact::<String, String>::default()).is_err());
+    }
+}
@@ -6,7 +6,10 @@
 #![doc = include_str!("../README.md")]
 #![deny(rust_2018_idioms)]
 #![forbid(unsafe_code)]
-use common::tokenizer::{StopChar, Token};
+use common::{
+    EntrySpan, ParseDiagnostic, ParseDiagnosticKind,
+    tokenizer::{StopChar, Token},
+};
 use icalendar::{ICalendar, ICalendarComponentType};
 use std::{
     borrow::Cow,
@@ -16,6 +19,7 @@ use std::{
 use vcard::VCard;
 
 pub mod common;
+#[cfg(feature = "datetime")]
 pub mod datecalc;
 pub mod icalendar;
 #[cfg(feature = "jmap")]
@@ -36,9 +40,14 @@ pub enum Entry {
     },
     UnterminatedComponent(Cow<'static, str>),
     TooManyComponents,
+    TooDeeplyNested,
+    InvalidEncoding,
+    EmptyLine,
     Eof,
 }
 
+pub(crate) const DEFAULT_MAX_DEPTH: usize = 20;
+
 pub struct Parser<'x> {
     pub(crate) input: &'x [u8],
     pub(crate) iter: Peekable<Enumerate<Iter<'x, u8>>>,
@@ -51,12 +60,22 @@ pub struct Parser<'x> {
     pub(crate) unfold_qp: bool,
     pub(crate) unquote: bool,
     pub(crate) skip_ws: bool,
+    pub(crate) caret_decode: bool,
+    pub(crate) lenient_durations: bool,
+    pub(crate) preserve_trailing_empty_lines: bool,
     pub(crate) token_buf: Vec<Token<'x>>,
+    pub(crate) diagnostics: Option<Vec<ParseDiagnostic>>,
+    pub(crate) spans: Option<Vec<EntrySpan>>,
+    pub(crate) line_cursor: (usize, usize),
+    pub(crate) max_depth: usize,
 }
 
 impl<'x> Parser<'x> {
     pub fn new(input: &'x str) -> Self {
-        let input = input.as_bytes();
+        Self::from_bytes(input.as_bytes())
+    }
+
+    pub fn from_bytes(input: &'x [u8]) -> Self {
         Self {
             input,
             iter: input.iter().enumerate().peekable(),
@@ -69,7 +88,14 @@ impl<'x> Parser<'x> {
             unfold_qp: false,
             unquote: true,
             skip_ws: false,
+            caret_decode: false,
+            lenient_durations: true,
+            preserve_trailing_empty_lines: false,
             token_buf: Vec::with_capacity(10),
+            diagnostics: None,
+            spans: None,
+            line_cursor: (0, 1),
+            max_depth: DEFAULT_MAX_DEPTH,
         }
     }
 
@@ -78,6 +104,152 @@ impl<'x> Parser<'x> {
         self
     }
 
+    /// Controls how DURATION values are parsed. Lenient mode (the default)
+    /// accepts these deviations from RFC 5545:
+    ///
+    /// - a missing or misplaced `T`, so `P1H` is one hour and `P1M` one minute
+    /// - components in any order, the last one winning when repeated
+    /// - a designator without digits, read as zero
+    /// - trailing digits without a designator, which are ignored
+    /// - whitespace anywhere in the value
+    /// - weeks combined with days or time, as in `P1W2D`
+    ///
+    /// Strict mode rejects all of the above. Rejected values are kept as text
+    /// and reported as `ParseDiagnosticKind::MalformedDuration`.
+    pub fn lenient_durations(mut self, lenient: bool) -> Self {
+        self.lenient_durations = lenient;
+        self
+    }
+
+    /// Empty lines at the end of the input are returned as `Entry::EmptyLine`,
+    /// one per line, instead of being skipped.
+    pub fn preserve_trailing_empty_lines(mut self, preserve: bool) -> Self {
+        self.preserve_trailing_empty_lines = preserve;
+        self
+    }
+
+    pub fn with_diagnostics(mut self) -> Self {
+        self.diagnostics = Some(Vec::new());
+        self
+    }
+
+    pub fn take_diagnostics(&mut self) -> Vec<ParseDiagnostic> {
+        self.diagnostics
+            .as_mut()
+            .map(std::mem::take)
+            .unwrap_or_default()
+    }
+
+    pub fn with_spans(mut self) -> Self {
+        self.spans = Some(Vec::new());
+        self
+    }
+
+    pub fn take_spans(&mut self) -> Vec<EntrySpan> {
+        self.spans.as_mut().map(std::mem::take).unwrap_or_default()
+    }
+
+    pub fn max_depth(mut self, max_depth: usize) -> Self {
+        self.max_depth = max_depth;
+        self
+    }
+
+    pub(crate) fn add_diagnostic(&mut self, offset: usize, kind: ParseDiagnosticKind) {
+        if self.diagnostics.is_none() {
+            return;
+        }
+        let is_folded = |pos: usize| matches!(self.input.get(pos), Some(b' ' | b'\t'));
+
+        // Locate the start of the logical line, skipping back over folded lines
+        let mut line_start = offset.min(self.input.len());
+        loop {
+            line_start = self.input[..line_start]
+                .iter()
+                .rposition(|&ch| ch == b'\n')
+                .map_or(0, |pos| pos + 1);
+            if line_start == 0 || !is_folded(line_start) {
+                break;
+            }
+            line_start -= 1;
+        }
+
+        let mut line = Vec::new();
+        let mut pos = line_start;
+        while let Some(&ch) = self.input.get(pos) {
+            match ch {
+                b'\r' => {}
+                b'\n' if is_folded(pos + 1) => {
+                    pos += 1;
+                }
+                b'\n' => break,
+                _ => line.push(ch),
+            }
+            pos += 1;
+        }
+
+        let line_num = self.line_num_at(line_start);
+        if let Some(diagnostics) = &mut self.diagnostics {
+            diagnostics.push(ParseDiagnostic {
+                line_num,
+                line: String::from_utf8_lossy(&line).into_owned(),
+                kind,
+            });
+        }
+    }
+
+    // Called once an entry has been parsed, the iterator then sits at the
+    // start of the next logical line
+    pub(crate) fn add_span(&mut self, component_id: u32, entry_id: usize, start: usize) {
+        if self.spans.is_none() {
+            return;
+        }
+        let mut end = self
+            .iter
+            .peek()
+            .map_or(self.input.len(), |(idx, _)| *idx)
+            .max(start);
+        while end > start && matches!(self.input[end - 1], b'\r' | b'\n') {
+            end -= 1;
+        }
+        let line_num = self.line_num_at(start);
+        if let Some(spans) = &mut self.spans {
+            spans.push(EntrySpan {
+                component_id,
+                entry_id: entry_id as u32,
+                span: start..end,
+                line_num,
+            });
+        }
+    }
+
+    // Lookups mostly move forward, so only the bytes since the previous
+    // lookup are scanned for line breaks
+    pub(crate) fn line_num_at(&mut self, offset: usize) -> usize {
+        let offset = offset.min(self.input.len());
+        let (pos, line_num) = self.line_cursor;
+        let line_num = if offset >= pos {
+            line_num + count_lines(&self.input[pos..offset])
+        } else {
+            line_num - count_lines(&self.input[offset..pos])
+        };
+        self.line_cursor = (offset, line_num);
+        line_num
+    }
+
+    // Empty tokens end at the line feed, the line only counts when nothing but
+    // further empty lines follow it
+    fn is_trailing_empty_line(&self, lf_pos: usize) -> bool {
+        let line_start = self.input[..lf_pos]
+            .iter()
+            .rposition(|&ch| ch == b'\n')
+            .map_or(0, |pos| pos + 1);
+        self.input.get(lf_pos) == Some(&b'\n')
+            && self.input[line_start..lf_pos].iter().all(|&ch| ch == b'\r')
+            && self.input[lf_pos + 1..]
+                .iter()
+                .all(|&ch| matches!(ch, b'\r' | b'\n'))
+    }
+
     pub fn entry(&mut self) -> Entry {
         self.expect_iana_token();
 
@@ -127,6 +299,10 @@ impl<'x> Parser<'x> {
                         }
                     }
                 } else if token.text.is_empty() {
+                    if self.preserve_trailing_empty_lines && self.is_trailing_empty_line(token.end)
+                    {
+                        return Entry::EmptyLine;
+                    }
                     continue;
                 }
 
@@ -143,3 +319,8 @@ impl<'x> Parser<'x> {
         }
     }
 }
+
+#[inline]
+fn count_lines(bytes: &[u8]) -> usize {
+    bytes.iter().filter(|&&ch| ch == b'\n').count()
+}
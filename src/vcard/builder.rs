@@ -5,7 +5,10 @@
  */
 
 use crate::{
-    common::parser::{Boolean, Timestamp},
+    common::{
+        parser::{Boolean, Timestamp},
+        writer::WriterOptions,
+    },
     vcard::*,
 };
 
@@ -55,6 +58,39 @@ impl VCardEntry {
         self
     }
 
+    pub fn set_param(&mut self, name: VCardParameterName, value: impl Into<VCardParameterValue>) {
+        let mut value = Some(value.into());
+        self.params.retain_mut(|param| {
+            if param.name != name {
+                true
+            } else if let Some(value) = value.take() {
+                param.value = value;
+                true
+            } else {
+                false
+            }
+        });
+        if let Some(value) = value {
+            self.params.push(VCardParameter { name, value });
+        }
+    }
+
+    pub fn remove_param(&mut self, name: &VCardParameterName) {
+        self.params.retain(|p| &p.name != name);
+    }
+
+    pub fn with_agent(mut self, vcard: &VCard) -> Self {
+        let mut text = String::new();
+        let _ = vcard.write_with_options(
+            &mut text,
+            vcard.version().unwrap_or_default(),
+            &WriterOptions::default().fold_width(None),
+        );
+        self.values
+            .push(VCardValue::Text(text.replace("\r\n", "\n")));
+        self
+    }
+
     pub fn is_type(&self, typ: &VCardValueType) -> bool {
         self.parameters(&VCardParameterName::Value)
             .any(|p| matches!(p, VCardParameterValue::ValueType(v) if v == typ))
@@ -0,0 +1,204 @@
+/*
+ * SPDX-FileCopyrightText: 2020 Stalwart Labs LLC <hello@stalw.art>
+ *
+ * SPDX-License-Identifier: Apache-2.0 OR MIT
+ */
+
+use super::{VCard, VCardEntry, VCardParameter, VCardParameterName, VCardProperty};
+
+#[derive(Debug, Clone, Copy, Default, PartialEq, Eq)]
+pub enum MergeStrategy {
+    // The card with the most recent REV wins, ties are resolved in favour of the local card
+    #[default]
+    NewestRevision,
+    PreferLocal,
+    PreferRemote,
+}
+
+impl VCard {
+    pub fn merge(&mut self, other: &VCard, strategy: MergeStrategy) -> Vec<VCardProperty> {
+        let remote_wins = match strategy {
+            MergeStrategy::NewestRevision => other.revision() > self.revision(),
+            MergeStrategy::PreferLocal => false,
+            MergeStrategy::PreferRemote => true,
+        };
+
+        // Bring the remote PIDs into the local CLIENTPIDMAP numbering
+        let mut other = other.clone();
+        other.renumber_pids(self);
+
+        let mut conflicts = Vec::new();
+        for entry in other.entries {
+            if matches!(
+                entry.name,
+                VCardProperty::Begin | VCardProperty::End | VCardProperty::Version
+            ) {
+                continue;
+            }
+
+            if is_single_valued(&entry.name) {
+                match self.entries.iter_mut().find(|e| e.name == entry.name) {
+                    Some(local) if local.values != entry.values || !same_params(local, &entry) => {
+                        if !matches!(entry.name, VCardProperty::Rev | VCardProperty::Prodid) {
+                            conflicts.push(entry.name.clone());
+                        }
+                        if remote_wins {
+                            *local = entry;
+                        }
+                    }
+                    Some(_) => {}
+                    None => self.entries.push(entry),
+                }
+            } else if let Some(local) = self.entries.iter_mut().find(|e| {
+                e.name == entry.name && e.values == entry.values && same_params(e, &entry)
+            }) {
+                for pid in entry.params {
+                    if pid.name == VCardParameterName::Pid && !local.params.contains(&pid) {
+                        local.params.push(pid);
+                    }
+                }
+            } else {
+                self.entries.push(entry);
+            }
+        }
+
+        conflicts
+    }
+
+    fn revision(&self) -> Option<i64> {
+        self.property(&VCardProperty::Rev)
+            .and_then(|entry| entry.values.first())
+            .and_then(|value| value.as_partial_date_time())
+            .and_then(|dt| dt.to_timestamp())
+    }
+}
+
+// PIDs identify where a property came from rather than what it contains
+fn same_params(a: &VCardEntry, b: &VCardEntry) -> bool {
+    let contains =
+        |entry: &VCardEntry, param: &VCardParameter| entry.params.iter().any(|p| p == param);
+
+    content_params(a).count() == content_params(b).count()
+        && content_params(a).all(|param| contains(b, param))
+        && content_params(b).all(|param| contains(a, param))
+}
+
+fn content_params(entry: &VCardEntry) -> impl Iterator<Item = &VCardParameter> {
+    entry
+        .params
+        .iter()
+        .filter(|p| p.name != VCardParameterName::Pid)
+}
+
+fn is_single_valued(prop: &VCardProperty) -> bool {
+    matches!(
+        prop,
+        VCardProperty::Kind
+            | VCardProperty::N
+            | VCardProperty::Bday
+            | VCardProperty::Anniversary
+            | VCardProperty::Gender
+            | VCardProperty::Prodid
+            | VCardProperty::Rev
+            | VCardProperty::Uid
+            | VCardProperty::Birthplace
+            | VCardProperty::Deathplace
+            | VCardProperty::Deathdate
+            | VCardProperty::Created
+            | VCardProperty::Gramgender
+            | VCardProperty::Language
+    )
+}
+
+#[cfg(test)]
+mod tests {
+    use super::MergeStrategy;
+    use crate::vcard::{VCard, VCardProperty};
+
+    #[test]
+    fn merge_vcards() {
+        let local = VCard::parse(concat!(
+            "BEGIN:VCARD\r\n",
+            "VERSION:4.0\r\n",
+            "UID:urn:uuid:contact\r\n",
+            "FN:Jane Doe\r\n",
+            "N:Doe;Jane;;;\r\n",
+            "BDAY:19800101\r\n",
+            "REV:20240101T000000Z\r\n",
+            "CLIENTPIDMAP:1;urn:uuid:local-device\r\n",
+            "TEL;TYPE=home;PID=1.1:+1-555-0100\r\n",
+            "EMAIL:jane@example.com\r\n",
+            "END:VCARD\r\n"
+        ))
+        .unwrap();
+        let remote = VCard::parse(concat!(
+            "BEGIN:VCARD\r\n",
+            "VERSION:4.0\r\n",
+            "UID:urn:uuid:contact\r\n",
+            "FN:Jane Doe\r\n",
+            "N:Doe;Janet;;;\r\n",
+            "GENDER:F\r\n",
+            "REV:20240201T000000Z\r\n",
+            "CLIENTPIDMAP:1;urn:uuid:remote-device\r\n",
+            "TEL;TYPE=home;PID=1.1:+1-555-0100\r\n",
+            "TEL;TYPE=work:+1-555-0100\r\n",
+            "EMAIL:jane@example.com\r\n",
+            "EMAIL:jdoe@example.org\r\n",
+            "END:VCARD\r\n"
+        ))
+        .unwrap();
+
+        let text = |card: &VCard, prop: VCardProperty| {
+            card.properties(&prop)
+                .map(|entry| {
+                    let mut out = String::new();
+                    let _ = entry.write_to(&mut out, true);
+                    out.trim_end().to_string()
+                })
+                .collect::<Vec<_>>()
+        };
+
+        // The remote card has the newer REV so its N wins
+        let mut merged = local.clone();
+        let conflicts = merged.merge(&remote, MergeStrategy::NewestRevision);
+        assert_eq!(conflicts, [VCardProperty::N]);
+        assert_eq!(text(&merged, VCardProperty::N), ["N:Doe;Janet;;;"]);
+        assert_eq!(text(&merged, VCardProperty::Rev), ["REV:20240201T000000Z"]);
+        assert_eq!(text(&merged, VCardProperty::Bday), ["BDAY:19800101"]);
+        assert_eq!(text(&merged, VCardProperty::Gender), ["GENDER:F"]);
+        assert_eq!(text(&merged, VCardProperty::Fn), ["FN:Jane Doe"]);
+        assert_eq!(
+            text(&merged, VCardProperty::Clientpidmap),
+            [
+                "CLIENTPIDMAP:1;urn:uuid:local-device",
+                "CLIENTPIDMAP:2;urn:uuid:remote-device"
+            ]
+        );
+        // Same number with a different TYPE is kept apart, PIDs of duplicates are combined
+        assert_eq!(
+            text(&merged, VCardProperty::Tel),
+            [
+                "TEL;TYPE=HOME;PID=1.1,1.2:+1-555-0100",
+                "TEL;TYPE=WORK:+1-555-0100"
+            ]
+        );
+        assert_eq!(
+            text(&merged, VCardProperty::Email),
+            ["EMAIL:jane@example.com", "EMAIL:jdoe@example.org"]
+        );
+        assert_eq!(merged.properties(&VCardProperty::Version).count(), 1);
+
+        let mut merged = local.clone();
+        assert_eq!(
+            merged.merge(&remote, MergeStrategy::PreferLocal),
+            [VCardProperty::N]
+        );
+        assert_eq!(text(&merged, VCardProperty::N), ["N:Doe;Jane;;;"]);
+        assert_eq!(text(&merged, VCardProperty::Rev), ["REV:20240101T000000Z"]);
+
+        // Merging a card into itself changes nothing
+        let mut merged = local.clone();
+        assert!(merged.merge(&local, MergeStrategy::PreferRemote).is_empty());
+        assert_eq!(merged, local);
+    }
+}
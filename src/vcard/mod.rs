@@ -6,10 +6,14 @@
 
 use crate::{
     Entry, Parser,
-    common::{CalendarScale, Data, IanaParse, IanaString, IanaType, PartialDateTime},
+    common::{
+        CalendarScale, Data, EntrySpan, IanaParse, IanaString, IanaType, ParseDiagnostic,
+        PartialDateTime,
+    },
 };
 
 pub mod builder;
+pub mod merge;
 pub mod parser;
 pub mod types;
 pub mod utils;
@@ -127,6 +131,7 @@ pub enum VCardProperty {
     Pronouns,      // [RFC9554, Section 3.4]
     Socialprofile, // [RFC9554, Section 3.5]
     Jsprop,        // [RFC9555, Section 3.2.1]
+    Agent,         // [RFC2426, Section 3.5.4]
 }
 
 #[derive(Debug, Clone, PartialEq)]
@@ -448,4 +453,31 @@ impl VCard {
             other => Err(other),
         }
     }
+
+    pub fn parse_bytes(value: impl AsRef<[u8]>) -> Result<Self, Entry> {
+        let mut parser = Parser::from_bytes(value.as_ref());
+        match parser.entry() {
+            Entry::VCard(vcard) => Ok(vcard),
+            other => Err(other),
+        }
+    }
+
+    pub fn parse_with_spans(input: &str) -> Result<(Self, Vec<EntrySpan>), Entry> {
+        let mut parser = Parser::new(input).with_spans();
+        match parser.entry() {
+            Entry::VCard(vcard) => Ok((vcard, parser.take_spans())),
+            other => Err(other),
+        }
+    }
+
+    pub fn parse_with_diagnostics(input: &str) -> (Self, Vec<ParseDiagnostic>) {
+        let mut parser = Parser::new(input);
+        parser.diagnostics = Some(Vec::new());
+        let vcard = match parser.entry() {
+            Entry::VCard(vcard) => vcard,
+            _ => VCard::default(),
+        };
+
+        (vcard, parser.diagnostics.unwrap_or_default())
+    }
 }
@@ -11,7 +11,7 @@ use super::{
 use crate::{
     Entry, Parser, Token,
     common::{
-        CalendarScale, Data, Encoding, IanaParse, IanaType,
+        CalendarScale, Data, Encoding, IanaParse, IanaType, ParseDiagnosticKind,
         parser::{Boolean, Integer, Timestamp, parse_digits, parse_small_digits},
         tokenizer::StopChar,
     },
@@ -38,7 +38,7 @@ struct Params {
 impl Parser<'_> {
     pub fn vcard(&mut self) -> Entry {
         let mut vcard = VCard::default();
-        let mut is_v4 = true;
+        let mut is_v4 = self.vcard_is_v4();
         let mut is_valid = false;
 
         'outer: loop {
@@ -49,6 +49,7 @@ impl Parser<'_> {
                 Some(token) => token,
                 None => break,
             };
+            let line_offset = token.start;
             self.stop_dot = false;
 
             let mut params = Params {
@@ -80,6 +81,9 @@ impl Parser<'_> {
                 StopChar::Lf => {
                     // Invalid line
                     if name.is_empty() || !self.strict {
+                        if !name.is_empty() {
+                            self.add_diagnostic(line_offset, ParseDiagnosticKind::MalformedLine);
+                        }
                         continue;
                     } else {
                         return Entry::InvalidLine(Token::new(name).into_string());
@@ -98,9 +102,21 @@ impl Parser<'_> {
                 Some(name) => name,
                 None => {
                     if !name.is_empty() {
-                        VCardProperty::Other(Token::new(name).into_string())
+                        let name = Token::new(name).into_string();
+                        if self.diagnostics.is_some()
+                            && !name
+                                .get(..2)
+                                .is_some_and(|prefix| prefix.eq_ignore_ascii_case("X-"))
+                        {
+                            self.add_diagnostic(
+                                line_offset,
+                                ParseDiagnosticKind::UnknownProperty(name.clone()),
+                            );
+                        }
+                        VCardProperty::Other(name)
                     } else {
                         // Invalid line, skip
+                        self.add_diagnostic(line_offset, ParseDiagnosticKind::MalformedLine);
                         if params.stop_char != StopChar::Lf {
                             self.seek_lf();
                         }
@@ -108,6 +124,16 @@ impl Parser<'_> {
                     }
                 }
             };
+            if self.diagnostics.is_some() {
+                for data_type in &params.data_types {
+                    if let IanaType::Other(data_type) = data_type {
+                        self.add_diagnostic(
+                            line_offset,
+                            ParseDiagnosticKind::UnknownValueType(data_type.clone()),
+                        );
+                    }
+                }
+            }
             let mut entry = VCardEntry {
                 group: params.group_name,
                 name,
@@ -173,22 +199,33 @@ impl Parser<'_> {
                             }
                         };
                         if let Some(bytes) = bytes {
+                            // Inline media is always binary, even if it happens to be valid UTF-8
+                            let is_media = encoding == Encoding::Base64
+                                && matches!(
+                                    entry.name,
+                                    VCardProperty::Photo
+                                        | VCardProperty::Logo
+                                        | VCardProperty::Sound
+                                        | VCardProperty::Key
+                                );
+
                             if let Some(decoded) = params
                                 .charset
                                 .as_deref()
                                 .or(default_encoding)
+                                .filter(|_| !is_media)
                                 .and_then(|charset| {
                                     charset_decoder(charset.as_bytes())
                                         .map(|decoder| decoder(&bytes))
                                 })
                             {
                                 token.text = Cow::Owned(decoded.into_bytes());
-                            } else if std::str::from_utf8(&bytes).is_ok() {
+                            } else if !is_media && std::str::from_utf8(&bytes).is_ok() {
                                 token.text = Cow::Owned(bytes);
                             } else {
                                 entry.values.push(VCardValue::Binary(Data {
                                     data: bytes,
-                                    content_type: None,
+                                    content_type: entry.legacy_media_type(),
                                 }));
                                 if is_eol {
                                     break;
@@ -197,6 +234,12 @@ impl Parser<'_> {
                                 }
                             }
                         }
+                    } else if let Some(charset) = params.charset.as_deref()
+                        && std::str::from_utf8(&token.text).is_err()
+                        && let Some(decoder) = charset_decoder(charset.as_bytes())
+                    {
+                        // Values that are already valid UTF-8 are kept as-is
+                        token.text = Cow::Owned(decoder(&token.text).into_bytes());
                     }
 
                     let default_type = match &default_type {
@@ -237,7 +280,12 @@ impl Parser<'_> {
                         _ => &VCardValueType::Text,
                     };
 
-                    let value = match data_types.next().unwrap_or(&IanaType::Iana(*default_type)) {
+                    let token_start = token.start;
+                    let default_type = IanaType::Iana(*default_type);
+                    let data_type = data_types.next();
+                    let is_explicit = data_type.is_some();
+                    let data_type = data_type.unwrap_or(&default_type);
+                    let value = match data_type {
                         IanaType::Iana(value) => match value {
                             VCardValueType::Date if is_v4 => token
                                 .into_vcard_date()
@@ -309,6 +357,48 @@ impl Parser<'_> {
                         IanaType::Other(_) => VCardValue::Text(token.into_string()),
                     };
 
+                    if self.diagnostics.is_some()
+                        && let IanaType::Iana(data_type) = data_type
+                    {
+                        match (data_type, &value) {
+                            (
+                                VCardValueType::Date
+                                | VCardValueType::DateAndOrTime
+                                | VCardValueType::DateTime
+                                | VCardValueType::Time
+                                | VCardValueType::Timestamp,
+                                VCardValue::Text(_),
+                            ) => {
+                                self.add_diagnostic(
+                                    token_start,
+                                    ParseDiagnosticKind::MalformedDate,
+                                );
+                            }
+                            (
+                                VCardValueType::Date
+                                | VCardValueType::DateAndOrTime
+                                | VCardValueType::DateTime
+                                | VCardValueType::Time
+                                | VCardValueType::Timestamp,
+                                VCardValue::PartialDateTime(dt),
+                            ) if !dt.is_valid() => {
+                                self.add_diagnostic(
+                                    token_start,
+                                    ParseDiagnosticKind::MalformedDate,
+                                );
+                            }
+                            (
+                                VCardValueType::Integer
+                                | VCardValueType::Float
+                                | VCardValueType::UtcOffset,
+                                VCardValue::Text(_),
+                            ) if is_explicit => {
+                                self.add_diagnostic(token_start, ParseDiagnosticKind::InvalidValue);
+                            }
+                            _ => {}
+                        }
+                    }
+
                     if is_structured {
                         match (last_is_comma, entry.values.last_mut(), value) {
                             (
@@ -353,6 +443,7 @@ impl Parser<'_> {
                     }));
             }
 
+            self.add_span(0, vcard.entries.len(), line_offset);
             vcard.entries.push(entry);
         }
 
@@ -363,6 +454,39 @@ impl Parser<'_> {
         }
     }
 
+    // Some producers emit VERSION after other properties, so look ahead for it
+    // to avoid parsing the properties before it with the wrong version rules
+    fn vcard_is_v4(&mut self) -> bool {
+        let start = self.iter.peek().map_or(self.input.len(), |(pos, _)| *pos);
+        let mut depth = 0usize;
+
+        for line in self.input[start..].split(|ch| *ch == b'\n') {
+            if line.starts_with(b" ") || line.starts_with(b"\t") {
+                continue;
+            }
+            let Some((name, value)) = line
+                .iter()
+                .position(|ch| *ch == b':')
+                .map(|pos| (&line[..pos], line[pos + 1..].trim_ascii()))
+            else {
+                continue;
+            };
+
+            if name.eq_ignore_ascii_case(b"BEGIN") && value.eq_ignore_ascii_case(b"VCARD") {
+                depth += 1;
+            } else if name.eq_ignore_ascii_case(b"END") && value.eq_ignore_ascii_case(b"VCARD") {
+                if depth == 0 {
+                    break;
+                }
+                depth -= 1;
+            } else if depth == 0 && name.eq_ignore_ascii_case(b"VERSION") {
+                return !matches!(value.first(), Some(b'1'..=b'3'));
+            }
+        }
+
+        true
+    }
+
     fn vcard_parameters(&mut self, params: &mut Params) {
         while params.stop_char == StopChar::Semicolon {
             self.expect_iana_token();
@@ -839,7 +963,7 @@ impl PartialDateTime {
 
 #[cfg(test)]
 mod tests {
-    use crate::Entry;
+    use crate::{Entry, vcard::VCardVersion};
 
     use super::*;
     use std::io::Write;
@@ -1321,4 +1445,453 @@ mod tests {
             );
         }
     }
+
+    #[test]
+    fn test_whitespace_roundtrip() {
+        for version in [VCardVersion::V4_0, VCardVersion::V3_0] {
+            for note in [
+                "  leading spaces",
+                "trailing spaces  ",
+                "internal  double   spaces",
+                concat!(
+                    "a long note that is folded across several lines ",
+                    "     with runs of spaces landing near the fold boundary     ",
+                    "and again here      at the end  "
+                ),
+            ] {
+                let mut vcard = VCard {
+                    entries: vec![
+                        VCardEntry::new(VCardProperty::Note).with_value(note.to_string()),
+                    ],
+                };
+
+                for _ in 0..2 {
+                    let mut vcard_text = String::new();
+                    vcard.write_to(&mut vcard_text, version).unwrap();
+                    vcard = VCard::parse(&vcard_text).unwrap();
+                    assert_eq!(
+                        vcard
+                            .property(&VCardProperty::Note)
+                            .and_then(|entry| entry.values.first())
+                            .and_then(|value| value.as_text()),
+                        Some(note),
+                        "Failed to roundtrip {note:?}: {vcard_text}"
+                    );
+                }
+            }
+        }
+    }
+
+    #[test]
+    fn test_escaped_separators() {
+        for version in [VCardVersion::V4_0, VCardVersion::V3_0] {
+            let mut vcard = VCard::parse(concat!(
+                "BEGIN:VCARD\r\n",
+                "VERSION:4.0\r\n",
+                "NICKNAME:Jim\\, Jimmie,Jr\\;x,C:\\\\\r\n",
+                "ADR:;;1\\, Main\\; St;Town;;;\r\n",
+                "END:VCARD\r\n"
+            ))
+            .unwrap();
+
+            for _ in 0..2 {
+                assert_eq!(
+                    vcard.property(&VCardProperty::Nickname).unwrap().values,
+                    [
+                        VCardValue::Text("Jim, Jimmie".into()),
+                        VCardValue::Text("Jr;x".into()),
+                        VCardValue::Text("C:\\".into())
+                    ]
+                );
+                assert_eq!(
+                    vcard
+                        .property(&VCardProperty::Adr)
+                        .unwrap()
+                        .values
+                        .get(2)
+                        .and_then(|value| value.as_text()),
+                    Some("1, Main; St")
+                );
+
+                let mut vcard_text = String::new();
+                vcard.write_to(&mut vcard_text, version).unwrap();
+                vcard = VCard::parse(&vcard_text).unwrap();
+            }
+        }
+    }
+
+    #[test]
+    fn test_n_components() {
+        for (input, values, output) in [
+            (
+                "N:van Beethoven;Ludwig;;;;;Jr",
+                &["van Beethoven", "Ludwig", "", "", "", "", "Jr"][..],
+                "N:van Beethoven;Ludwig;;;;;Jr",
+            ),
+            (
+                "N:Doe;John;;;;;",
+                &["Doe", "John", "", "", "", "", ""][..],
+                "N:Doe;John;;;",
+            ),
+            (
+                "N:Doe;;;;;Sr;",
+                &["Doe", "", "", "", "", "Sr", ""][..],
+                "N:Doe;;;;;Sr",
+            ),
+            (
+                "N:Doe;John;;;",
+                &["Doe", "John", "", "", ""][..],
+                "N:Doe;John;;;",
+            ),
+        ] {
+            let vcard = VCard::parse(format!(
+                "BEGIN:VCARD\r\nVERSION:4.0\r\n{input}\r\nEND:VCARD\r\n"
+            ))
+            .unwrap();
+            assert_eq!(
+                vcard
+                    .property(&VCardProperty::N)
+                    .unwrap()
+                    .values
+                    .iter()
+                    .filter_map(|value| value.as_text())
+                    .collect::<Vec<_>>(),
+                values,
+                "{input}"
+            );
+            assert!(
+                vcard.to_string().contains(&format!("\r\n{output}\r\n")),
+                "{input}: {vcard}"
+            );
+        }
+    }
+
+    #[test]
+    fn test_parse_with_diagnostics() {
+        let input = concat!(
+            "BEGIN:VCARD\r\n",
+            "VERSION:4.0\r\n",
+            "FN:Jane Doe\r\n",
+            "BDAY:0000-00-00\r\n",
+            "ANNIVERSARY:not a date\r\n",
+            "FOO:bar\r\n",
+            "X-CUSTOM:value\r\n",
+            "GARBAGE\r\n",
+            "EMAIL;TYPE=\"work:jane@example.com\r\n",
+            "TZ;VALUE=utc-offset:abc\r\n",
+            "NOTE;VALUE=x-unknown:\r\n",
+            " folded note\r\n",
+            "END:VCARD\r\n"
+        );
+
+        let (vcard, diagnostics) = VCard::parse_with_diagnostics(input);
+        assert_eq!(
+            diagnostics
+                .iter()
+                .map(|d| (d.line_num, d.kind.clone()))
+                .collect::<Vec<_>>(),
+            vec![
+                (4, ParseDiagnosticKind::MalformedDate),
+                (5, ParseDiagnosticKind::MalformedDate),
+                (6, ParseDiagnosticKind::UnknownProperty("FOO".to_string())),
+                (8, ParseDiagnosticKind::MalformedLine),
+                (9, ParseDiagnosticKind::UnterminatedQuote),
+                (10, ParseDiagnosticKind::InvalidValue),
+                (
+                    11,
+                    ParseDiagnosticKind::UnknownValueType("x-unknown".to_string())
+                ),
+            ]
+        );
+        assert_eq!(diagnostics[0].line, "BDAY:0000-00-00");
+        assert_eq!(diagnostics[6].line, "NOTE;VALUE=x-unknown:folded note");
+
+        for property in [
+            VCardProperty::Fn,
+            VCardProperty::Bday,
+            VCardProperty::Other("X-CUSTOM".to_string()),
+            VCardProperty::Other("FOO".to_string()),
+            VCardProperty::Note,
+        ] {
+            assert!(vcard.property(&property).is_some(), "missing {property:?}");
+        }
+        assert_eq!(VCard::parse(input).unwrap(), vcard);
+    }
+
+    #[test]
+    fn test_legacy_charset() {
+        let mut input = Vec::new();
+        input.extend_from_slice(b"BEGIN:VCARD\r\nVERSION:2.1\r\n");
+        input.extend_from_slice(b"FN;CHARSET=ISO-8859-1:Jos\xe9 Mart\xednez\r\n");
+        input.extend_from_slice(b"ORG;CHARSET=ISO-8859-1:Caf\xe9 Ni\xf1o;Direcci\xf3n\r\n");
+        input.extend_from_slice("TITLE;CHARSET=ISO-8859-1:Gerente de cafés\r\n".as_bytes());
+        input.extend_from_slice("NOTE:Ünïcödé\r\n".as_bytes());
+        input.extend_from_slice(b"END:VCARD\r\n");
+
+        let vcard = VCard::parse_bytes(&input).unwrap();
+        let values = |property: VCardProperty| {
+            vcard
+                .property(&property)
+                .unwrap()
+                .values
+                .iter()
+                .filter_map(|value| value.as_text())
+                .collect::<Vec<_>>()
+        };
+
+        assert_eq!(values(VCardProperty::Fn), ["José Martínez"]);
+        assert_eq!(values(VCardProperty::Org), ["Café Niño", "Dirección"]);
+        assert_eq!(values(VCardProperty::Title), ["Gerente de cafés"]);
+        assert_eq!(values(VCardProperty::Note), ["Ünïcödé"]);
+    }
+
+    #[test]
+    fn test_late_version() {
+        let properties = concat!(
+            "FN:Jane Doe\r\n",
+            "BDAY:1980-01-01\r\n",
+            "REV:1995-10-31T22:27:10Z\r\n",
+        );
+        let late = VCard::parse(format!(
+            "BEGIN:VCARD\r\n{properties}VERSION:3.0\r\nEND:VCARD\r\n"
+        ))
+        .unwrap();
+        let early = VCard::parse(format!(
+            "BEGIN:VCARD\r\nVERSION:3.0\r\n{properties}END:VCARD\r\n"
+        ))
+        .unwrap();
+
+        assert_eq!(late.version(), Some(VCardVersion::V3_0));
+        for property in [VCardProperty::Bday, VCardProperty::Rev] {
+            assert_eq!(late.property(&property), early.property(&property));
+            assert!(
+                late.property(&property).unwrap().values[0]
+                    .as_partial_date_time()
+                    .is_some()
+            );
+        }
+        assert_eq!(late.to_string(), early.to_string());
+        assert!(
+            late.write_version(VCardVersion::V3_0)
+                .starts_with("BEGIN:VCARD\r\nVERSION:3.0\r\nFN:Jane Doe\r\n")
+        );
+    }
+
+    #[test]
+    fn test_write_version_3() {
+        let vcard = VCard::parse(concat!(
+            "BEGIN:VCARD\r\n",
+            "VERSION:4.0\r\n",
+            "KIND:individual\r\n",
+            "FN:Jane Doe\r\n",
+            "N;SORT-AS=\"Doe,Jane\":Doe;Jane;;Dr.;Jr.;Sec;III\r\n",
+            "ADR;TYPE=work;LABEL=\"123 Main St\\nTown\";PID=1.1:;;123 Main St;Town;ST;12345;USA;a;b\r\n",
+            "TEL;VALUE=uri;TYPE=cell,voice;PREF=1:tel:+1-555-0100\r\n",
+            "TEL;TYPE=home;PREF=2:+1-555-0101\r\n",
+            "EMAIL;PREF=3:jane@example.com\r\n",
+            "ANNIVERSARY:20090808\r\n",
+            "BDAY;VALUE=date-and-or-time:--0203\r\n",
+            "SOCIALPROFILE;SERVICE-TYPE=Mastodon:https://example.com/@jane\r\n",
+            "X-CUSTOM;X-PARAM=a:value\r\n",
+            "END:VCARD\r\n"
+        ))
+        .unwrap();
+
+        let v3 = vcard.write_version(VCardVersion::V3_0);
+        assert_eq!(
+            v3,
+            concat!(
+                "BEGIN:VCARD\r\n",
+                "VERSION:3.0\r\n",
+                "FN:Jane Doe\r\n",
+                "N:Doe;Jane;;Dr.;Jr.\r\n",
+                "ADR;TYPE=WORK:;;123 Main St;Town;ST;12345;USA\r\n",
+                "LABEL;TYPE=WORK:123 Main St\\nTown\r\n",
+                "TEL;TYPE=CELL,VOICE,PREF:+1-555-0100\r\n",
+                "TEL;TYPE=HOME:+1-555-0101\r\n",
+                "EMAIL;TYPE=PREF:jane@example.com\r\n",
+                "X-ANNIVERSARY;VALUE=DATE:2009-08-08\r\n",
+                "BDAY:--02-03\r\n",
+                "X-SOCIALPROFILE:https://example.com/@jane\r\n",
+                "X-CUSTOM;X-PARAM=a:value\r\n",
+                "END:VCARD\r\n"
+            )
+        );
+        assert!(VCard::parse(&v3).is_ok());
+        assert_eq!(vcard.write_version(VCardVersion::V4_0), vcard.to_string());
+    }
+
+    #[test]
+    fn test_agent_roundtrip() {
+        let agent = concat!(
+            r"AGENT:BEGIN:VCARD\nVERSION:3.0\nFN:Susan Thomas\nTEL:+1-919-555-1234\nEMAIL",
+            "\r\n ",
+            r"\;TYPE=INTERNET:sthomas@host.com\nN:Thomas\;Susan\;\;\;\nEND:VCARD\n",
+            "\r\n"
+        );
+        let input = format!(
+            concat!(
+                "BEGIN:VCARD\r\n",
+                "VERSION:3.0\r\n",
+                "FN:Jane Doe\r\n",
+                "{}",
+                "AGENT;VALUE=URI:CID:JQPUBLIC.part3.960129T083020.xyzMail@host3.com\r\n",
+                "END:VCARD\r\n"
+            ),
+            agent
+        );
+        let vcard = VCard::parse(&input).unwrap();
+        let agents = vcard.properties(&VCardProperty::Agent).collect::<Vec<_>>();
+        assert_eq!(agents.len(), 2);
+
+        let nested = agents[0].as_agent().unwrap();
+        assert_eq!(
+            nested
+                .property(&VCardProperty::Fn)
+                .and_then(|entry| entry.values.first())
+                .and_then(|value| value.as_text()),
+            Some("Susan Thomas")
+        );
+        assert_eq!(nested.property(&VCardProperty::N).unwrap().values.len(), 5);
+        assert!(agents[1].as_agent().is_none());
+        assert_eq!(
+            agents[1].values.first().and_then(|value| value.as_text()),
+            Some("CID:JQPUBLIC.part3.960129T083020.xyzMail@host3.com")
+        );
+        assert_eq!(vcard.to_string(), input);
+
+        let mut out = String::new();
+        VCardEntry::new(VCardProperty::Agent)
+            .with_agent(&nested)
+            .write_to(&mut out, false)
+            .unwrap();
+        assert_eq!(out, agent);
+    }
+
+    #[test]
+    fn test_other_name_case() {
+        let vcard = VCard::parse(concat!(
+            "BEGIN:VCARD\r\n",
+            "VERSION:4.0\r\n",
+            "x-foo;x-param=a:one\r\n",
+            "X-FOO;X-PARAM=b:two\r\n",
+            "END:VCARD\r\n"
+        ))
+        .unwrap();
+        let (lower, upper) = (&vcard.entries[1], &vcard.entries[2]);
+
+        assert_ne!(lower.name, upper.name);
+        assert!(lower.name.eq_ignore_ascii_case(&upper.name));
+        assert!(!lower.name.eq_ignore_ascii_case(&VCardProperty::Note));
+        assert!(
+            lower.params[0]
+                .name
+                .eq_ignore_ascii_case(&upper.params[0].name)
+        );
+        assert_eq!(lower.name.as_str(), "x-foo");
+    }
+
+    #[test]
+    fn test_name_roundtrip() {
+        macro_rules! roundtrip {
+            ($ty:ident, $($variant:ident),+ $(,)?) => {{
+                // Fails to build when a new variant is missing from the list
+                let _ = |name: &$ty| match name {
+                    $($ty::$variant)|+ | $ty::Other(_) => (),
+                };
+                for name in [$($ty::$variant),+] {
+                    assert_eq!($ty::from(name.as_str()), name);
+                    assert_eq!($ty::from(name.as_str().to_ascii_lowercase().as_str()), name);
+                }
+                assert_eq!($ty::from("X-UNKNOWN"), $ty::Other("X-UNKNOWN".to_string()));
+            }};
+        }
+
+        roundtrip!(
+            VCardProperty,
+            Source,
+            Kind,
+            Xml,
+            Fn,
+            N,
+            Nickname,
+            Photo,
+            Bday,
+            Anniversary,
+            Gender,
+            Adr,
+            Tel,
+            Email,
+            Impp,
+            Lang,
+            Tz,
+            Geo,
+            Title,
+            Role,
+            Logo,
+            Org,
+            Member,
+            Related,
+            Categories,
+            Note,
+            Prodid,
+            Rev,
+            Sound,
+            Uid,
+            Clientpidmap,
+            Url,
+            Version,
+            Key,
+            Fburl,
+            Caladruri,
+            Caluri,
+            Birthplace,
+            Deathplace,
+            Deathdate,
+            Expertise,
+            Hobby,
+            Interest,
+            OrgDirectory,
+            ContactUri,
+            Created,
+            Gramgender,
+            Language,
+            Pronouns,
+            Socialprofile,
+            Jsprop,
+            Agent,
+            Begin,
+            End
+        );
+        roundtrip!(
+            VCardParameterName,
+            Language,
+            Value,
+            Pref,
+            Altid,
+            Pid,
+            Type,
+            Mediatype,
+            Calscale,
+            SortAs,
+            Geo,
+            Tz,
+            Index,
+            Level,
+            Group,
+            Cc,
+            Author,
+            AuthorName,
+            Created,
+            Derived,
+            Label,
+            Phonetic,
+            PropId,
+            Script,
+            ServiceType,
+            Username,
+            Jsptr,
+            Jscomps
+        );
+    }
 }
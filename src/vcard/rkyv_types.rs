@@ -59,6 +59,7 @@ impl ArchivedVCardProperty {
             ArchivedVCardProperty::Pronouns => "PRONOUNS",
             ArchivedVCardProperty::Socialprofile => "SOCIALPROFILE",
             ArchivedVCardProperty::Jsprop => "JSPROP",
+            ArchivedVCardProperty::Agent => "AGENT",
             ArchivedVCardProperty::Begin => "BEGIN",
             ArchivedVCardProperty::End => "END",
             ArchivedVCardProperty::Other(s) => s.as_str(),
@@ -263,6 +264,10 @@ impl ArchivedVCardProperty {
                 ArchivedValueType::Vcard(ArchivedVCardValueType::Text),
                 ValueSeparator::None,
             ),
+            ArchivedVCardProperty::Agent => (
+                ArchivedValueType::Vcard(ArchivedVCardValueType::Text),
+                ValueSeparator::None,
+            ),
             ArchivedVCardProperty::Begin | ArchivedVCardProperty::End => (
                 ArchivedValueType::Vcard(ArchivedVCardValueType::Text),
                 ValueSeparator::Skip,
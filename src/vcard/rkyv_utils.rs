@@ -7,6 +7,21 @@
 use super::*;
 use crate::common::{ArchivedCalendarScale, ArchivedData, ArchivedPartialDateTime};
 
+// Cached bytes are validated before use, so corrupt input is an error rather than UB
+impl VCard {
+    pub fn to_rkyv_bytes(&self) -> Result<rkyv::util::AlignedVec, rkyv::rancor::Error> {
+        rkyv::to_bytes::<rkyv::rancor::Error>(self)
+    }
+
+    pub fn from_rkyv_bytes(bytes: &[u8]) -> Result<Self, rkyv::rancor::Error> {
+        rkyv::from_bytes::<VCard, rkyv::rancor::Error>(bytes)
+    }
+
+    pub fn access_rkyv_bytes(bytes: &[u8]) -> Result<&ArchivedVCard, rkyv::rancor::Error> {
+        rkyv::access::<ArchivedVCard, rkyv::rancor::Error>(bytes)
+    }
+}
+
 impl ArchivedVCard {
     pub fn uid(&self) -> Option<&str> {
         self.property(&VCardProperty::Uid)
@@ -55,6 +70,15 @@ impl ArchivedVCardEntry {
 }
 
 impl ArchivedVCardValue {
+    pub fn is_empty(&self) -> bool {
+        match self {
+            ArchivedVCardValue::Text(v) => v.is_empty(),
+            ArchivedVCardValue::Component(v) => v.iter().all(|s| s.is_empty()),
+            ArchivedVCardValue::Binary(v) => v.data.is_empty(),
+            _ => false,
+        }
+    }
+
     pub fn as_text(&self) -> Option<&str> {
         match self {
             ArchivedVCardValue::Text(s) => Some(s),
@@ -214,3 +238,32 @@ impl ArchivedVCardParameterValue {
         }
     }
 }
+
+#[cfg(test)]
+mod tests {
+    use crate::vcard::VCard;
+    use rkyv::util::AlignedVec;
+
+    #[test]
+    fn rkyv_bytes_roundtrip() {
+        let vcard = VCard::parse(concat!(
+            "BEGIN:VCARD\r\n",
+            "VERSION:4.0\r\n",
+            "UID:cached\r\n",
+            "FN:Jane Doe\r\n",
+            "END:VCARD\r\n"
+        ))
+        .unwrap();
+
+        let bytes = vcard.to_rkyv_bytes().unwrap();
+        assert_eq!(VCard::from_rkyv_bytes(&bytes).unwrap(), vcard);
+        let archived = VCard::access_rkyv_bytes(&bytes).unwrap();
+        assert_eq!(archived.uid(), Some("cached"));
+        assert_eq!(archived.to_string(), vcard.to_string());
+
+        let mut corrupt = AlignedVec::<16>::new();
+        corrupt.extend_from_slice(&bytes[..bytes.len() / 2]);
+        assert!(VCard::access_rkyv_bytes(&corrupt).is_err());
+        assert!(VCard::from_rkyv_bytes(&corrupt).is_err());
+    }
+}
@@ -7,7 +7,7 @@
 use crate::{
     common::{
         parser::Timestamp,
-        writer::{write_bytes, write_param_value, write_text},
+        writer::{DEFAULT_FOLD_WIDTH, write_bytes, write_param_value, write_text},
     },
     vcard::*,
 };
@@ -82,7 +82,7 @@ impl ArchivedVCardEntry {
 
             match &param.value {
                 ArchivedVCardParameterValue::Text(v) => {
-                    write_param_value(out, &mut line_len, v)?;
+                    write_param_value(out, &mut line_len, DEFAULT_FOLD_WIDTH, v)?;
                 }
                 ArchivedVCardParameterValue::Integer(i) => {
                     write!(out, "{}", i)?;
@@ -101,19 +101,19 @@ impl ArchivedVCardEntry {
                     if types.is_none() {
                         types = Some(v);
                     }
-                    write_param_value(out, &mut line_len, v.as_str())?;
+                    write_param_value(out, &mut line_len, DEFAULT_FOLD_WIDTH, v.as_str())?;
                 }
                 ArchivedVCardParameterValue::Type(v) => {
-                    write_param_value(out, &mut line_len, v.as_str())?;
+                    write_param_value(out, &mut line_len, DEFAULT_FOLD_WIDTH, v.as_str())?;
                 }
                 ArchivedVCardParameterValue::Calscale(v) => {
-                    write_param_value(out, &mut line_len, v.as_str())?;
+                    write_param_value(out, &mut line_len, DEFAULT_FOLD_WIDTH, v.as_str())?;
                 }
                 ArchivedVCardParameterValue::Level(v) => {
-                    write_param_value(out, &mut line_len, v.as_str())?;
+                    write_param_value(out, &mut line_len, DEFAULT_FOLD_WIDTH, v.as_str())?;
                 }
                 ArchivedVCardParameterValue::Phonetic(v) => {
-                    write_param_value(out, &mut line_len, v.as_str())?;
+                    write_param_value(out, &mut line_len, DEFAULT_FOLD_WIDTH, v.as_str())?;
                 }
                 ArchivedVCardParameterValue::Jscomps(v) => {
                     out.write_str("\"")?;
@@ -176,7 +176,21 @@ impl ArchivedVCardEntry {
                 _ => {}
             }
 
-            for (pos, value) in self.values.iter().enumerate() {
+            // Components past the five defined by RFC 6350 are only written when populated
+            let values = match self.name {
+                ArchivedVCardProperty::N => {
+                    let len = self
+                        .values
+                        .iter()
+                        .rposition(|value| !value.is_empty())
+                        .map_or(0, |pos| pos + 1)
+                        .max(5);
+                    &self.values[..len.min(self.values.len())]
+                }
+                _ => self.values.as_slice(),
+            };
+
+            for (pos, value) in values.iter().enumerate() {
                 if pos > 0 {
                     write!(out, "{separator}")?;
                     line_len += 1;
@@ -189,7 +203,14 @@ impl ArchivedVCardEntry {
 
                 match value {
                     ArchivedVCardValue::Text(v) => {
-                        write_text(out, &mut line_len, v, escape_semicolon, escape_comma)?;
+                        write_text(
+                            out,
+                            &mut line_len,
+                            DEFAULT_FOLD_WIDTH,
+                            v,
+                            escape_semicolon,
+                            escape_comma,
+                        )?;
                     }
                     ArchivedVCardValue::Component(v) => {
                         for (pos, item) in v.iter().enumerate() {
@@ -197,7 +218,7 @@ impl ArchivedVCardEntry {
                                 write!(out, ",")?;
                                 line_len += 1;
                             }
-                            write_text(out, &mut line_len, item, true, true)?;
+                            write_text(out, &mut line_len, DEFAULT_FOLD_WIDTH, item, true, true)?;
                         }
                     }
                     ArchivedVCardValue::Integer(v) => {
@@ -238,10 +259,10 @@ impl ArchivedVCardEntry {
                                 write!(out, "{ct};")?;
                                 line_len += ct.len() + 1;
                             }
-                            write!(out, "base64\\,")?;
-                            line_len += 8;
+                            write!(out, "base64,")?;
+                            line_len += 7;
                         }
-                        write_bytes(out, Some(&mut line_len), &v.data)?;
+                        write_bytes(out, Some(&mut line_len), DEFAULT_FOLD_WIDTH, &v.data)?;
                     }
                     ArchivedVCardValue::Sex(v) => {
                         let text = v.as_str();
@@ -633,4 +654,23 @@ mod tests {
         );
         assert_eq!(owned, archived_out);
     }
+
+    #[test]
+    fn archived_trims_n_components() {
+        let input = "BEGIN:VCARD\r\nVERSION:4.0\r\nN:Doe;John;;;;;\r\nEND:VCARD\r\n".to_string();
+        let mut parser = Parser::new(&input);
+        let Entry::VCard(vcard) = parser.entry() else {
+            panic!("expected vcard");
+        };
+
+        let bytes = rkyv::to_bytes::<rkyv::rancor::Error>(&vcard).unwrap();
+        let archived = rkyv::access::<ArchivedVCard, rkyv::rancor::Error>(&bytes).unwrap();
+        let mut archived_out = String::new();
+        archived
+            .write_to(&mut archived_out, VCardVersion::V4_0)
+            .unwrap();
+
+        assert!(archived_out.contains("\r\nN:Doe;John;;;\r\n"));
+        assert_eq!(vcard.to_string(), archived_out);
+    }
 }
@@ -63,6 +63,7 @@ impl IanaParse for VCardProperty {
             "PRONOUNS" => VCardProperty::Pronouns,
             "SOCIALPROFILE" => VCardProperty::Socialprofile,
             "JSPROP" => VCardProperty::Jsprop,
+            "AGENT" => VCardProperty::Agent,
         )
     }
 }
@@ -120,11 +121,24 @@ impl VCardProperty {
             VCardProperty::Pronouns => "PRONOUNS",
             VCardProperty::Socialprofile => "SOCIALPROFILE",
             VCardProperty::Jsprop => "JSPROP",
+            VCardProperty::Agent => "AGENT",
             VCardProperty::Begin => "BEGIN",
             VCardProperty::End => "END",
             VCardProperty::Other(v) => v.as_str(),
         }
     }
+
+    // Unknown names keep their original spelling so they can be written back as-is
+    pub fn eq_ignore_ascii_case(&self, other: &VCardProperty) -> bool {
+        self == other || self.as_str().eq_ignore_ascii_case(other.as_str())
+    }
+}
+
+impl From<&str> for VCardProperty {
+    fn from(value: &str) -> Self {
+        <VCardProperty as IanaParse>::parse(value.as_bytes())
+            .unwrap_or_else(|| VCardProperty::Other(value.to_string()))
+    }
 }
 
 impl VCardProperty {
@@ -240,6 +254,7 @@ impl VCardProperty {
                 (ValueType::Vcard(VCardValueType::Uri), ValueSeparator::None)
             }
             VCardProperty::Jsprop => (ValueType::Vcard(VCardValueType::Text), ValueSeparator::None),
+            VCardProperty::Agent => (ValueType::Vcard(VCardValueType::Text), ValueSeparator::None),
             VCardProperty::Other(_) => {
                 (ValueType::Vcard(VCardValueType::Text), ValueSeparator::None)
             }
@@ -529,6 +544,18 @@ impl VCardParameterName {
         Self::try_parse(input.as_bytes()).unwrap_or_else(|| VCardParameterName::Other(input.into()))
     }
 
+    pub fn canonical_value(&self, value: &str) -> Option<&'static str> {
+        let value = value.as_bytes();
+        match self {
+            VCardParameterName::Value => VCardValueType::parse(value).map(|v| v.as_str()),
+            VCardParameterName::Type => VCardType::parse(value).map(|v| v.as_str()),
+            VCardParameterName::Calscale => CalendarScale::parse(value).map(|v| v.as_str()),
+            VCardParameterName::Level => VCardLevel::parse(value).map(|v| v.as_str()),
+            VCardParameterName::Phonetic => VCardPhonetic::parse(value).map(|v| v.as_str()),
+            _ => None,
+        }
+    }
+
     pub fn as_str(&self) -> &str {
         match self {
             VCardParameterName::Language => "LANGUAGE",
@@ -562,6 +589,10 @@ impl VCardParameterName {
         }
     }
 
+    pub fn eq_ignore_ascii_case(&self, other: &VCardParameterName) -> bool {
+        self == other || self.as_str().eq_ignore_ascii_case(other.as_str())
+    }
+
     pub fn into_string(self) -> Cow<'static, str> {
         match self {
             VCardParameterName::Language => "LANGUAGE".into(),
@@ -596,6 +627,12 @@ impl VCardParameterName {
     }
 }
 
+impl From<&str> for VCardParameterName {
+    fn from(value: &str) -> Self {
+        VCardParameterName::parse(value)
+    }
+}
+
 impl VCardVersion {
     pub fn try_parse(input: &str) -> Option<Self> {
         hashify::tiny_map!(input.as_bytes(),
@@ -5,17 +5,74 @@
  */
 
 use super::{VCard, VCardEntry, VCardParameterName, VCardProperty, VCardValue, VCardVersion};
+#[cfg(feature = "datetime")]
+use crate::common::timezone::Tz;
 use crate::{
     common::{
-        CalendarScale, Data, IanaString, IanaType, PartialDateTime,
-        writer::{write_bytes, write_jscomps},
+        CalendarScale, Data, GeoUri, IanaString, IanaType, PartialDateTime, UtcOffset,
+        writer::{DEFAULT_FOLD_WIDTH, write_bytes, write_jscomps},
     },
     vcard::{
-        Jscomp, VCardLevel, VCardParameter, VCardParameterValue, VCardPhonetic, VCardType,
-        VCardValueType,
+        Jscomp, VCardGramGender, VCardKind, VCardLevel, VCardParameter, VCardParameterValue,
+        VCardPhonetic, VCardSex, VCardType, VCardValueType,
     },
 };
-use std::borrow::Cow;
+use ahash::AHashMap;
+#[cfg(feature = "datetime")]
+use chrono::FixedOffset;
+use std::borrow::{Borrow, Cow};
+#[cfg(feature = "datetime")]
+use std::str::FromStr;
+
+#[cfg(feature = "datetime")]
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum TzValue<'x> {
+    Offset(FixedOffset),
+    Iana(Tz),
+    Text(&'x str),
+}
+
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum VCardMedia<'x> {
+    Inline {
+        content_type: Option<&'x str>,
+        data: &'x [u8],
+    },
+    Uri {
+        uri: &'x str,
+        content_type: Option<&'x str>,
+    },
+}
+
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub struct StructuredName<'x> {
+    pub family: &'x [String],
+    pub given: &'x [String],
+    pub additional: &'x [String],
+    pub prefix: &'x [String],
+    pub suffix: &'x [String],
+}
+
+fn social_service(uri: &str) -> Option<(&'static str, &str)> {
+    let (scheme, address) = uri.split_once(':')?;
+    let service = match scheme.to_ascii_lowercase().as_str() {
+        "xmpp" => "XMPP",
+        "skype" => "Skype",
+        "sip" | "sips" => "SIP",
+        "facetime" => "FaceTime",
+        _ => return None,
+    };
+    let username = address
+        .trim_start_matches('/')
+        .split(['?', ';'])
+        .next()
+        .unwrap_or_default();
+    Some((service, username))
+}
+
+pub fn sort_by_pref<T: Borrow<VCardEntry>>(entries: &mut [T]) {
+    entries.sort_by_key(|entry| entry.borrow().pref().unwrap_or(u32::MAX));
+}
 
 impl VCard {
     pub fn uid(&self) -> Option<&str> {
@@ -35,6 +92,145 @@ impl VCard {
         self.entries.iter().filter(move |entry| &entry.name == prop)
     }
 
+    pub fn property_mut(&mut self, prop: &VCardProperty) -> Option<&mut VCardEntry> {
+        self.entries.iter_mut().find(|entry| &entry.name == prop)
+    }
+
+    pub fn properties_mut<'x, 'y: 'x>(
+        &'x mut self,
+        prop: &'y VCardProperty,
+    ) -> impl Iterator<Item = &'x mut VCardEntry> + 'x {
+        self.entries
+            .iter_mut()
+            .filter(move |entry| &entry.name == prop)
+    }
+
+    pub fn group<'x, 'y: 'x>(&'x self, name: &'y str) -> impl Iterator<Item = &'x VCardEntry> + 'x {
+        self.entries.iter().filter(move |entry| {
+            entry
+                .group
+                .as_deref()
+                .is_some_and(|group| group.eq_ignore_ascii_case(name))
+        })
+    }
+
+    pub fn groups(&self) -> impl Iterator<Item = (&str, Vec<&VCardEntry>)> {
+        let mut groups: Vec<(&str, Vec<&VCardEntry>)> = Vec::new();
+        for entry in &self.entries {
+            if let Some(name) = entry.group.as_deref() {
+                if let Some((_, entries)) = groups
+                    .iter_mut()
+                    .find(|(group, _)| group.eq_ignore_ascii_case(name))
+                {
+                    entries.push(entry);
+                } else {
+                    groups.push((name, vec![entry]));
+                }
+            }
+        }
+        groups.into_iter()
+    }
+
+    // ALTID values are scoped to the property name, entries without one stand alone
+    pub fn altid_groups(&self) -> impl Iterator<Item = (String, Vec<&VCardEntry>)> {
+        let mut groups: Vec<(String, Vec<&VCardEntry>)> = Vec::new();
+        for entry in &self.entries {
+            if let Some(alt_id) = entry.alt_id() {
+                if let Some((_, entries)) = groups.iter_mut().find(|(group, entries)| {
+                    group == alt_id && entries.first().is_some_and(|e| e.name == entry.name)
+                }) {
+                    entries.push(entry);
+                } else {
+                    groups.push((alt_id.to_string(), vec![entry]));
+                }
+            } else {
+                groups.push((String::new(), vec![entry]));
+            }
+        }
+        groups.into_iter()
+    }
+
+    pub fn new_group_name(&self) -> String {
+        (1..)
+            .map(|n| format!("item{n}"))
+            .find(|name| self.group(name).next().is_none())
+            .unwrap()
+    }
+
+    pub fn formatted_name(&self) -> Option<&str> {
+        self.properties_by_pref(&VCardProperty::Fn)
+            .find_map(|entry| entry.values.first()?.as_text())
+    }
+
+    pub fn emails(&self) -> impl Iterator<Item = (&str, Vec<&VCardType>)> {
+        self.properties_by_pref(&VCardProperty::Email)
+            .filter_map(|entry| Some((entry.values.first()?.as_text()?, entry.types())))
+    }
+
+    pub fn structured_name(&self) -> Option<StructuredName<'_>> {
+        let entry = self.properties_by_pref(&VCardProperty::N).next()?;
+        let component = |idx: usize| match entry.values.get(idx) {
+            Some(VCardValue::Text(text)) if !text.is_empty() => std::slice::from_ref(text),
+            Some(VCardValue::Component(items)) => items.as_slice(),
+            _ => &[],
+        };
+
+        Some(StructuredName {
+            family: component(0),
+            given: component(1),
+            additional: component(2),
+            prefix: component(3),
+            suffix: component(4),
+        })
+    }
+
+    pub fn preferred(&self, prop: &VCardProperty) -> Option<&VCardEntry> {
+        self.entries
+            .iter()
+            .filter(|entry| &entry.name == prop)
+            .min_by_key(|entry| entry.pref().unwrap_or(u32::MAX))
+    }
+
+    fn properties_by_pref<'x, 'y: 'x>(
+        &'x self,
+        prop: &'y VCardProperty,
+    ) -> impl Iterator<Item = &'x VCardEntry> + 'x {
+        let mut entries = self.properties(prop).collect::<Vec<_>>();
+        sort_by_pref(&mut entries);
+        entries.into_iter()
+    }
+
+    #[cfg(feature = "datetime")]
+    pub fn timezone(&self) -> Option<TzValue<'_>> {
+        match self.property(&VCardProperty::Tz)?.values.first()? {
+            VCardValue::PartialDateTime(dt) => dt.to_fixed_offset().map(TzValue::Offset),
+            VCardValue::Text(text) => Some(match Tz::from_str(text) {
+                Ok(Tz::Fixed(offset)) => TzValue::Offset(offset),
+                Ok(tz @ Tz::Tz(_)) => TzValue::Iana(tz),
+                _ => TzValue::Text(text),
+            }),
+            _ => None,
+        }
+    }
+
+    pub fn geo(&self) -> Option<(f64, f64)> {
+        let text = self
+            .property(&VCardProperty::Geo)?
+            .values
+            .first()?
+            .as_text()?;
+        GeoUri::parse(text)
+            .map(|geo| geo.coordinates())
+            .or_else(|| {
+                // vCard 3.0 and older
+                let (latitude, longitude) = text.split_once(';')?;
+                Some((
+                    latitude.trim().parse().ok()?,
+                    longitude.trim().parse().ok()?,
+                ))
+            })
+    }
+
     pub fn version(&self) -> Option<VCardVersion> {
         self.entries
             .iter()
@@ -47,12 +243,151 @@ impl VCard {
             })
     }
 
+    pub fn normalize_social_profiles(&mut self) {
+        for entry in &mut self.entries {
+            match &entry.name {
+                VCardProperty::Impp | VCardProperty::Socialprofile => {}
+                VCardProperty::Other(name) if name.eq_ignore_ascii_case("X-SOCIALPROFILE") => {
+                    // Apple Contacts stores the service in TYPE and the username in X-USER
+                    for param in &mut entry.params {
+                        match (&param.name, &param.value) {
+                            (VCardParameterName::Type, VCardParameterValue::Text(_)) => {
+                                param.name = VCardParameterName::ServiceType;
+                            }
+                            (VCardParameterName::Other(name), _)
+                                if name.eq_ignore_ascii_case("X-USER") =>
+                            {
+                                param.name = VCardParameterName::Username;
+                            }
+                            _ => {}
+                        }
+                    }
+                }
+                _ => continue,
+            }
+            entry.name = VCardProperty::Socialprofile;
+
+            if let Some((service, username)) = entry
+                .values
+                .first()
+                .and_then(|value| value.as_text())
+                .and_then(social_service)
+            {
+                let username = username.to_string();
+                if entry.parameter(&VCardParameterName::ServiceType).is_none() {
+                    entry.add_param(VCardParameter::service_type(service.to_string()));
+                }
+                if !username.is_empty() && entry.parameter(&VCardParameterName::Username).is_none()
+                {
+                    entry.add_param(VCardParameter::username(username));
+                }
+            }
+        }
+    }
+
+    pub fn pid_sources(&self) -> AHashMap<u32, (String, Option<String>)> {
+        self.properties(&VCardProperty::Clientpidmap)
+            .filter_map(|entry| {
+                let id = entry.values.first().and_then(pid_number)?;
+                let uri = entry.values.get(1)?.as_text()?;
+                let uuid = uri
+                    .get(..9)
+                    .filter(|prefix| prefix.eq_ignore_ascii_case("urn:uuid:"))
+                    .map(|_| uri[9..].to_string());
+                Some((id, (uri.to_string(), uuid)))
+            })
+            .collect()
+    }
+
+    // Returns the entry index and source id of every PID without a CLIENTPIDMAP
+    pub fn unmapped_pids(&self) -> Vec<(usize, u32)> {
+        let sources = self.pid_sources();
+        self.entries
+            .iter()
+            .enumerate()
+            .flat_map(|(idx, entry)| {
+                entry
+                    .pids()
+                    .filter_map(|(_, source)| source)
+                    .filter(|source| !sources.contains_key(source))
+                    .map(move |source| (idx, source))
+            })
+            .collect()
+    }
+
+    // Renumbers CLIENTPIDMAP source ids so they can be merged into `other`,
+    // sharing ids for identical source URIs and returning the old to new mapping
+    pub fn renumber_pids(&mut self, other: &VCard) -> AHashMap<u32, u32> {
+        let other_sources = other.pid_sources();
+        let mut next_id = other_sources.keys().max().map_or(1, |id| id + 1);
+        let mut sources = self.pid_sources().into_iter().collect::<Vec<_>>();
+        sources.sort_unstable_by_key(|(id, _)| *id);
+        let mapping = sources
+            .into_iter()
+            .map(|(id, (uri, _))| {
+                let new_id = other_sources
+                    .iter()
+                    .find(|(_, (other_uri, _))| other_uri == &uri)
+                    .map(|(other_id, _)| *other_id)
+                    .unwrap_or_else(|| {
+                        next_id += 1;
+                        next_id - 1
+                    });
+                (id, new_id)
+            })
+            .collect::<AHashMap<_, _>>();
+
+        for entry in &mut self.entries {
+            if entry.name == VCardProperty::Clientpidmap {
+                if let Some(value) = entry.values.first_mut()
+                    && let Some(new_id) = pid_number(value).and_then(|id| mapping.get(&id))
+                {
+                    *value = VCardValue::Text(new_id.to_string());
+                }
+                continue;
+            }
+
+            for param in &mut entry.params {
+                if param.name == VCardParameterName::Pid
+                    && let Some((local, Some(source))) = param.value.as_text().and_then(parse_pid)
+                    && let Some(new_id) = mapping.get(&source)
+                {
+                    param.value = VCardParameterValue::Text(format!("{local}.{new_id}"));
+                }
+            }
+        }
+
+        mapping
+    }
+
     pub fn size(&self) -> usize {
         self.entries.iter().map(|e| e.size()).sum()
     }
 }
 
+fn pid_number(value: &VCardValue) -> Option<u32> {
+    match value {
+        VCardValue::Integer(id) => u32::try_from(*id).ok(),
+        value => value.as_text()?.trim().parse().ok(),
+    }
+}
+
+fn parse_pid(value: &str) -> Option<(u32, Option<u32>)> {
+    match value.split_once('.') {
+        Some((local, source)) => Some((
+            local.trim().parse().ok()?,
+            Some(source.trim().parse().ok()?),
+        )),
+        None => Some((value.trim().parse().ok()?, None)),
+    }
+}
+
 impl VCardEntry {
+    pub fn pids(&self) -> impl Iterator<Item = (u32, Option<u32>)> + '_ {
+        self.parameters(&VCardParameterName::Pid)
+            .filter_map(|value| value.as_text().and_then(parse_pid))
+    }
+
     #[inline]
     pub fn parameters(
         &self,
@@ -67,6 +402,38 @@ impl VCardEntry {
         })
     }
 
+    #[inline]
+    pub fn parameter(&self, prop: &VCardParameterName) -> Option<&VCardParameterValue> {
+        self.params.iter().find_map(move |param| {
+            if &param.name == prop {
+                Some(&param.value)
+            } else {
+                None
+            }
+        })
+    }
+
+    pub(crate) fn legacy_media_type(&self) -> Option<String> {
+        let prefix = match self.name {
+            VCardProperty::Photo | VCardProperty::Logo => "image",
+            VCardProperty::Sound => "audio",
+            _ => return None,
+        };
+        self.parameters(&VCardParameterName::Type)
+            .find_map(|v| match v {
+                VCardParameterValue::Text(typ) => Some(typ),
+                _ => None,
+            })
+            .map(|typ| {
+                let typ = typ.to_ascii_lowercase();
+                if typ.contains('/') {
+                    typ
+                } else {
+                    format!("{prefix}/{typ}")
+                }
+            })
+    }
+
     pub fn language(&self) -> Option<&str> {
         self.parameters(&VCardParameterName::Language)
             .find_map(|v| v.as_text())
@@ -92,6 +459,58 @@ impl VCardEntry {
             .find_map(|v| v.as_text())
     }
 
+    pub fn pref(&self) -> Option<u32> {
+        self.parameters(&VCardParameterName::Pref)
+            .find_map(|v| v.as_integer()?.iana().copied())
+            .or_else(|| {
+                // vCard 3.0 marks preferred entries with TYPE=PREF
+                self.parameters(&VCardParameterName::Type)
+                    .any(|v| v.as_text().is_some_and(|v| v.eq_ignore_ascii_case("pref")))
+                    .then_some(1)
+            })
+    }
+
+    pub fn types(&self) -> Vec<&VCardType> {
+        self.parameters(&VCardParameterName::Type)
+            .filter_map(|v| v.as_type()?.iana().copied())
+            .collect()
+    }
+
+    pub fn media_type(&self) -> Option<&str> {
+        self.parameters(&VCardParameterName::Mediatype)
+            .find_map(|v| v.as_text())
+    }
+
+    pub fn as_media(&self) -> Option<VCardMedia<'_>> {
+        match self.values.first()? {
+            VCardValue::Binary(data) => Some(VCardMedia::Inline {
+                content_type: data.content_type.as_deref().or_else(|| self.media_type()),
+                data: &data.data,
+            }),
+            VCardValue::Text(uri) if uri.contains(':') => Some(VCardMedia::Uri {
+                uri,
+                content_type: self.media_type(),
+            }),
+            _ => None,
+        }
+    }
+
+    pub fn as_agent(&self) -> Option<VCard> {
+        if self.name != VCardProperty::Agent || self.is_type(&VCardValueType::Uri) {
+            return None;
+        }
+
+        self.values
+            .first()?
+            .as_text()
+            .filter(|text| {
+                text.trim_start()
+                    .get(..11)
+                    .is_some_and(|begin| begin.eq_ignore_ascii_case("BEGIN:VCARD"))
+            })
+            .and_then(|text| VCard::parse(text).ok())
+    }
+
     pub fn size(&self) -> usize {
         self.group.as_ref().map_or(0, |g| g.len())
             + self.name.as_str().len()
@@ -101,6 +520,15 @@ impl VCardEntry {
 }
 
 impl VCardValue {
+    pub fn is_empty(&self) -> bool {
+        match self {
+            VCardValue::Text(v) => v.is_empty(),
+            VCardValue::Component(v) => v.iter().all(|s| s.is_empty()),
+            VCardValue::Binary(v) => v.data.is_empty(),
+            _ => false,
+        }
+    }
+
     pub fn as_text(&self) -> Option<&str> {
         match self {
             VCardValue::Text(v) => v.as_str().into(),
@@ -167,6 +595,18 @@ impl VCardValue {
         }
     }
 
+    #[inline]
+    pub fn as_date_time(&self) -> Option<&PartialDateTime> {
+        self.as_partial_date_time()
+    }
+
+    pub fn as_utc_offset(&self) -> Option<UtcOffset> {
+        match self {
+            VCardValue::PartialDateTime(dt) if !dt.has_date() && !dt.has_time() => dt.utc_offset(),
+            _ => None,
+        }
+    }
+
     pub fn as_binary(&self) -> Option<&Data> {
         match self {
             VCardValue::Binary(d) => Some(d),
@@ -174,6 +614,34 @@ impl VCardValue {
         }
     }
 
+    pub fn as_component(&self) -> Option<&[String]> {
+        match self {
+            VCardValue::Component(v) => Some(v.as_slice()),
+            _ => None,
+        }
+    }
+
+    pub fn as_kind(&self) -> Option<&VCardKind> {
+        match self {
+            VCardValue::Kind(v) => Some(v),
+            _ => None,
+        }
+    }
+
+    pub fn as_sex(&self) -> Option<&VCardSex> {
+        match self {
+            VCardValue::Sex(v) => Some(v),
+            _ => None,
+        }
+    }
+
+    pub fn as_gram_gender(&self) -> Option<&VCardGramGender> {
+        match self {
+            VCardValue::GramGender(v) => Some(v),
+            _ => None,
+        }
+    }
+
     pub fn size(&self) -> usize {
         match self {
             VCardValue::Text(v) => v.len(),
@@ -194,6 +662,10 @@ impl VCardValue {
 
 impl Data {
     pub fn to_unwrapped_string(&self) -> String {
+        self.to_data_uri()
+    }
+
+    pub fn to_data_uri(&self) -> String {
         use std::fmt::Write;
         let mut out = String::with_capacity(
             self.data.len().div_ceil(4) + self.content_type.as_ref().map_or(0, |ct| ct.len() + 5),
@@ -203,7 +675,7 @@ impl Data {
             let _ = write!(&mut out, "{ct};");
         }
         let _ = write!(&mut out, "base64,");
-        let _ = write_bytes(&mut out, None, &self.data);
+        let _ = write_bytes(&mut out, None, DEFAULT_FOLD_WIDTH, &self.data);
         out
     }
 }
@@ -395,3 +867,509 @@ impl Jscomp {
         }
     }
 }
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    #[cfg(feature = "datetime")]
+    fn timezone() {
+        for (tz, expected) in [
+            (
+                "TZ:-0500",
+                TzValue::Offset(FixedOffset::west_opt(5 * 3600).unwrap()),
+            ),
+            (
+                "TZ;VALUE=utc-offset:+0530",
+                TzValue::Offset(FixedOffset::east_opt(5 * 3600 + 30 * 60).unwrap()),
+            ),
+            (
+                "TZ:-03:30",
+                TzValue::Offset(FixedOffset::west_opt(3 * 3600 + 30 * 60).unwrap()),
+            ),
+            (
+                "TZ:America/New_York",
+                TzValue::Iana(Tz::Tz(chrono_tz::America::New_York)),
+            ),
+            (
+                "TZ;VALUE=text:Raleigh/North America",
+                TzValue::Text("Raleigh/North America"),
+            ),
+        ] {
+            let vcard = VCard::parse(format!(
+                "BEGIN:VCARD\r\nVERSION:4.0\r\n{tz}\r\nEND:VCARD\r\n"
+            ))
+            .unwrap();
+            assert_eq!(vcard.timezone(), Some(expected), "failed for {tz}");
+        }
+
+        let vcard =
+            VCard::parse("BEGIN:VCARD\r\nVERSION:3.0\r\nTZ:-05:00\r\nEND:VCARD\r\n").unwrap();
+        assert_eq!(
+            vcard.timezone(),
+            Some(TzValue::Offset(FixedOffset::west_opt(5 * 3600).unwrap()))
+        );
+    }
+
+    #[test]
+    fn as_media() {
+        let vcard = VCard::parse(concat!(
+            "BEGIN:VCARD\r\n",
+            "VERSION:4.0\r\n",
+            "PHOTO;MEDIATYPE=image/png:https://example.com/photo.png\r\n",
+            "PHOTO:https://example.com/photo.jpg\r\n",
+            "PHOTO;MEDIATYPE=image/png:data:image/jpeg;base64,aGVsbG8=\r\n",
+            "LOGO;MEDIATYPE=image/gif:data:;base64,aGVsbG8=\r\n",
+            "END:VCARD\r\n"
+        ))
+        .unwrap();
+
+        assert_eq!(
+            vcard
+                .entries
+                .iter()
+                .filter_map(|entry| entry.as_media())
+                .collect::<Vec<_>>(),
+            vec![
+                VCardMedia::Uri {
+                    uri: "https://example.com/photo.png",
+                    content_type: Some("image/png"),
+                },
+                VCardMedia::Uri {
+                    uri: "https://example.com/photo.jpg",
+                    content_type: None,
+                },
+                VCardMedia::Inline {
+                    content_type: Some("image/jpeg"),
+                    data: b"hello",
+                },
+                VCardMedia::Inline {
+                    content_type: Some("image/gif"),
+                    data: b"hello",
+                },
+            ]
+        );
+    }
+
+    #[test]
+    fn typed_accessors() {
+        let vcard = VCard::parse(concat!(
+            "BEGIN:VCARD\r\n",
+            "VERSION:4.0\r\n",
+            "FN;ALTID=1;LANGUAGE=ja;PREF=2:ジェーン・ドウ\r\n",
+            "FN;ALTID=1;LANGUAGE=en;PREF=1:Jane Doe\r\n",
+            "N:Doe,Smith;Jane;Mary,Ann;Dr.;\r\n",
+            "EMAIL;TYPE=home:jane@home.example.com\r\n",
+            "EMAIL;TYPE=work;PREF=1:jane@work.example.com\r\n",
+            "EMAIL;TYPE=work,home;PREF=50:jane@shared.example.com\r\n",
+            "END:VCARD\r\n"
+        ))
+        .unwrap();
+
+        assert_eq!(vcard.formatted_name(), Some("Jane Doe"));
+        assert_eq!(
+            vcard.emails().collect::<Vec<_>>(),
+            vec![
+                ("jane@work.example.com", vec![&VCardType::Work]),
+                (
+                    "jane@shared.example.com",
+                    vec![&VCardType::Work, &VCardType::Home]
+                ),
+                ("jane@home.example.com", vec![&VCardType::Home]),
+            ]
+        );
+
+        let name = vcard.structured_name().unwrap();
+        assert_eq!(name.family, ["Doe", "Smith"]);
+        assert_eq!(name.given, ["Jane"]);
+        assert_eq!(name.additional, ["Mary", "Ann"]);
+        assert_eq!(name.prefix, ["Dr."]);
+        assert!(name.suffix.is_empty());
+
+        let vcard = VCard::parse(concat!(
+            "BEGIN:VCARD\r\n",
+            "VERSION:3.0\r\n",
+            "EMAIL;TYPE=INTERNET:first@example.com\r\n",
+            "EMAIL;TYPE=INTERNET,PREF:second@example.com\r\n",
+            "END:VCARD\r\n"
+        ))
+        .unwrap();
+        assert_eq!(vcard.formatted_name(), None);
+        assert_eq!(vcard.structured_name(), None);
+        assert_eq!(
+            vcard.emails().map(|(email, _)| email).collect::<Vec<_>>(),
+            vec!["second@example.com", "first@example.com"]
+        );
+    }
+
+    #[test]
+    fn set_and_remove_params() {
+        let mut entry = VCardEntry::new(VCardProperty::Email)
+            .with_param(VCardParameter::pref(1u32))
+            .with_param(VCardParameter::new(
+                VCardParameterName::Other("X-SOURCE".to_string()),
+                "import".to_string(),
+            ))
+            .with_value("jane@example.com".to_string());
+
+        entry.set_param(VCardParameterName::Pref, 2u32);
+        entry.remove_param(&VCardParameterName::Other("X-SOURCE".to_string()));
+        assert_eq!(
+            entry.parameter(&VCardParameterName::Pref),
+            Some(&VCardParameterValue::Integer(2))
+        );
+        assert_eq!(entry.params.len(), 1);
+    }
+
+    #[test]
+    fn inline_media() {
+        let data = Data::from_data_uri("data:image/jpeg;base64,SGVsbG8=").unwrap();
+        assert_eq!(data.content_type.as_deref(), Some("image/jpeg"));
+        assert_eq!(data.data, b"Hello");
+        assert_eq!(data.to_data_uri(), "data:image/jpeg;base64,SGVsbG8=");
+        assert_eq!(Data::from_data_uri("https://example.com/photo.jpg"), None);
+
+        for (input, expected) in [
+            (
+                "BEGIN:VCARD\r\nVERSION:4.0\r\nPHOTO:data:image/jpeg;base64,SGVsbG8=\r\nEND:VCARD\r\n",
+                "PHOTO:data:image/jpeg;base64,SGVsbG8=\r\n",
+            ),
+            (
+                "BEGIN:VCARD\r\nVERSION:3.0\r\nPHOTO;ENCODING=b;TYPE=JPEG:SGVsbG8=\r\nEND:VCARD\r\n",
+                "PHOTO;TYPE=JPEG:data:image/jpeg;base64,SGVsbG8=\r\n",
+            ),
+        ] {
+            let vcard = VCard::parse(input).unwrap();
+            let photo = vcard.property(&VCardProperty::Photo).unwrap();
+            assert_eq!(
+                photo.values.first().and_then(|v| v.as_binary()),
+                Some(&data)
+            );
+
+            let mut out = String::new();
+            photo.write_to(&mut out, true).unwrap();
+            assert_eq!(out, expected);
+        }
+    }
+
+    #[test]
+    fn properties_mut() {
+        let mut vcard = VCard::parse(concat!(
+            "BEGIN:VCARD\r\n",
+            "VERSION:4.0\r\n",
+            "CATEGORIES:work,friends\r\n",
+            "X-TAG:one\r\n",
+            "X-LABEL:two\r\n",
+            "CATEGORIES:family\r\n",
+            "END:VCARD\r\n"
+        ))
+        .unwrap();
+
+        for entry in vcard.properties_mut(&VCardProperty::Categories) {
+            for value in &mut entry.values {
+                if let VCardValue::Text(text) = value {
+                    *text = text.to_uppercase();
+                }
+            }
+        }
+        if let Some(entry) = vcard.property_mut(&VCardProperty::Other("X-TAG".to_string())) {
+            entry.values = vec![VCardValue::Text("ONE".to_string())];
+        }
+
+        assert_eq!(
+            vcard
+                .properties(&VCardProperty::Categories)
+                .flat_map(|entry| entry.values.iter().filter_map(|v| v.as_text()))
+                .collect::<Vec<_>>(),
+            ["WORK", "FRIENDS", "FAMILY"]
+        );
+        assert_eq!(
+            vcard
+                .properties(&VCardProperty::Other("X-TAG".to_string()))
+                .count(),
+            1
+        );
+        assert_eq!(
+            vcard
+                .property(&VCardProperty::Other("X-LABEL".to_string()))
+                .and_then(|entry| entry.values.first()?.as_text()),
+            Some("two")
+        );
+        assert_eq!(
+            vcard
+                .property(&VCardProperty::Other("X-TAG".to_string()))
+                .and_then(|entry| entry.values.first()?.as_text()),
+            Some("ONE")
+        );
+    }
+
+    #[test]
+    fn groups() {
+        let vcard = VCard::parse(concat!(
+            "BEGIN:VCARD\r\n",
+            "VERSION:3.0\r\n",
+            "FN:Jane Doe\r\n",
+            "item1.TEL:+1-555-0100\r\n",
+            "item2.EMAIL:jane@example.com\r\n",
+            "ITEM1.X-ABLabel:_$!<Mobile>!$_\r\n",
+            "item2.X-ABLabel:Work\r\n",
+            "item3.URL:https://example.com\r\n",
+            "END:VCARD\r\n"
+        ))
+        .unwrap();
+
+        assert_eq!(
+            vcard
+                .group("item1")
+                .map(|entry| entry.name.as_str())
+                .collect::<Vec<_>>(),
+            ["TEL", "X-ABLabel"]
+        );
+        assert_eq!(
+            vcard
+                .groups()
+                .map(|(name, entries)| (name, entries.len()))
+                .collect::<Vec<_>>(),
+            [("item1", 2), ("item2", 2), ("item3", 1)]
+        );
+        assert_eq!(vcard.new_group_name(), "item4");
+        assert_eq!(VCard::default().new_group_name(), "item1");
+    }
+
+    #[test]
+    fn normalize_social_profiles() {
+        let mut vcard = VCard::parse(concat!(
+            "BEGIN:VCARD\r\n",
+            "VERSION:3.0\r\n",
+            "IMPP:xmpp:alice@example.com\r\n",
+            "IMPP:skype:alice.smith?call\r\n",
+            "X-SOCIALPROFILE;TYPE=twitter;X-USER=alice:https://twitter.com/alice\r\n",
+            "SOCIALPROFILE;SERVICE-TYPE=Jabber:xmpp:alice@example.org\r\n",
+            "IMPP:gg:12345\r\n",
+            "END:VCARD\r\n"
+        ))
+        .unwrap();
+        vcard.normalize_social_profiles();
+
+        assert_eq!(
+            vcard
+                .properties(&VCardProperty::Socialprofile)
+                .map(|entry| (
+                    entry
+                        .parameter(&VCardParameterName::ServiceType)
+                        .and_then(|v| v.as_text()),
+                    entry
+                        .parameter(&VCardParameterName::Username)
+                        .and_then(|v| v.as_text()),
+                    entry.values.first().and_then(|v| v.as_text()).unwrap(),
+                ))
+                .collect::<Vec<_>>(),
+            [
+                (
+                    Some("XMPP"),
+                    Some("alice@example.com"),
+                    "xmpp:alice@example.com"
+                ),
+                (Some("Skype"), Some("alice.smith"), "skype:alice.smith?call"),
+                (Some("twitter"), Some("alice"), "https://twitter.com/alice"),
+                (
+                    Some("Jabber"),
+                    Some("alice@example.org"),
+                    "xmpp:alice@example.org"
+                ),
+                (None, None, "gg:12345"),
+            ]
+        );
+        assert_eq!(vcard.properties(&VCardProperty::Impp).count(), 0);
+    }
+
+    #[test]
+    fn preferred_entries() {
+        let vcard = VCard::parse(concat!(
+            "BEGIN:VCARD\r\n",
+            "VERSION:4.0\r\n",
+            "EMAIL:a@example.com\r\n",
+            "EMAIL;PREF=2:b@example.com\r\n",
+            "EMAIL;PREF=1:c@example.com\r\n",
+            "EMAIL;PREF=2:d@example.com\r\n",
+            "TEL:+1-555-0100\r\n",
+            "TEL:+1-555-0101\r\n",
+            "END:VCARD\r\n"
+        ))
+        .unwrap();
+        let value = |entry: &VCardEntry| entry.values[0].as_text().unwrap().to_string();
+
+        assert_eq!(
+            vcard.preferred(&VCardProperty::Email).map(value),
+            Some("c@example.com".to_string())
+        );
+        assert_eq!(
+            vcard.preferred(&VCardProperty::Tel).map(value),
+            Some("+1-555-0100".to_string())
+        );
+        assert!(vcard.preferred(&VCardProperty::Adr).is_none());
+
+        let mut entries = vcard
+            .properties(&VCardProperty::Email)
+            .cloned()
+            .collect::<Vec<_>>();
+        sort_by_pref(&mut entries);
+        assert_eq!(
+            entries.iter().map(value).collect::<Vec<_>>(),
+            [
+                "c@example.com",
+                "b@example.com",
+                "d@example.com",
+                "a@example.com"
+            ]
+        );
+    }
+
+    #[test]
+    fn altid_groups() {
+        let vcard = VCard::parse(concat!(
+            "BEGIN:VCARD\r\n",
+            "VERSION:4.0\r\n",
+            "FN;ALTID=1;LANGUAGE=en:Taro Yamada\r\n",
+            "FN;ALTID=1;LANGUAGE=ja:山田太郎\r\n",
+            "TITLE;ALTID=1;LANGUAGE=en:Engineer\r\n",
+            "NOTE:Hello\r\n",
+            "END:VCARD\r\n"
+        ))
+        .unwrap();
+
+        assert_eq!(
+            vcard
+                .altid_groups()
+                .map(|(alt_id, entries)| (
+                    alt_id,
+                    entries[0].name.clone(),
+                    entries
+                        .iter()
+                        .map(|entry| entry.language())
+                        .collect::<Vec<_>>()
+                ))
+                .collect::<Vec<_>>(),
+            [
+                (String::new(), VCardProperty::Version, vec![None]),
+                (
+                    "1".to_string(),
+                    VCardProperty::Fn,
+                    vec![Some("en"), Some("ja")]
+                ),
+                ("1".to_string(), VCardProperty::Title, vec![Some("en")]),
+                (String::new(), VCardProperty::Note, vec![None]),
+            ]
+        );
+    }
+
+    #[test]
+    fn value_accessors() {
+        let vcard = VCard::parse(concat!(
+            "BEGIN:VCARD\r\n",
+            "VERSION:4.0\r\n",
+            "KIND:individual\r\n",
+            "N:Public;John,Jack;;;\r\n",
+            "GENDER:M\r\n",
+            "BDAY:19960415\r\n",
+            "X-COUNT;VALUE=INTEGER:42\r\n",
+            "TZ;VALUE=utc-offset:-0500\r\n",
+            "END:VCARD\r\n"
+        ))
+        .unwrap();
+        let value = |prop: VCardProperty| &vcard.property(&prop).unwrap().values[0];
+
+        assert_eq!(
+            value(VCardProperty::Kind).as_kind(),
+            Some(&VCardKind::Individual)
+        );
+        assert_eq!(
+            vcard.property(&VCardProperty::N).unwrap().values[1].as_component(),
+            Some(&["John".to_string(), "Jack".to_string()][..])
+        );
+        assert_eq!(value(VCardProperty::Gender).as_sex(), Some(&VCardSex::Male));
+        assert_eq!(
+            value(VCardProperty::Bday)
+                .as_date_time()
+                .and_then(|dt| dt.year),
+            Some(1996)
+        );
+        assert_eq!(
+            value(VCardProperty::Other("X-COUNT".to_string())).as_integer(),
+            Some(42)
+        );
+        assert_eq!(
+            value(VCardProperty::Tz)
+                .as_utc_offset()
+                .map(|offset| offset.to_seconds()),
+            Some(-18000)
+        );
+        assert!(value(VCardProperty::Bday).as_utc_offset().is_none());
+        assert!(value(VCardProperty::Kind).as_binary().is_none());
+        assert!(value(VCardProperty::N).as_float().is_none());
+    }
+
+    #[test]
+    fn pid_mapping() {
+        let card = |input: &str| VCard::parse(input).unwrap();
+        let mut local = card(concat!(
+            "BEGIN:VCARD\r\n",
+            "VERSION:4.0\r\n",
+            "CLIENTPIDMAP:1;urn:uuid:3df403f4-5924-4bb7-b077-3c711d9eb34b\r\n",
+            "CLIENTPIDMAP:2;http://example.com/sync\r\n",
+            "EMAIL;PID=1.1,2.2:jane@example.com\r\n",
+            "TEL;PID=3.3:+1-555-0100\r\n",
+            "NOTE;PID=4:No source\r\n",
+            "END:VCARD\r\n"
+        ));
+        let remote = card(concat!(
+            "BEGIN:VCARD\r\n",
+            "VERSION:4.0\r\n",
+            "CLIENTPIDMAP:1;http://example.com/sync\r\n",
+            "CLIENTPIDMAP:5;urn:uuid:0a1b2c3d-0000-4000-8000-000000000000\r\n",
+            "EMAIL;PID=1.1:jane@example.com\r\n",
+            "END:VCARD\r\n"
+        ));
+
+        let sources = local.pid_sources();
+        assert_eq!(
+            sources.get(&1),
+            Some(&(
+                "urn:uuid:3df403f4-5924-4bb7-b077-3c711d9eb34b".to_string(),
+                Some("3df403f4-5924-4bb7-b077-3c711d9eb34b".to_string())
+            ))
+        );
+        assert_eq!(
+            sources.get(&2),
+            Some(&("http://example.com/sync".to_string(), None))
+        );
+        assert_eq!(local.unmapped_pids(), [(4, 3)]);
+        assert!(remote.unmapped_pids().is_empty());
+
+        // The shared sync source reuses the remote id, the other one gets a fresh id
+        let mapping = local.renumber_pids(&remote);
+        assert_eq!(mapping.get(&1), Some(&6));
+        assert_eq!(mapping.get(&2), Some(&1));
+        assert_eq!(
+            local.entries[3].pids().collect::<Vec<_>>(),
+            [(1, Some(6)), (2, Some(1))]
+        );
+        assert_eq!(local.entries[4].pids().collect::<Vec<_>>(), [(3, Some(3))]);
+        assert_eq!(local.entries[5].pids().collect::<Vec<_>>(), [(4, None)]);
+        assert_eq!(
+            local
+                .pid_sources()
+                .into_iter()
+                .map(|(id, (uri, _))| (id, uri))
+                .collect::<AHashMap<_, _>>(),
+            AHashMap::from_iter([
+                (
+                    6,
+                    "urn:uuid:3df403f4-5924-4bb7-b077-3c711d9eb34b".to_string()
+                ),
+                (1, "http://example.com/sync".to_string()),
+            ])
+        );
+    }
+}
@@ -9,23 +9,52 @@ use crate::{
     common::{
         IanaString,
         parser::Timestamp,
-        writer::{write_bytes, write_jscomps, write_param_value, write_text},
+        writer::{
+            WriterOptions, write_bytes, write_io, write_jscomps, write_param_value, write_text,
+        },
     },
-    vcard::{VCardParameterName, VCardParameterValue, VCardProperty, VCardValue, ValueSeparator},
+    vcard::{
+        VCardParameter, VCardParameterName, VCardParameterValue, VCardProperty, VCardValue,
+        ValueSeparator,
+    },
+};
+use std::{
+    fmt::{Display, Write},
+    io,
 };
-use std::fmt::{Display, Write};
 
 impl VCard {
     pub fn write_to(&self, out: &mut impl Write, version: VCardVersion) -> std::fmt::Result {
+        self.write_with_options(out, version, &WriterOptions::default())
+    }
+
+    pub fn write_to_io<W: io::Write>(&self, out: &mut W) -> io::Result<()> {
+        write_io(out, |out| {
+            self.write_to(out, self.version().unwrap_or_default())
+        })
+    }
+
+    pub fn write_with_options(
+        &self,
+        out: &mut impl Write,
+        version: VCardVersion,
+        options: &WriterOptions,
+    ) -> std::fmt::Result {
         write!(out, "BEGIN:VCARD\r\n")?;
         write!(out, "VERSION:{version}\r\n")?;
         let is_v4 = matches!(version, VCardVersion::V4_0);
+        if let Some(prodid) = &options.prodid {
+            VCardEntry::new(VCardProperty::Prodid)
+                .with_value(prodid.to_string())
+                .write_with_options(out, is_v4, options)?;
+        }
         for entry in &self.entries {
             if !matches!(
                 entry.name,
                 VCardProperty::Begin | VCardProperty::End | VCardProperty::Version
-            ) {
-                entry.write_to(out, is_v4)?;
+            ) && (options.prodid.is_none() || entry.name != VCardProperty::Prodid)
+            {
+                entry.write_with_options(out, is_v4, options)?;
             }
         }
         write!(out, "END:VCARD\r\n")
@@ -34,7 +63,17 @@ impl VCard {
 
 impl VCardEntry {
     pub fn write_to(&self, out: &mut impl Write, is_v4: bool) -> std::fmt::Result {
+        self.write_with_options(out, is_v4, &WriterOptions::default())
+    }
+
+    pub fn write_with_options(
+        &self,
+        out: &mut impl Write,
+        is_v4: bool,
+        options: &WriterOptions,
+    ) -> std::fmt::Result {
         let mut line_len = 0;
+        let fold_width = options.max_line_len();
 
         if let Some(group_name) = &self.group {
             write!(out, "{group_name}.")?;
@@ -52,7 +91,7 @@ impl VCardEntry {
                 write!(out, ",")?;
                 line_len += 1;
 
-                if line_len + 1 > 75 {
+                if line_len + 1 > fold_width {
                     write!(out, "\r\n ")?;
                     line_len = 1;
                 }
@@ -61,7 +100,7 @@ impl VCardEntry {
                 line_len += 1;
                 let name = param.name.as_str();
                 let need_len = name.len() + 1;
-                if line_len + need_len > 75 {
+                if line_len + need_len > fold_width {
                     write!(out, "\r\n ")?;
                     line_len = 1;
                 }
@@ -76,7 +115,12 @@ impl VCardEntry {
 
             match &param.value {
                 VCardParameterValue::Text(v) => {
-                    write_param_value(out, &mut line_len, v)?;
+                    let v = if options.canonical_param_case {
+                        param.name.canonical_value(v).unwrap_or(v)
+                    } else {
+                        v
+                    };
+                    write_param_value(out, &mut line_len, fold_width, v)?;
                 }
                 VCardParameterValue::Integer(i) => {
                     write!(out, "{i}")?;
@@ -95,19 +139,19 @@ impl VCardEntry {
                     if types.is_none() {
                         types = Some(v);
                     }
-                    write_param_value(out, &mut line_len, v.as_str())?;
+                    write_param_value(out, &mut line_len, fold_width, v.as_str())?;
                 }
                 VCardParameterValue::Type(v) => {
-                    write_param_value(out, &mut line_len, v.as_str())?;
+                    write_param_value(out, &mut line_len, fold_width, v.as_str())?;
                 }
                 VCardParameterValue::Calscale(v) => {
-                    write_param_value(out, &mut line_len, v.as_str())?;
+                    write_param_value(out, &mut line_len, fold_width, v.as_str())?;
                 }
                 VCardParameterValue::Level(v) => {
-                    write_param_value(out, &mut line_len, v.as_str())?;
+                    write_param_value(out, &mut line_len, fold_width, v.as_str())?;
                 }
                 VCardParameterValue::Phonetic(v) => {
-                    write_param_value(out, &mut line_len, v.as_str())?;
+                    write_param_value(out, &mut line_len, fold_width, v.as_str())?;
                 }
                 VCardParameterValue::Jscomps(v) => {
                     out.write_str("\"")?;
@@ -137,7 +181,7 @@ impl VCardEntry {
                 VCardValue::Component(items) => items.iter().any(|s| !s.is_ascii()),
                 _ => false,
             }) {
-                if line_len + 14 > 75 {
+                if line_len + 14 > fold_width {
                     write!(out, "\r\n ")?;
                     line_len = 1;
                 }
@@ -168,20 +212,41 @@ impl VCardEntry {
             _ => {}
         }
 
-        for (pos, value) in self.values.iter().enumerate() {
+        // Components past the five defined by RFC 6350 are only written when populated
+        let values = match self.name {
+            VCardProperty::N => {
+                let len = self
+                    .values
+                    .iter()
+                    .rposition(|value| !value.is_empty())
+                    .map_or(0, |pos| pos + 1)
+                    .max(5);
+                &self.values[..len.min(self.values.len())]
+            }
+            _ => self.values.as_slice(),
+        };
+
+        for (pos, value) in values.iter().enumerate() {
             if pos > 0 {
                 write!(out, "{separator}")?;
                 line_len += 1;
             }
 
-            if line_len + 1 > 75 {
+            if line_len + 1 > fold_width {
                 write!(out, "\r\n ")?;
                 line_len = 1;
             }
 
             match value {
                 VCardValue::Text(v) => {
-                    write_text(out, &mut line_len, v, escape_semicolon, escape_comma)?;
+                    write_text(
+                        out,
+                        &mut line_len,
+                        fold_width,
+                        v,
+                        escape_semicolon,
+                        escape_comma,
+                    )?;
                 }
                 VCardValue::Component(v) => {
                     for (pos, item) in v.iter().enumerate() {
@@ -189,7 +254,7 @@ impl VCardEntry {
                             write!(out, ",")?;
                             line_len += 1;
                         }
-                        write_text(out, &mut line_len, item, true, true)?;
+                        write_text(out, &mut line_len, fold_width, item, true, true)?;
                     }
                 }
                 VCardValue::Integer(v) => {
@@ -230,10 +295,10 @@ impl VCardEntry {
                             write!(out, "{ct};")?;
                             line_len += ct.len() + 1;
                         }
-                        write!(out, "base64\\,")?;
-                        line_len += 8;
+                        write!(out, "base64,")?;
+                        line_len += 7;
                     }
-                    write_bytes(out, Some(&mut line_len), &v.data)?;
+                    write_bytes(out, Some(&mut line_len), fold_width, &v.data)?;
                 }
                 VCardValue::Sex(v) => {
                     let text = v.as_str();
@@ -509,6 +574,157 @@ impl PartialDateTime {
     }
 }
 
+impl VCard {
+    pub fn write_version(&self, version: VCardVersion) -> String {
+        let mut out = String::new();
+        let _ = if matches!(version, VCardVersion::V4_0) {
+            self.write_to(&mut out, version)
+        } else {
+            self.to_legacy().write_to(&mut out, version)
+        };
+        out
+    }
+
+    fn to_legacy(&self) -> VCard {
+        let mut entries = Vec::with_capacity(self.entries.len());
+
+        for original in &self.entries {
+            let mut entry = original.clone();
+
+            // vCard 3.0 has no preference order, only the most preferred entry
+            // of each property is marked with TYPE=PREF
+            let is_preferred = original.pref().is_some()
+                && self
+                    .preferred(&original.name)
+                    .is_some_and(|preferred| std::ptr::eq(preferred, original));
+
+            match &entry.name {
+                VCardProperty::Kind
+                | VCardProperty::Xml
+                | VCardProperty::Member
+                | VCardProperty::Clientpidmap
+                | VCardProperty::Gramgender
+                | VCardProperty::Jsprop => continue,
+                VCardProperty::Anniversary
+                | VCardProperty::Gender
+                | VCardProperty::Lang
+                | VCardProperty::Related
+                | VCardProperty::Birthplace
+                | VCardProperty::Deathplace
+                | VCardProperty::Deathdate
+                | VCardProperty::Expertise
+                | VCardProperty::Hobby
+                | VCardProperty::Interest
+                | VCardProperty::OrgDirectory
+                | VCardProperty::ContactUri
+                | VCardProperty::Created
+                | VCardProperty::Language
+                | VCardProperty::Pronouns
+                | VCardProperty::Socialprofile => {
+                    // Keep date values formatted as dates once the property is renamed
+                    if entry
+                        .parameters(&VCardParameterName::Value)
+                        .next()
+                        .is_none()
+                        && let Some(VCardValue::PartialDateTime(dt)) = entry.values.first()
+                    {
+                        let value_type = if dt.has_date() && dt.has_time() {
+                            VCardValueType::DateTime
+                        } else if dt.has_time() {
+                            VCardValueType::Time
+                        } else {
+                            VCardValueType::Date
+                        };
+                        entry.add_param(VCardParameter::value(value_type));
+                    }
+                    entry.name = VCardProperty::Other(format!("X-{}", entry.name.as_str()));
+                }
+                VCardProperty::N => {
+                    entry.values.truncate(5);
+                }
+                VCardProperty::Adr => {
+                    entry.values.truncate(7);
+                }
+                VCardProperty::Tel if entry.is_type(&VCardValueType::Uri) => {
+                    for value in &mut entry.values {
+                        if let VCardValue::Text(text) = value
+                            && let Some(number) = text
+                                .get(..4)
+                                .filter(|scheme| scheme.eq_ignore_ascii_case("tel:"))
+                                .map(|_| text[4..].to_string())
+                        {
+                            *text = number;
+                        }
+                    }
+                    entry.params.retain(|param| {
+                        !matches!(
+                            param.value,
+                            VCardParameterValue::ValueType(VCardValueType::Uri)
+                        )
+                    });
+                }
+                _ => {}
+            }
+
+            let mut label = None;
+            let params = std::mem::take(&mut entry.params);
+            for param in params {
+                match (&param.name, &param.value) {
+                    (VCardParameterName::Pref, _) if is_preferred => {
+                        entry.add_param(VCardParameter::typ(VCardParameterValue::Text(
+                            "PREF".to_string(),
+                        )));
+                    }
+                    (
+                        VCardParameterName::Value,
+                        VCardParameterValue::ValueType(
+                            VCardValueType::DateAndOrTime
+                            | VCardValueType::Timestamp
+                            | VCardValueType::LanguageTag,
+                        ),
+                    ) => {}
+                    (VCardParameterName::Label, VCardParameterValue::Text(text))
+                        if entry.name == VCardProperty::Adr =>
+                    {
+                        label = Some(text.clone());
+                    }
+                    (
+                        VCardParameterName::Language
+                        | VCardParameterName::Value
+                        | VCardParameterName::Type
+                        | VCardParameterName::Other(_),
+                        _,
+                    ) => {
+                        entry.params.push(param);
+                    }
+                    _ => {}
+                }
+            }
+
+            // vCard 3.0 carries address labels as a separate property
+            if let Some(label) = label {
+                let label = VCardEntry::new(VCardProperty::Other("LABEL".to_string()))
+                    .with_group(entry.group.clone())
+                    .with_params(
+                        entry
+                            .params
+                            .iter()
+                            .filter(|param| param.name == VCardParameterName::Type)
+                            .cloned()
+                            .collect(),
+                    )
+                    .with_value(label);
+                entries.push(entry);
+                entries.push(label);
+            } else {
+                entries.push(entry);
+            }
+        }
+
+        VCard { entries }
+    }
+}
+
 impl Display for VCard {
     fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
         self.write_to(f, self.version().unwrap_or_default())
@@ -132,6 +132,7 @@ fn do_convert(input: &str) -> ConvertResult {
                 ConvertResult::error(format!("Unterminated component: {component}"))
             }
             Entry::TooManyComponents => ConvertResult::error("Too many components."),
+            Entry::TooDeeplyNested => ConvertResult::error("Components are nested too deeply."),
             Entry::Eof => ConvertResult::error("Unexpected end of file."),
             _ => ConvertResult::error(
                 "Unrecognized format. Please provide a valid iCalendar, JSCalendar, vCard or JSContact document.",